@@ -0,0 +1,295 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serenity::builder::CreateEmbed;
+use serenity::model::prelude::{GuildId, MessageId};
+use serenity::prelude::TypeMapKey;
+use serenity::utils::Colour;
+
+use crate::config::EMBED_COLOR;
+use crate::store::Store;
+
+/// Accepts both `"https://..."` and Discord's `{"url": "https://..."}` shape.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ImageSpec {
+    Url(String),
+    Object { url: String },
+}
+
+impl ImageSpec {
+    fn url(&self) -> &str {
+        match self {
+            ImageSpec::Url(url) | ImageSpec::Object { url } => url,
+        }
+    }
+}
+
+/// Accepts both `"text"` and Discord's `{"text": "...", "icon_url": "..."}` shape.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FooterSpec {
+    Text(String),
+    Object { text: String, icon_url: Option<String> },
+}
+
+impl FooterSpec {
+    fn text(&self) -> &str {
+        match self {
+            FooterSpec::Text(text) | FooterSpec::Object { text, .. } => text,
+        }
+    }
+}
+
+/// Accepts a colour as a number or as a `#rrggbb` string.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ColourSpec {
+    Value(u32),
+    Hex(String),
+}
+
+impl ColourSpec {
+    fn colour(&self) -> Option<Colour> {
+        match self {
+            ColourSpec::Value(value) => Some(Colour::new(*value)),
+            ColourSpec::Hex(hex) => parse_colour(hex),
+        }
+    }
+}
+
+/// Parses `#ff8800` or `ff8800`.
+pub fn parse_colour(input: &str) -> Option<Colour> {
+    let hex = input.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok().map(Colour::new)
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FieldSpec {
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub inline: bool,
+}
+
+/// An embed as written by staff, in JSON or through the wizard.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbedSpec {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    #[serde(alias = "colour")]
+    pub color: Option<ColourSpec>,
+    pub fields: Vec<FieldSpec>,
+    pub image: Option<ImageSpec>,
+    pub thumbnail: Option<ImageSpec>,
+    pub footer: Option<FooterSpec>,
+}
+
+impl EmbedSpec {
+    /// Parses JSON, allowing it to be wrapped in a code block.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let json = input.trim().trim_start_matches("```json").trim_start_matches("```").trim_end_matches("```");
+        let spec: EmbedSpec = serde_json::from_str(json).map_err(|err| format!("Invalid embed JSON: {}", err))?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Checks the limits Discord would otherwise reject the embed for.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.title.is_none() && self.description.is_none() && self.fields.is_empty() && self.image.is_none() {
+            return Err("The embed needs at least a title, description, field or image.".to_string());
+        }
+        if self.title.as_ref().is_some_and(|title| title.chars().count() > 256) {
+            return Err("Titles can be at most 256 characters.".to_string());
+        }
+        if self.description.as_ref().is_some_and(|description| description.chars().count() > 4096) {
+            return Err("Descriptions can be at most 4096 characters.".to_string());
+        }
+        if self.fields.len() > 25 {
+            return Err("Embeds can have at most 25 fields.".to_string());
+        }
+        if self.fields.iter().any(|field| field.name.chars().count() > 256 || field.value.chars().count() > 1024) {
+            return Err("Field names can be at most 256 characters and values at most 1024.".to_string());
+        }
+        if self.footer.as_ref().is_some_and(|footer| footer.text().chars().count() > 2048) {
+            return Err("Footers can be at most 2048 characters.".to_string());
+        }
+        if self.text_length() > 6000 {
+            return Err("Embeds can have at most 6000 characters of text in total.".to_string());
+        }
+        if self.color.as_ref().is_some_and(|colour| colour.colour().is_none()) {
+            return Err("Colours must be written as `#rrggbb`.".to_string());
+        }
+        Ok(())
+    }
+
+    /// Characters counted towards Discord's total embed limit.
+    fn text_length(&self) -> usize {
+        let count = |text: &str| text.chars().count();
+        self.title.as_deref().map_or(0, count)
+            + self.description.as_deref().map_or(0, count)
+            + self.fields.iter().map(|field| count(&field.name) + count(&field.value)).sum::<usize>()
+            + self.footer.as_ref().map_or(0, |footer| count(footer.text()))
+    }
+
+    /// Replaces the `{user}` and `{server}` placeholders in every piece of text.
+    pub fn fill(&self, user: &str, server: &str) -> Self {
+        let fill = |text: &str| text.replace("{user}", user).replace("{server}", server);
+        let mut spec = self.clone();
+        spec.title = spec.title.as_deref().map(fill);
+        spec.description = spec.description.as_deref().map(fill);
+        for field in &mut spec.fields {
+            field.name = fill(&field.name);
+            field.value = fill(&field.value);
+        }
+        spec.footer = spec.footer.map(|footer| match footer {
+            FooterSpec::Text(text) => FooterSpec::Text(fill(&text)),
+            FooterSpec::Object { text, icon_url } => FooterSpec::Object { text: fill(&text), icon_url },
+        });
+        spec
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    pub fn build(&self) -> CreateEmbed {
+        let mut embed = CreateEmbed::default();
+        if let Some(title) = &self.title {
+            embed.title(title);
+        }
+        if let Some(description) = &self.description {
+            embed.description(description);
+        }
+        if let Some(url) = &self.url {
+            embed.url(url);
+        }
+        embed.color(self.color.as_ref().and_then(ColourSpec::colour).unwrap_or(EMBED_COLOR));
+        for field in &self.fields {
+            embed.field(&field.name, &field.value, field.inline);
+        }
+        if let Some(image) = &self.image {
+            embed.image(image.url());
+        }
+        if let Some(thumbnail) = &self.thumbnail {
+            embed.thumbnail(thumbnail.url());
+        }
+        match &self.footer {
+            Some(FooterSpec::Text(text)) => {
+                embed.footer(|f| f.text(text));
+            }
+            Some(FooterSpec::Object { text, icon_url }) => {
+                embed.footer(|f| {
+                    f.text(text);
+                    if let Some(icon_url) = icon_url {
+                        f.icon_url(icon_url);
+                    }
+                    f
+                });
+            }
+            None => {}
+        }
+        embed
+    }
+}
+
+/// Most announcements remembered per guild for `rembed edit`.
+const MAX_POSTED: usize = 1000;
+
+/// Embeds posted with `rembed`, per guild, oldest first. `rembed edit` refuses to touch
+/// anything else the bot posted, like mod log entries or ticket panels.
+pub struct PostedEmbeds;
+
+impl TypeMapKey for PostedEmbeds {
+    type Value = Arc<Store<HashMap<GuildId, VecDeque<MessageId>>>>;
+}
+
+/// Remembers an embed posted with `rembed`, forgetting the oldest past [`MAX_POSTED`].
+pub fn remember(posted: &mut VecDeque<MessageId>, message_id: MessageId) {
+    posted.push_back(message_id);
+    if posted.len() > MAX_POSTED {
+        posted.pop_front();
+    }
+}
+
+/// Saved embed templates per guild, by lowercase name.
+pub struct EmbedTemplates;
+
+impl TypeMapKey for EmbedTemplates {
+    type Value = Arc<Store<HashMap<GuildId, HashMap<String, EmbedSpec>>>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_accepts_code_blocks_and_both_shapes() {
+        let spec = EmbedSpec::parse(
+            "```json\n{\"title\": \"Hi\", \"colour\": \"#ff8800\", \"image\": {\"url\": \"https://a.png\"}, \
+             \"footer\": \"Bye\", \"fields\": [{\"name\": \"A\", \"value\": \"B\"}]}\n```",
+        )
+        .expect("valid embed");
+        assert_eq!(spec.title.as_deref(), Some("Hi"));
+        assert_eq!(spec.color.as_ref().and_then(ColourSpec::colour), Some(Colour::new(0xff8800)));
+        assert_eq!(spec.image.as_ref().map(ImageSpec::url), Some("https://a.png"));
+        assert_eq!(spec.footer.as_ref().map(FooterSpec::text), Some("Bye"));
+        assert!(!spec.fields[0].inline);
+
+        let spec = EmbedSpec::parse(r#"{"description": "x", "color": 255, "footer": {"text": "f", "icon_url": null}}"#).expect("valid embed");
+        assert_eq!(spec.color.as_ref().and_then(ColourSpec::colour), Some(Colour::new(255)));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_embeds() {
+        assert!(matches!(EmbedSpec::parse("not json"), Err(err) if err.starts_with("Invalid embed JSON")));
+        assert!(EmbedSpec::parse("{}").is_err());
+        assert!(EmbedSpec::parse(r##"{"title": "x", "color": "#12"}"##).is_err());
+        assert!(EmbedSpec::parse(&format!(r#"{{"title": "{}"}}"#, "a".repeat(257))).is_err());
+        assert!(EmbedSpec::parse(&format!(r#"{{"title": "x", "footer": "{}"}}"#, "a".repeat(2049))).is_err());
+    }
+
+    #[test]
+    fn validate_enforces_the_total_length() {
+        let mut spec = EmbedSpec { description: Some("a".repeat(4000)), ..Default::default() };
+        spec.fields.push(FieldSpec { name: "n".to_string(), value: "v".repeat(1000), inline: false });
+        assert!(spec.validate().is_ok());
+        spec.fields.push(FieldSpec { name: "n".to_string(), value: "v".repeat(1000), inline: false });
+        assert_eq!(spec.validate().unwrap_err(), "Embeds can have at most 6000 characters of text in total.");
+    }
+
+    #[test]
+    fn remember_forgets_the_oldest_posts() {
+        let mut posted = VecDeque::new();
+        for id in 0..(MAX_POSTED as u64 + 5) {
+            remember(&mut posted, MessageId(id));
+        }
+        assert_eq!(posted.len(), MAX_POSTED);
+        assert!(!posted.contains(&MessageId(4)));
+        assert!(posted.contains(&MessageId(5)));
+    }
+
+    #[test]
+    fn fill_replaces_placeholders_everywhere() {
+        let spec = EmbedSpec {
+            title: Some("Welcome {user}".to_string()),
+            description: Some("to {server}, {user}!".to_string()),
+            url: Some("https://example.com/{user}".to_string()),
+            fields: vec![FieldSpec { name: "{server}".to_string(), value: "{user}".to_string(), inline: true }],
+            footer: Some(FooterSpec::Object { text: "{server}".to_string(), icon_url: None }),
+            ..Default::default()
+        };
+        let filled = spec.fill("Alice", "Rusty");
+        assert_eq!(filled.title.as_deref(), Some("Welcome Alice"));
+        assert_eq!(filled.description.as_deref(), Some("to Rusty, Alice!"));
+        assert_eq!(filled.url.as_deref(), Some("https://example.com/{user}"));
+        assert_eq!((filled.fields[0].name.as_str(), filled.fields[0].value.as_str()), ("Rusty", "Alice"));
+        assert_eq!(filled.footer.as_ref().map(FooterSpec::text), Some("Rusty"));
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::model::guild::VerificationLevel;
+use serenity::model::prelude::{ChannelId, GuildId, Member, RoleId, UserId};
+use serenity::model::Timestamp;
+use serenity::prelude::TypeMapKey;
+use tokio::sync::Mutex;
+
+use crate::store::Store;
+use crate::utils::truncate;
+use crate::{lockdown, modlog, settings, store};
+
+/// Reason attached to kicks while raid mode is active.
+const RAID_KICK_REASON: &str = "Raid mode is active: new joins are blocked";
+
+/// Most recent suspects kept per raid for the mod log and `raidmode status`.
+const MAX_SUSPECTS: usize = 50;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AntiRaidSettings {
+    /// Whether raid mode turns on automatically when the join rate is exceeded.
+    pub enabled: bool,
+    pub join_threshold: usize,
+    pub window_secs: u64,
+    /// Kick everyone who joins while raid mode is active.
+    pub kick_joins: bool,
+    /// Raise the server verification level while raid mode is active.
+    pub raise_verification: bool,
+    /// Lock the configured lockdown channels while raid mode is active.
+    pub lockdown: bool,
+    /// Automatically triggered raid mode ends after this long. 0 keeps it on until `raidmode off`.
+    pub auto_disable_secs: u64,
+    /// Role pinged when raid mode turns on.
+    pub alert_role: Option<RoleId>,
+}
+
+impl Default for AntiRaidSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            join_threshold: 10,
+            window_secs: 10,
+            kick_joins: true,
+            raise_verification: true,
+            lockdown: false,
+            auto_disable_secs: 15 * 60,
+            alert_role: None,
+        }
+    }
+}
+
+/// A member who joined during, or just before, a raid.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Suspect {
+    pub id: UserId,
+    pub tag: String,
+    pub created_at: Timestamp,
+}
+
+impl Suspect {
+    fn from_member(member: &Member) -> Self {
+        Self { id: member.user.id, tag: member.user.tag(), created_at: member.user.created_at() }
+    }
+}
+
+/// Raid mode state, persisted with what's needed to undo its measures. Suspects are kept in
+/// [`RaidSuspects`] instead, since kicks can't be undone and joins come in too fast to save each.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RaidMode {
+    pub since: Timestamp,
+    pub reason: String,
+    /// Verification level to restore when raid mode ends.
+    previous_verification: Option<VerificationLevel>,
+    /// Channels raid mode locked, to unlock when it ends.
+    locked_channels: Vec<ChannelId>,
+    /// When automatically triggered raid mode ends, as a unix timestamp.
+    expires_at: Option<i64>,
+}
+
+/// Active raid modes, persisted so their measures can still be undone after a restart.
+pub struct ActiveRaids;
+
+impl TypeMapKey for ActiveRaids {
+    type Value = Arc<Store<HashMap<GuildId, RaidMode>>>;
+}
+
+/// Accounts flagged during a guild's current raid mode.
+#[derive(Default)]
+pub struct Flagged {
+    /// The most recent suspects, oldest first.
+    pub suspects: VecDeque<Suspect>,
+    /// Every suspect flagged, including those no longer kept.
+    pub total: usize,
+}
+
+impl Flagged {
+    fn add(&mut self, suspect: Suspect) {
+        self.total += 1;
+        self.suspects.push_back(suspect);
+        if self.suspects.len() > MAX_SUSPECTS {
+            self.suspects.pop_front();
+        }
+    }
+}
+
+/// Suspects of each guild's active raid mode, kept in memory only.
+pub struct RaidSuspects;
+
+impl TypeMapKey for RaidSuspects {
+    type Value = Arc<Mutex<HashMap<GuildId, Flagged>>>;
+}
+
+/// Recent joins in each guild, used to spot raids.
+pub struct RecentJoins;
+
+impl TypeMapKey for RecentJoins {
+    type Value = Arc<Mutex<HashMap<GuildId, VecDeque<(Instant, Suspect)>>>>;
+}
+
+/// Tracks the join rate and enforces raid mode for a newly joined member.
+/// Returns `true` if the member was kicked.
+pub async fn on_member_join(ctx: &Context, member: &Member) -> bool {
+    let guild_id = member.guild_id;
+    let settings = settings::get(ctx, guild_id).await.antiraid;
+    let suspect = Suspect::from_member(member);
+    let now = Instant::now();
+
+    let recent = {
+        let joins = store::get::<RecentJoins>(ctx).await;
+        let mut joins = joins.lock().await;
+        let joins = joins.entry(guild_id).or_default();
+
+        let window = Duration::from_secs(settings.window_secs);
+        while joins.front().is_some_and(|(at, _)| now - *at >= window) {
+            joins.pop_front();
+        }
+        joins.push_back((now, suspect.clone()));
+        joins.iter().map(|(_, suspect)| suspect.clone()).collect::<Vec<_>>()
+    };
+
+    let raids = store::get::<ActiveRaids>(ctx).await;
+    let active = raids.read().await.contains_key(&guild_id);
+    if active {
+        let flagged = store::get::<RaidSuspects>(ctx).await;
+        flagged.lock().await.entry(guild_id).or_default().add(suspect.clone());
+        if settings.kick_joins {
+            kick(ctx, guild_id, &suspect).await;
+        }
+    } else if settings.enabled && recent.len() >= settings.join_threshold {
+        let reason = format!("{} joins within {} seconds", recent.len(), settings.window_secs);
+        activate(ctx, guild_id, reason, recent, true).await;
+    } else {
+        return false;
+    }
+
+    settings.kick_joins
+}
+
+async fn kick(ctx: &Context, guild_id: GuildId, suspect: &Suspect) {
+    if let Err(err) = guild_id.kick_with_reason(&ctx.http, suspect.id, RAID_KICK_REASON).await {
+        error!("Failed to kick {} during raid mode: {}", suspect.id, err);
+    }
+}
+
+/// Turns raid mode on. Returns `false` if it was already active.
+///
+/// Automatic activations expire after the configured duration; manual ones last until turned off.
+pub async fn activate(
+    ctx: &Context,
+    mut guild_id: GuildId,
+    reason: String,
+    suspects: Vec<Suspect>,
+    automatic: bool,
+) -> bool {
+    let settings = settings::get(ctx, guild_id).await;
+    let antiraid = &settings.antiraid;
+    let raids = store::get::<ActiveRaids>(ctx).await;
+
+    let since = Timestamp::now();
+    let expires_at = (automatic && antiraid.auto_disable_secs > 0)
+        .then(|| since.unix_timestamp().saturating_add(i64::try_from(antiraid.auto_disable_secs).unwrap_or(i64::MAX)));
+    let started = raids
+        .write(|raids| {
+            if raids.contains_key(&guild_id) {
+                return false;
+            }
+            raids.insert(
+                guild_id,
+                RaidMode {
+                    since,
+                    reason: reason.clone(),
+                    previous_verification: None,
+                    locked_channels: Vec::new(),
+                    expires_at,
+                },
+            );
+            true
+        })
+        .await;
+    match started {
+        Ok(true) => {}
+        Ok(false) => return false,
+        Err(err) => {
+            error!("Failed to save raid mode: {:#}", err);
+            return false;
+        }
+    }
+    warn!(guild_id = u64::from(guild_id), "Raid mode activated: {}", reason);
+    let flagged = store::get::<RaidSuspects>(ctx).await;
+    let mut entry = Flagged::default();
+    for suspect in &suspects {
+        entry.add(suspect.clone());
+    }
+    flagged.lock().await.insert(guild_id, entry);
+
+    let mut previous_verification = None;
+    if antiraid.raise_verification {
+        let current = guild_id.to_guild_cached(&ctx.cache).map(|guild| guild.verification_level);
+        if current.is_some_and(|level| level < VerificationLevel::High) {
+            match guild_id.edit(&ctx.http, |g| g.verification_level(VerificationLevel::High)).await {
+                Ok(_) => previous_verification = current,
+                Err(err) => error!("Failed to raise verification level: {}", err),
+            }
+        }
+    }
+
+    let mut locked_channels = Vec::new();
+    if antiraid.lockdown {
+        for channel_id in &settings.lockdown.channels {
+            match lockdown::lock(ctx, *channel_id).await {
+                Ok(true) => locked_channels.push(*channel_id),
+                Ok(false) => {}
+                Err(err) => error!("Failed to lock {} during raid mode: {}", channel_id, err),
+            }
+        }
+    }
+
+    let result = raids
+        .write(|raids| {
+            if let Some(raid) = raids.get_mut(&guild_id) {
+                raid.previous_verification = previous_verification;
+                raid.locked_channels = locked_channels.clone();
+            }
+        })
+        .await;
+    if let Err(err) = result {
+        error!("Failed to save raid mode measures: {:#}", err);
+    }
+
+    if antiraid.kick_joins {
+        for suspect in &suspects {
+            kick(ctx, guild_id, suspect).await;
+        }
+    }
+
+    let mut measures = Vec::new();
+    if antiraid.kick_joins {
+        measures.push("kicking new joins".to_string());
+    }
+    if previous_verification.is_some() {
+        measures.push("verification level raised to High".to_string());
+    }
+    if !locked_channels.is_empty() {
+        measures.push(format!("{} channels locked", locked_channels.len()));
+    }
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Raid Mode Activated");
+    embed.field("Trigger", &reason, false);
+    embed.field("Measures", if measures.is_empty() { "None".to_string() } else { measures.join(", ") }, false);
+    embed.field("Suspicious Accounts", suspect_list(&suspects), false);
+    modlog::alert(ctx, guild_id, antiraid.alert_role, embed).await;
+
+    if let Some(expires_at) = expires_at {
+        expire_at(ctx, guild_id, expires_at);
+    }
+
+    true
+}
+
+/// Ends raid mode once `expires_at` passes, unless it was turned off or restarted in the meantime.
+fn expire_at(ctx: &Context, guild_id: GuildId, expires_at: i64) {
+    let ctx = ctx.clone();
+    tokio::spawn(async move {
+        let delay = expires_at.saturating_sub(Timestamp::now().unix_timestamp());
+        tokio::time::sleep(Duration::from_secs(u64::try_from(delay).unwrap_or_default())).await;
+        deactivate_if(&ctx, guild_id, true).await;
+    });
+}
+
+/// Picks raid mode expiry back up after a restart. Raid modes that expired while the bot was
+/// offline end straight away.
+pub async fn resume(ctx: &Context) {
+    let raids = store::get::<ActiveRaids>(ctx).await;
+    let expiring = raids
+        .read()
+        .await
+        .iter()
+        .filter_map(|(guild_id, raid)| raid.expires_at.map(|expires_at| (*guild_id, expires_at)))
+        .collect::<Vec<_>>();
+    for (guild_id, expires_at) in expiring {
+        expire_at(ctx, guild_id, expires_at);
+    }
+}
+
+/// Turns raid mode off, undoing its measures. Returns `false` if it wasn't active.
+pub async fn deactivate(ctx: &Context, guild_id: GuildId) -> bool {
+    deactivate_if(ctx, guild_id, false).await
+}
+
+/// Deactivates raid mode, but only once it has expired if `expired_only` is set.
+async fn deactivate_if(ctx: &Context, mut guild_id: GuildId, expired_only: bool) -> bool {
+    let now = Timestamp::now().unix_timestamp();
+    let raids = store::get::<ActiveRaids>(ctx).await;
+    let removed = raids
+        .write(|raids| {
+            let raid = raids.get(&guild_id)?;
+            if expired_only && raid.expires_at.is_none_or(|expires_at| expires_at > now) {
+                return None;
+            }
+            raids.remove(&guild_id)
+        })
+        .await;
+    let raid = match removed {
+        Ok(Some(raid)) => raid,
+        Ok(None) => return false,
+        Err(err) => {
+            error!("Failed to save the end of raid mode: {:#}", err);
+            return false;
+        }
+    };
+    info!(guild_id = u64::from(guild_id), "Raid mode deactivated");
+    let flagged = store::get::<RaidSuspects>(ctx).await;
+    let flagged = flagged.lock().await.remove(&guild_id).unwrap_or_default();
+
+    if let Some(level) = raid.previous_verification {
+        if let Err(err) = guild_id.edit(&ctx.http, |g| g.verification_level(level)).await {
+            error!("Failed to restore verification level: {}", err);
+        }
+    }
+    for channel_id in &raid.locked_channels {
+        if let Err(err) = lockdown::unlock(ctx, *channel_id).await {
+            error!("Failed to unlock {} after raid mode: {}", channel_id, err);
+        }
+    }
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Raid Mode Deactivated");
+    embed.field("Active Since", format!("<t:{}:R>", raid.since.unix_timestamp()), true);
+    embed.field("Accounts Flagged", flagged.total, true);
+    if expired_only {
+        embed.description("Raid mode expired automatically.");
+    }
+    modlog::log(ctx, guild_id, embed).await;
+
+    true
+}
+
+/// Returns the guild's raid mode state, if it is active.
+pub async fn current(ctx: &Context, guild_id: GuildId) -> Option<RaidMode> {
+    let raids = store::get::<ActiveRaids>(ctx).await;
+    let raids = raids.read().await;
+    raids.get(&guild_id).cloned()
+}
+
+/// The guild's most recent raid suspects and how many were flagged in total.
+pub async fn suspects(ctx: &Context, guild_id: GuildId) -> (Vec<Suspect>, usize) {
+    let flagged = store::get::<RaidSuspects>(ctx).await;
+    let flagged = flagged.lock().await;
+    flagged
+        .get(&guild_id)
+        .map_or_else(Default::default, |flagged| (flagged.suspects.iter().cloned().collect(), flagged.total))
+}
+
+/// Formats suspicious accounts with their account age, newest accounts first.
+pub fn suspect_list(suspects: &[Suspect]) -> String {
+    if suspects.is_empty() {
+        return "None".to_string();
+    }
+
+    let mut suspects = suspects.to_vec();
+    suspects.sort_by_key(|suspect| std::cmp::Reverse(suspect.created_at.unix_timestamp()));
+    let list = suspects
+        .iter()
+        .map(|suspect| format!("{} ({}), created <t:{}:R>", suspect.tag, suspect.id, suspect.created_at.unix_timestamp()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    truncate(&list, 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flagged_keeps_the_most_recent_suspects() {
+        let mut flagged = Flagged::default();
+        for id in 0..(MAX_SUSPECTS as u64 + 10) {
+            flagged.add(Suspect { id: UserId(id), tag: format!("user#{}", id), created_at: Timestamp::now() });
+        }
+        assert_eq!(flagged.total, MAX_SUSPECTS + 10);
+        assert_eq!(flagged.suspects.len(), MAX_SUSPECTS);
+        assert_eq!(flagged.suspects.front().map(|suspect| suspect.id), Some(UserId(10)));
+    }
+}
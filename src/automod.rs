@@ -0,0 +1,449 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::model::prelude::{ChannelId, GuildId, Message, MessageId, RoleId, User, UserId};
+use serenity::model::Timestamp;
+use serenity::prelude::TypeMapKey;
+use tokio::sync::Mutex;
+
+use crate::cases::{self, CaseKind};
+use crate::utils::truncate;
+use crate::{modlog, settings, store};
+
+/// How long message history is kept per user. Duplicates are counted over this window.
+const HISTORY_WINDOW: Duration = Duration::from_secs(60);
+/// Once this many users are tracked, idle histories are dropped.
+const MAX_TRACKED_USERS: usize = 10_000;
+/// Messages shorter than this (in letters) are never flagged for caps.
+const MIN_CAPS_LETTERS: usize = 10;
+
+static CUSTOM_EMOJI: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<a?:\w+:\d+>").expect("custom emoji pattern should be valid"));
+
+/// What automod does when a rule is broken. Every action except `Off` deletes the message.
+///
+/// Variants are ordered by severity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutomodAction {
+    Off,
+    Delete,
+    Warn,
+    Timeout,
+    Kick,
+}
+
+impl AutomodAction {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "delete" => Some(Self::Delete),
+            "warn" => Some(Self::Warn),
+            "timeout" => Some(Self::Timeout),
+            "kick" => Some(Self::Kick),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AutomodAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Off => "off",
+            Self::Delete => "delete",
+            Self::Warn => "warn",
+            Self::Timeout => "timeout",
+            Self::Kick => "kick",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A rule that triggers once a per-message or per-window count reaches `limit`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ThresholdRule {
+    pub limit: usize,
+    pub action: AutomodAction,
+}
+
+impl ThresholdRule {
+    const fn new(limit: usize, action: AutomodAction) -> Self {
+        Self { limit, action }
+    }
+
+    fn triggered(&self, count: usize) -> bool {
+        self.action != AutomodAction::Off && count >= self.limit
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutomodSettings {
+    pub enabled: bool,
+    /// Messages allowed per `flood_window_secs` before `flood` triggers.
+    pub flood: ThresholdRule,
+    pub flood_window_secs: u64,
+    /// User and role mentions in a single message.
+    pub mentions: ThresholdRule,
+    /// Identical messages sent within the history window.
+    pub duplicates: ThresholdRule,
+    /// Percentage of uppercase letters in a message.
+    pub caps: ThresholdRule,
+    /// Unicode and custom emoji in a single message.
+    pub emoji: ThresholdRule,
+    pub timeout_secs: u64,
+    pub exempt_roles: Vec<RoleId>,
+}
+
+impl Default for AutomodSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            flood: ThresholdRule::new(6, AutomodAction::Timeout),
+            flood_window_secs: 5,
+            mentions: ThresholdRule::new(6, AutomodAction::Timeout),
+            duplicates: ThresholdRule::new(4, AutomodAction::Delete),
+            caps: ThresholdRule::new(80, AutomodAction::Delete),
+            emoji: ThresholdRule::new(15, AutomodAction::Delete),
+            timeout_secs: 600,
+            exempt_roles: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Violation {
+    Flood,
+    MassMention,
+    Duplicate,
+    Caps,
+    Emoji,
+}
+
+impl Violation {
+    fn describe(self) -> &'static str {
+        match self {
+            Self::Flood => "Sending messages too quickly",
+            Self::MassMention => "Mass mentions",
+            Self::Duplicate => "Repeated messages",
+            Self::Caps => "Excessive caps",
+            Self::Emoji => "Excessive emoji",
+        }
+    }
+
+    fn rule(self, settings: &AutomodSettings) -> ThresholdRule {
+        match self {
+            Self::Flood => settings.flood,
+            Self::MassMention => settings.mentions,
+            Self::Duplicate => settings.duplicates,
+            Self::Caps => settings.caps,
+            Self::Emoji => settings.emoji,
+        }
+    }
+}
+
+/// The parts of a message automod looks at.
+pub struct Sample<'a> {
+    pub content: &'a str,
+    pub mentions: usize,
+    pub at: Instant,
+}
+
+struct Entry {
+    content: String,
+    at: Instant,
+}
+
+/// Sliding-window message history for every user automod has seen recently.
+#[derive(Default)]
+pub struct Tracker {
+    histories: HashMap<(GuildId, UserId), VecDeque<Entry>>,
+    /// Until when a user's ongoing flood was already acted on, so one burst is one case.
+    flood_cooldowns: HashMap<(GuildId, UserId), Instant>,
+}
+
+impl Tracker {
+    /// Records a message and returns the first rule it breaks, checking mass mentions, flood,
+    /// duplicates, caps and emoji in that order.
+    ///
+    /// After a flood is reported, further flooding is ignored until the user has been quiet
+    /// for a whole flood window.
+    pub fn record(
+        &mut self,
+        guild_id: GuildId,
+        user_id: UserId,
+        sample: &Sample,
+        settings: &AutomodSettings,
+    ) -> Option<Violation> {
+        if self.histories.len() >= MAX_TRACKED_USERS {
+            self.histories
+                .retain(|_, history| history.back().is_some_and(|e| sample.at - e.at < HISTORY_WINDOW));
+            self.flood_cooldowns.retain(|_, until| *until > sample.at);
+        }
+
+        let history = self.histories.entry((guild_id, user_id)).or_default();
+        while history.front().is_some_and(|e| sample.at - e.at >= HISTORY_WINDOW) {
+            history.pop_front();
+        }
+        history.push_back(Entry { content: sample.content.to_string(), at: sample.at });
+
+        let flood_window = Duration::from_secs(settings.flood_window_secs);
+        let recent = history.iter().filter(|e| sample.at - e.at < flood_window).count();
+        let duplicates = if sample.content.is_empty() {
+            0
+        } else {
+            history.iter().filter(|e| e.content == sample.content).count()
+        };
+
+        let flooding = settings.flood.triggered(recent);
+        let cooling_down = self.flood_cooldowns.get(&(guild_id, user_id)).is_some_and(|until| sample.at < *until);
+        if flooding {
+            self.flood_cooldowns.insert((guild_id, user_id), sample.at + flood_window);
+        }
+        let recent = if cooling_down { 0 } else { recent };
+
+        let checks = [
+            (Violation::MassMention, sample.mentions),
+            (Violation::Flood, recent),
+            (Violation::Duplicate, duplicates),
+            (Violation::Caps, caps_percentage(sample.content)),
+            (Violation::Emoji, emoji_count(sample.content)),
+        ];
+
+        checks
+            .into_iter()
+            .find(|(violation, count)| violation.rule(settings).triggered(*count))
+            .map(|(violation, _)| violation)
+    }
+}
+
+/// Percentage of letters in `content` that are uppercase, or 0 for short messages.
+pub fn caps_percentage(content: &str) -> usize {
+    let letters = content.chars().filter(|c| c.is_alphabetic()).count();
+    if letters < MIN_CAPS_LETTERS {
+        return 0;
+    }
+
+    let upper = content.chars().filter(|c| c.is_uppercase()).count();
+    upper * 100 / letters
+}
+
+/// Counts custom emoji (`<:name:id>`) and common Unicode emoji in `content`.
+pub fn emoji_count(content: &str) -> usize {
+    let custom = CUSTOM_EMOJI.find_iter(content).count();
+    let stripped = CUSTOM_EMOJI.replace_all(content, "");
+
+    let unicode = stripped
+        .chars()
+        .filter(|c| matches!(*c as u32, 0x1F000..=0x1FAFF | 0x2600..=0x27BF))
+        .count();
+
+    custom + unicode
+}
+
+pub struct AutomodTracker;
+
+impl TypeMapKey for AutomodTracker {
+    type Value = Arc<Mutex<Tracker>>;
+}
+
+/// Runs automod against a guild message and punishes the author if it breaks a rule.
+pub async fn check_message(ctx: &Context, msg: &Message) {
+    let Some(guild_id) = msg.guild_id else { return };
+    if msg.author.bot {
+        return;
+    }
+
+    let settings = settings::get(ctx, guild_id).await.automod;
+    if !settings.enabled {
+        return;
+    }
+    if let Some(member) = &msg.member {
+        if member.roles.iter().any(|role| settings.exempt_roles.contains(role)) {
+            return;
+        }
+    }
+
+    let sample = Sample {
+        content: &msg.content,
+        mentions: msg.mentions.len() + msg.mention_roles.len() + usize::from(msg.mention_everyone),
+        at: Instant::now(),
+    };
+
+    let tracker = store::get::<AutomodTracker>(ctx).await;
+    let violation = tracker.lock().await.record(guild_id, msg.author.id, &sample, &settings);
+
+    if let Some(violation) = violation {
+        let offence = Offence {
+            guild_id,
+            channel_id: msg.channel_id,
+            message_id: msg.id,
+            author: &msg.author,
+            content: &msg.content,
+        };
+        punish(ctx, &offence, violation.rule(&settings).action, violation.describe(), "Automod").await;
+    }
+}
+
+/// A message that broke a moderation rule.
+pub struct Offence<'a> {
+    pub guild_id: GuildId,
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+    pub author: &'a User,
+    pub content: &'a str,
+}
+
+/// Deletes the offending message, applies `action` to its author and reports it to the mod log.
+pub async fn punish(ctx: &Context, offence: &Offence<'_>, action: AutomodAction, reason: &str, source: &str) {
+    let Offence { guild_id, channel_id, message_id, author, content } = *offence;
+    let timeout_secs = settings::get(ctx, guild_id).await.automod.timeout_secs;
+
+    if action != AutomodAction::Off {
+        if let Err(err) = channel_id.delete_message(&ctx.http, message_id).await {
+            error!("{} failed to delete message {}: {}", source, message_id, err);
+        }
+    }
+
+    let result = match action {
+        AutomodAction::Off | AutomodAction::Delete => Ok(()),
+        AutomodAction::Warn => channel_id
+            .say(&ctx.http, format!("<@{}>, please stop. {}.", author.id, reason))
+            .await
+            .map(|_| ()),
+        AutomodAction::Timeout => {
+            let until = Timestamp::now().unix_timestamp() + timeout_secs as i64;
+            match Timestamp::from_unix_timestamp(until) {
+                Ok(until) => guild_id
+                    .edit_member(&ctx.http, author.id, |m| m.disable_communication_until_datetime(until))
+                    .await
+                    .map(|_| ()),
+                Err(err) => {
+                    error!("Invalid automod timeout of {} seconds: {}", timeout_secs, err);
+                    Ok(())
+                }
+            }
+        }
+        AutomodAction::Kick => guild_id.kick_with_reason(&ctx.http, author.id, reason).await,
+    };
+
+    if let Err(err) = &result {
+        error!("{} failed to {} {}: {}", source, action, author.id, err);
+    }
+
+    let case_kind = match action {
+        AutomodAction::Warn => Some(CaseKind::Warn),
+        AutomodAction::Timeout => Some(CaseKind::Timeout),
+        AutomodAction::Kick => Some(CaseKind::Kick),
+        AutomodAction::Off | AutomodAction::Delete => None,
+    };
+    if let (Some(kind), Ok(())) = (case_kind, &result) {
+        cases::add_case(ctx, guild_id, kind, author.id, None, &format!("{}: {}", source, reason)).await;
+    }
+    info!(user_id = u64::from(author.id), "{}: {} ({})", source, reason, action);
+
+    let mut embed = CreateEmbed::default();
+    embed.title(format!("{} Action", source));
+    embed.field("User", format!("{} ({})", author.tag(), author.id), true);
+    embed.field("Channel", format!("<#{}>", channel_id), true);
+    embed.field("Action", if result.is_ok() { action.to_string() } else { format!("{} (failed)", action) }, true);
+    embed.field("Reason", reason, false);
+    if !content.is_empty() {
+        embed.field("Message", truncate(content, 1000), false);
+    }
+    modlog::log(ctx, guild_id, embed).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUILD: GuildId = GuildId(1);
+    const USER: UserId = UserId(2);
+
+    fn sample(content: &str, mentions: usize, at: Instant) -> Sample<'_> {
+        Sample { content, mentions, at }
+    }
+
+    #[test]
+    fn caps_percentage_ignores_short_messages() {
+        assert_eq!(caps_percentage("HELLO"), 0);
+        assert_eq!(caps_percentage("HELLO THERE"), 100);
+        assert_eq!(caps_percentage("Hello There Friend"), 18);
+    }
+
+    #[test]
+    fn emoji_count_counts_custom_and_unicode() {
+        assert_eq!(emoji_count("no emoji here"), 0);
+        assert_eq!(emoji_count("<:pog:123> <a:dance:456> 😀🔥"), 4);
+        assert_eq!(emoji_count("sunny ☀ day"), 1);
+    }
+
+    #[test]
+    fn record_flags_floods_within_the_window() {
+        let mut tracker = Tracker::default();
+        let settings = AutomodSettings { flood: ThresholdRule::new(3, AutomodAction::Timeout), ..Default::default() };
+        let start = Instant::now();
+
+        assert_eq!(tracker.record(GUILD, USER, &sample("a", 0, start), &settings), None);
+        assert_eq!(tracker.record(GUILD, USER, &sample("b", 0, start + Duration::from_secs(1)), &settings), None);
+        assert_eq!(
+            tracker.record(GUILD, USER, &sample("c", 0, start + Duration::from_secs(2)), &settings),
+            Some(Violation::Flood)
+        );
+        let later = start + Duration::from_secs(settings.flood_window_secs + 10);
+        assert_eq!(tracker.record(GUILD, USER, &sample("d", 0, later), &settings), None);
+    }
+
+    #[test]
+    fn record_reports_a_flood_once_per_burst() {
+        let mut tracker = Tracker::default();
+        let settings = AutomodSettings { flood: ThresholdRule::new(3, AutomodAction::Timeout), ..Default::default() };
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+
+        let flagged = (0..20)
+            .filter(|i| tracker.record(GUILD, USER, &sample(&i.to_string(), 0, at(i * 500)), &settings).is_some())
+            .count();
+        assert_eq!(flagged, 1);
+
+        // A new burst after a quiet window is reported again
+        let quiet = 20 * 500 + settings.flood_window_secs * 1000;
+        let flagged = (0..3)
+            .filter(|i| tracker.record(GUILD, USER, &sample(&i.to_string(), 0, at(quiet + i * 100)), &settings).is_some())
+            .count();
+        assert_eq!(flagged, 1);
+    }
+
+    #[test]
+    fn record_counts_duplicates_per_user() {
+        let mut tracker = Tracker::default();
+        let settings = AutomodSettings { duplicates: ThresholdRule::new(2, AutomodAction::Delete), ..Default::default() };
+        let start = Instant::now();
+
+        assert_eq!(tracker.record(GUILD, USER, &sample("spam", 0, start), &settings), None);
+        assert_eq!(tracker.record(GUILD, UserId(3), &sample("spam", 0, start), &settings), None);
+        assert_eq!(
+            tracker.record(GUILD, USER, &sample("spam", 0, start + Duration::from_secs(10)), &settings),
+            Some(Violation::Duplicate)
+        );
+    }
+
+    #[test]
+    fn record_prefers_mass_mentions_and_skips_disabled_rules() {
+        let mut tracker = Tracker::default();
+        let mut settings = AutomodSettings::default();
+        let shouting = "EVERYONE LOOK AT THIS";
+
+        assert_eq!(tracker.record(GUILD, USER, &sample(shouting, 10, Instant::now()), &settings), Some(Violation::MassMention));
+
+        settings.mentions.action = AutomodAction::Off;
+        assert_eq!(tracker.record(GUILD, UserId(3), &sample(shouting, 10, Instant::now()), &settings), Some(Violation::Caps));
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serenity::client::Context;
+use serenity::model::prelude::{GuildId, Member, UserId};
+use serenity::model::Timestamp;
+use serenity::prelude::TypeMapKey;
+
+use crate::store::{self, Store};
+
+/// Name changes kept per member; older entries are dropped.
+const NAME_HISTORY_LIMIT: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CaseKind {
+    Warn,
+    Timeout,
+    Kick,
+    Ban,
+}
+
+impl fmt::Display for CaseKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CaseKind::Warn => "Warn",
+            CaseKind::Timeout => "Timeout",
+            CaseKind::Kick => "Kick",
+            CaseKind::Ban => "Ban",
+        })
+    }
+}
+
+/// A moderation action taken against a member, by a moderator or automatically.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Case {
+    pub id: u32,
+    pub kind: CaseKind,
+    pub user_id: UserId,
+    /// `None` for actions taken by automod, the filter or the link scanner.
+    pub moderator: Option<UserId>,
+    pub reason: String,
+    pub created_at: Timestamp,
+}
+
+/// A private moderator note about a member.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Note {
+    pub id: u32,
+    pub user_id: UserId,
+    pub author: UserId,
+    pub text: String,
+    pub created_at: Timestamp,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NameKind {
+    Username,
+    Nickname,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NameChange {
+    pub kind: NameKind,
+    pub old: Option<String>,
+    pub new: Option<String>,
+    pub changed_at: Timestamp,
+}
+
+/// Everything moderators have on record for one guild.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildRecords {
+    next_case: u32,
+    next_note: u32,
+    pub cases: Vec<Case>,
+    pub notes: Vec<Note>,
+    pub names: HashMap<UserId, Vec<NameChange>>,
+}
+
+/// A snapshot of the records concerning a single member.
+pub struct UserRecords {
+    pub cases: Vec<Case>,
+    pub notes: Vec<Note>,
+    pub names: Vec<NameChange>,
+}
+
+pub struct Records;
+
+impl TypeMapKey for Records {
+    type Value = Arc<Store<HashMap<GuildId, GuildRecords>>>;
+}
+
+/// Records a moderation case and returns its number.
+pub async fn add_case(
+    ctx: &Context,
+    guild_id: GuildId,
+    kind: CaseKind,
+    user_id: UserId,
+    moderator: Option<UserId>,
+    reason: &str,
+) -> Option<u32> {
+    let records = store::get::<Records>(ctx).await;
+    let result = records
+        .write(|all| {
+            let guild = all.entry(guild_id).or_default();
+            guild.next_case += 1;
+            guild.cases.push(Case {
+                id: guild.next_case,
+                kind,
+                user_id,
+                moderator,
+                reason: reason.to_string(),
+                created_at: Timestamp::now(),
+            });
+            guild.next_case
+        })
+        .await;
+
+    match result {
+        Ok(id) => Some(id),
+        Err(err) => {
+            error!("Failed to record {} case for {}: {:?}", kind, user_id, err);
+            None
+        }
+    }
+}
+
+/// Adds a moderator note and returns its number.
+pub async fn add_note(ctx: &Context, guild_id: GuildId, user_id: UserId, author: UserId, text: &str) -> anyhow::Result<u32> {
+    let records = store::get::<Records>(ctx).await;
+    records
+        .write(|all| {
+            let guild = all.entry(guild_id).or_default();
+            guild.next_note += 1;
+            guild.notes.push(Note {
+                id: guild.next_note,
+                user_id,
+                author,
+                text: text.to_string(),
+                created_at: Timestamp::now(),
+            });
+            guild.next_note
+        })
+        .await
+}
+
+/// Deletes a note, returning whether it existed.
+pub async fn remove_note(ctx: &Context, guild_id: GuildId, id: u32) -> anyhow::Result<bool> {
+    let records = store::get::<Records>(ctx).await;
+    records
+        .write(|all| {
+            let Some(guild) = all.get_mut(&guild_id) else {
+                return false;
+            };
+            let before = guild.notes.len();
+            guild.notes.retain(|note| note.id != id);
+            guild.notes.len() != before
+        })
+        .await
+}
+
+/// Returns the cases, notes and name history for one member, oldest first.
+pub async fn for_user(ctx: &Context, guild_id: GuildId, user_id: UserId) -> UserRecords {
+    let records = store::get::<Records>(ctx).await;
+    let all = records.read().await;
+    let Some(guild) = all.get(&guild_id) else {
+        return UserRecords { cases: Vec::new(), notes: Vec::new(), names: Vec::new() };
+    };
+
+    UserRecords {
+        cases: guild.cases.iter().filter(|case| case.user_id == user_id).cloned().collect(),
+        notes: guild.notes.iter().filter(|note| note.user_id == user_id).cloned().collect(),
+        names: guild.names.get(&user_id).cloned().unwrap_or_default(),
+    }
+}
+
+/// Remembers username and nickname changes between two versions of a member.
+pub async fn on_member_update(ctx: &Context, old: &Member, new: &Member) {
+    let mut changes = Vec::new();
+    if old.user.name != new.user.name {
+        changes.push(NameChange {
+            kind: NameKind::Username,
+            old: Some(old.user.name.clone()),
+            new: Some(new.user.name.clone()),
+            changed_at: Timestamp::now(),
+        });
+    }
+    if old.nick != new.nick {
+        changes.push(NameChange {
+            kind: NameKind::Nickname,
+            old: old.nick.clone(),
+            new: new.nick.clone(),
+            changed_at: Timestamp::now(),
+        });
+    }
+    if changes.is_empty() {
+        return;
+    }
+
+    let records = store::get::<Records>(ctx).await;
+    let result = records
+        .write(|all| {
+            let history = all.entry(new.guild_id).or_default().names.entry(new.user.id).or_default();
+            history.extend(changes);
+            let excess = history.len().saturating_sub(NAME_HISTORY_LIMIT);
+            history.drain(..excess);
+        })
+        .await;
+
+    if let Err(err) = result {
+        error!("Failed to record name change for {}: {:?}", new.user.id, err);
+    }
+}
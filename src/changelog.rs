@@ -0,0 +1,154 @@
+use std::env;
+use std::sync::{Arc, LazyLock};
+
+use serde::{Deserialize, Serialize};
+use serenity::builder::CreateEmbed;
+use serenity::prelude::TypeMapKey;
+use serenity::CacheAndHttp;
+use tracing::{error, info, warn};
+
+use crate::config::EMBED_COLOR;
+use crate::settings::Settings;
+use crate::store::Store;
+use crate::utils::truncate;
+
+/// The changelog as it was when the bot was built.
+const CHANGELOG: &str = include_str!("../CHANGELOG.md");
+
+/// The version being run, as set in `Cargo.toml`.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Set to `true` to announce new versions to the channels servers subscribed with `rconfig changelog`.
+const ANNOUNCE_VAR: &str = "ANNOUNCE_CHANGELOG";
+
+/// One `## <version>` section of the changelog.
+pub struct Release {
+    pub version: &'static str,
+    pub notes: &'static str,
+}
+
+impl Release {
+    pub fn embed(&self) -> CreateEmbed {
+        let mut embed = CreateEmbed::default();
+        embed.title(format!("What's new in {}", self.version));
+        embed.description(truncate(self.notes, 4000));
+        embed.color(EMBED_COLOR);
+        embed
+    }
+}
+
+/// Releases, newest first.
+pub static RELEASES: LazyLock<Vec<Release>> = LazyLock::new(|| parse(CHANGELOG));
+
+/// Splits the changelog into releases at each `## ` heading, accepting `## 1.2.0`,
+/// `## v1.2.0` and `## [1.2.0] - 2024-01-01`. An `Unreleased` section is left out.
+fn parse(changelog: &'static str) -> Vec<Release> {
+    let mut sections = changelog.split("\n## ");
+    // Anything before the first heading is a preamble, unless the file starts with one
+    let first = sections.next().and_then(|preamble| preamble.strip_prefix("## "));
+    first
+        .into_iter()
+        .chain(sections)
+        .filter_map(|section| {
+            let (heading, notes) = section.split_once('\n').unwrap_or((section, ""));
+            let heading = heading.trim_end_matches('\r');
+            let version = heading.split(" - ").next()?.trim().trim_start_matches('[').trim_end_matches(']');
+            let version = version.strip_prefix('v').unwrap_or(version);
+            (!version.eq_ignore_ascii_case("unreleased")).then(|| Release { version, notes: notes.trim() })
+        })
+        .collect()
+}
+
+/// The notes for the running [`VERSION`].
+pub fn current() -> Option<&'static Release> {
+    RELEASES.iter().find(|release| release.version == VERSION)
+}
+
+/// Finds a release by version, so `0.19`, `0.19.0` and `v0.19.0` all match `0.19.0`.
+pub fn find(version: &str) -> Option<&'static Release> {
+    let version = version.trim().trim_start_matches('v');
+    RELEASES.iter().find(|release| release.version == version || release.version.starts_with(&format!("{}.", version)))
+}
+
+/// The last version announced, so each one is only announced once.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnnouncedVersion {
+    pub version: Option<String>,
+}
+
+/// Posts the running version's release notes to every subscribed channel if it hasn't been
+/// announced yet and the owner turned announcements on. The first run only records the
+/// version, since a fresh install isn't an upgrade.
+pub async fn announce(
+    cache_and_http: Arc<CacheAndHttp>,
+    settings: <Settings as TypeMapKey>::Value,
+    state: Store<AnnouncedVersion>,
+) {
+    if !env::var(ANNOUNCE_VAR).is_ok_and(|value| value.eq_ignore_ascii_case("true")) {
+        return;
+    }
+    let Some(release) = current() else {
+        warn!("CHANGELOG.md has no notes for version {}, so it won't be announced", VERSION);
+        return;
+    };
+    let previous = state.read().await.version.clone();
+    if previous.as_deref() == Some(release.version) {
+        return;
+    }
+    if let Err(err) = state.write(|state| state.version = Some(release.version.to_string())).await {
+        error!("Failed to save the announced version: {:#}", err);
+        return;
+    }
+    if previous.is_none() {
+        return;
+    }
+
+    let channels = settings.read().await.values().filter_map(|settings| settings.changelog_channel).collect::<Vec<_>>();
+    info!("Announcing version {} to {} channels", release.version, channels.len());
+    for channel_id in channels {
+        if let Err(err) = channel_id.send_message(&cache_and_http.http, |m| m.set_embed(release.embed())).await {
+            warn!(channel_id = u64::from(channel_id), "Failed to announce version {}: {:?}", release.version, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_each_heading_style() {
+        let releases = parse(
+            "# Changelog\nIntro\n\n## Unreleased\n- soon\n\n## [1.2.0] - 2024-01-01\n- two\n\n## v1.1.0\n- one\n- more\n\n## 1.0.0\n",
+        );
+        let versions = releases.iter().map(|release| release.version).collect::<Vec<_>>();
+        assert_eq!(versions, ["1.2.0", "1.1.0", "1.0.0"]);
+        assert_eq!(releases[1].notes, "- one\n- more");
+        assert_eq!(releases[2].notes, "");
+    }
+
+    #[test]
+    fn parse_handles_crlf_and_a_leading_heading() {
+        let releases = parse("## 2.0.0\r\n- new\r\n\r\n## [1.0.0]\r\n- old\r\n");
+        let versions = releases.iter().map(|release| release.version).collect::<Vec<_>>();
+        assert_eq!(versions, ["2.0.0", "1.0.0"]);
+        assert_eq!(releases[0].notes, "- new");
+    }
+
+    #[test]
+    fn find_matches_partial_and_prefixed_versions() {
+        let version = |input: &str| find(input).map(|release| release.version);
+        assert_eq!(version("0.15"), Some("0.15.0"));
+        assert_eq!(version("v0.15.0"), Some("0.15.0"));
+        assert_eq!(version(" 0.16.0 "), Some("0.16.0"));
+        assert_eq!(version("0.1"), None);
+        assert_eq!(version("9"), None);
+    }
+
+    #[test]
+    fn the_running_version_has_notes() {
+        assert_eq!(current().map(|release| release.version), Some(VERSION));
+        assert_eq!(RELEASES.first().map(|release| release.version), Some(VERSION));
+    }
+}
@@ -17,31 +17,109 @@ use serenity::framework::StandardFramework;
 use serenity::framework::standard::macros::hook;
 use serenity::client::Context;
 use serenity::model::prelude::Message;
-use serenity::framework::standard::DispatchError;
+use serenity::framework::standard::{CommandGroup, CommandResult, DispatchError};
+use serenity::builder::{CreateEmbed, CreateMessage};
+use serenity_utils::menu::{Menu, MenuOptions};
+use crate::config::EMBED_COLOR;
 
 mod general;
+mod info;
 mod help;
 mod image;
 mod tools;
 mod fun;
 mod moderation;
+mod config;
+mod filter;
+mod roles;
+mod records;
+mod invites;
+mod modmail;
+mod suggestions;
+mod reports;
+mod tickets;
+mod perms;
+mod announcements;
+mod schedules;
 
 pub const COMMAND_PREFIX: &str = "r";
 
+/// Every command group, in the order they're listed in help.
+pub const GROUPS: &[&CommandGroup] = &[
+    &general::GENERAL_GROUP,
+    &info::INFO_GROUP,
+    &image::IMAGE_GROUP,
+    &tools::TOOLS_GROUP,
+    &fun::FUN_GROUP,
+    &moderation::MODERATION_GROUP,
+    &config::CONFIG_GROUP,
+    &filter::FILTER_GROUP,
+    &roles::ROLES_GROUP,
+    &records::RECORDS_GROUP,
+    &invites::INVITES_GROUP,
+    &modmail::MODMAIL_GROUP,
+    &suggestions::SUGGESTIONS_GROUP,
+    &reports::REPORTS_GROUP,
+    &tickets::TICKETS_GROUP,
+    &perms::PERMISSIONS_GROUP,
+    &announcements::ANNOUNCEMENTS_GROUP,
+    &schedules::SCHEDULING_GROUP,
+];
+
 pub fn framework() -> StandardFramework {
-    StandardFramework::new()
-        .configure(|cfg| cfg.prefix(COMMAND_PREFIX))
-        .group(&general::GENERAL_GROUP)
+    GROUPS
+        .iter()
+        .fold(StandardFramework::new().configure(|cfg| cfg.prefix(COMMAND_PREFIX)), |framework, group| {
+            framework.group(group)
+        })
         .help(&help::HELP)
-        .group(&image::IMAGE_GROUP)
-        .group(&tools::TOOLS_GROUP)
-        .group(&fun::FUN_GROUP)
-        .group(&moderation::MODERATION_GROUP)
         .on_dispatch_error(|ctx, msg, error, command_name| {
             Box::pin(dispatch_error_hook(ctx, msg, error, command_name))
         })
+        .normal_message(relay_direct_message)
+        .prefix_only(relay_direct_message)
 }       
 
+/// Sends `embeds` as a reaction-paginated menu with a page counter in the footer.
+async fn show_pages(ctx: &Context, msg: &Message, embeds: Vec<CreateEmbed>) -> CommandResult {
+    let total = embeds.len();
+    let pages = embeds
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut embed)| {
+            embed.color(EMBED_COLOR);
+            embed.footer(|f| {
+                f.text(format!("Page {}/{} • Requested by {}", index + 1, total, msg.author.name));
+                f.icon_url(msg.author.face());
+                f
+            });
+            let mut page = CreateMessage::default();
+            page.set_embed(embed);
+            page
+        })
+        .collect::<Vec<_>>();
+
+    if pages.len() == 1 {
+        msg.channel_id.send_message(&ctx.http, |m| {
+            m.clone_from(&pages[0]);
+            m
+        })
+        .await?;
+        return Ok(());
+    }
+
+    let options = MenuOptions { timeout: 120.0, ..Default::default() };
+    Menu::new(ctx, msg, &pages, options).run().await?;
+
+    Ok(())
+}
+
+/// Relays direct messages that weren't run as a command to modmail.
+#[hook]
+async fn relay_direct_message(ctx: &Context, msg: &Message) {
+    crate::modmail::on_direct_message(ctx, msg).await;
+}
+
 #[hook]
 async fn dispatch_error_hook(
     ctx: &Context,
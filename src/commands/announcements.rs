@@ -0,0 +1,343 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use serenity::client::Context;
+use serenity::framework::standard::{
+    macros::{command, group},
+    Args, CommandResult,
+};
+use serenity::model::channel::{AttachmentType, GuildChannel, Message};
+use serenity::model::prelude::{ChannelId, MessageId};
+use serenity::model::Permissions;
+use crate::announcements::{self, ColourSpec, EmbedSpec, EmbedTemplates, FieldSpec, FooterSpec, ImageSpec, PostedEmbeds};
+use crate::store;
+
+/// How long the wizard waits for each answer.
+const WIZARD_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// What the author needs in a channel to post or edit announcements there themselves.
+const POSTING_PERMISSIONS: Permissions =
+    Permissions::VIEW_CHANNEL.union(Permissions::SEND_MESSAGES).union(Permissions::EMBED_LINKS);
+
+#[group]
+#[commands(embed)]
+struct Announcements;
+
+/// Takes an optional leading channel argument, defaulting to the current channel.
+/// Replies and returns `None` if the channel isn't in this server or the author can't post there.
+async fn target_channel(ctx: &Context, msg: &Message, args: &mut Args) -> serenity::Result<Option<ChannelId>> {
+    let guild_id = msg.guild_id.unwrap();
+    let channel = if args.current().is_some_and(|arg| arg.parse::<ChannelId>().is_ok()) {
+        super::config::guild_channel(ctx, guild_id, args)
+    } else {
+        msg.channel_id.to_channel(ctx).await.ok().and_then(|channel| channel.guild())
+    };
+    let Some(channel) = channel else {
+        msg.reply(&ctx.http, "That channel isn't in this server.").await?;
+        return Ok(None);
+    };
+    if !can_post(ctx, msg, &channel).await {
+        msg.reply(&ctx.http, format!("You need View Channel, Send Messages and Embed Links in <#{}>.", channel.id)).await?;
+        return Ok(None);
+    }
+    Ok(Some(channel.id))
+}
+
+/// Whether the author could post embeds in `channel` themselves, so that Manage Messages
+/// doesn't let them reach channels they can't see or send in.
+async fn can_post(ctx: &Context, msg: &Message, channel: &GuildChannel) -> bool {
+    let Some(guild) = channel.guild_id.to_guild_cached(&ctx.cache) else {
+        return false;
+    };
+    let Ok(member) = guild.id.member(ctx, msg.author.id).await else {
+        return false;
+    };
+    guild.user_permissions_in(channel, &member).is_ok_and(|permissions| permissions.contains(POSTING_PERMISSIONS))
+}
+
+/// Fills the placeholders for the author and this server, then posts the embed.
+async fn post(ctx: &Context, msg: &Message, channel_id: ChannelId, spec: &EmbedSpec) -> CommandResult {
+    let server = msg.guild_id.and_then(|guild_id| guild_id.name(&ctx.cache)).unwrap_or_default();
+    let spec = spec.fill(&format!("<@{}>", msg.author.id), &server);
+    let posted = channel_id.send_message(&ctx.http, |m| m.set_embed(spec.build())).await?;
+    if let Some(guild_id) = msg.guild_id {
+        let embeds = store::get::<PostedEmbeds>(ctx).await;
+        embeds.write(|all| announcements::remember(all.entry(guild_id).or_default(), posted.id)).await?;
+    }
+    if channel_id != msg.channel_id {
+        msg.reply(&ctx.http, format!("Embed posted in <#{}>.", channel_id)).await?;
+    }
+    Ok(())
+}
+
+async fn template(ctx: &Context, msg: &Message, name: &str) -> Option<EmbedSpec> {
+    let templates = store::get::<EmbedTemplates>(ctx).await;
+    let all = templates.read().await;
+    all.get(&msg.guild_id?)?.get(&name.to_lowercase()).cloned()
+}
+
+/// Reads an embed from JSON, or from a saved template when given a name.
+async fn spec_from(ctx: &Context, msg: &Message, input: &str) -> Result<EmbedSpec, String> {
+    let input = input.trim();
+    if input.starts_with('{') || input.starts_with("```") {
+        return EmbedSpec::parse(input);
+    }
+    template(ctx, msg, input).await.ok_or_else(|| format!("There is no template called `{}`.", input))
+}
+
+#[command]
+#[description("Composes embeds from JSON or a step-by-step wizard, edits bot announcements and manages templates")]
+#[usage("rembed <send/wizard/edit/template>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[sub_commands(send, wizard, edit, template_cmd)]
+async fn embed(ctx: &Context, msg: &Message) -> CommandResult {
+    msg.reply(
+        &ctx.http,
+        "Usage: `rembed send [channel] <json or template>`, `rembed wizard [channel]`, \
+         `rembed edit <message link or ID> <json or template>`, `rembed template <save/list/show/delete>`. \
+         `{user}` and `{server}` are replaced with your mention and the server name.",
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Posts an embed given as JSON or a template name")]
+#[usage("rembed send [channel mention or ID] <json or template name>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[min_args(1)]
+async fn send(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(channel_id) = target_channel(ctx, msg, &mut args).await? else {
+        return Ok(());
+    };
+    match spec_from(ctx, msg, args.rest()).await {
+        Ok(spec) => post(ctx, msg, channel_id, &spec).await,
+        Err(err) => {
+            msg.reply(&ctx.http, err).await?;
+            Ok(())
+        }
+    }
+}
+
+/// Asks the author a question and returns their answer, or `None` if they cancel or go quiet.
+async fn ask(ctx: &Context, msg: &Message, question: &str) -> serenity::Result<Option<String>> {
+    msg.channel_id.say(&ctx.http, question).await?;
+    let reply = msg.author.await_reply(ctx).channel_id(msg.channel_id).timeout(WIZARD_TIMEOUT).await;
+    match reply {
+        Some(reply) if !reply.content.trim().eq_ignore_ascii_case("cancel") => Ok(Some(reply.content.trim().to_string())),
+        Some(_) => {
+            msg.channel_id.say(&ctx.http, "Embed wizard cancelled.").await?;
+            Ok(None)
+        }
+        None => {
+            msg.channel_id.say(&ctx.http, "No answer received. Embed wizard cancelled.").await?;
+            Ok(None)
+        }
+    }
+}
+
+/// `None` for `skip`, otherwise the answer.
+fn optional(answer: String) -> Option<String> {
+    (!answer.eq_ignore_ascii_case("skip")).then_some(answer)
+}
+
+#[command]
+#[description("Builds an embed step by step, then posts it or saves it as a template")]
+#[usage("rembed wizard [channel mention or ID]")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+async fn wizard(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(channel_id) = target_channel(ctx, msg, &mut args).await? else {
+        return Ok(());
+    };
+    let mut spec = EmbedSpec::default();
+
+    msg.channel_id.say(&ctx.http, "Let's build an embed. Answer `skip` to leave something out or `cancel` to stop.").await?;
+    let Some(title) = ask(ctx, msg, "**Title?**").await? else { return Ok(()) };
+    spec.title = optional(title);
+    let Some(description) = ask(ctx, msg, "**Description?**").await? else { return Ok(()) };
+    spec.description = optional(description);
+
+    loop {
+        let Some(colour) = ask(ctx, msg, "**Colour?** (e.g. `#ff8800`)").await? else { return Ok(()) };
+        let Some(colour) = optional(colour) else { break };
+        if announcements::parse_colour(&colour).is_some() {
+            spec.color = Some(ColourSpec::Hex(colour));
+            break;
+        }
+        msg.channel_id.say(&ctx.http, "That isn't a colour like `#ff8800`.").await?;
+    }
+
+    let Some(image) = ask(ctx, msg, "**Image URL?**").await? else { return Ok(()) };
+    spec.image = optional(image).map(ImageSpec::Url);
+    let Some(footer) = ask(ctx, msg, "**Footer text?**").await? else { return Ok(()) };
+    spec.footer = optional(footer).map(FooterSpec::Text);
+
+    while spec.fields.len() < 25 {
+        let Some(field) = ask(ctx, msg, "**Add a field?** Send `name | value` (add `| inline` to put it beside others) or `done`.").await?
+        else {
+            return Ok(());
+        };
+        if field.eq_ignore_ascii_case("done") || field.eq_ignore_ascii_case("skip") {
+            break;
+        }
+        let parts = field.split('|').map(str::trim).collect::<Vec<_>>();
+        match parts.as_slice() {
+            [name, value] | [name, value, _] if !name.is_empty() && !value.is_empty() => spec.fields.push(FieldSpec {
+                name: name.to_string(),
+                value: value.to_string(),
+                inline: parts.get(2).is_some_and(|flag| flag.eq_ignore_ascii_case("inline")),
+            }),
+            _ => {
+                msg.channel_id.say(&ctx.http, "Fields are written as `name | value`.").await?;
+            }
+        }
+    }
+
+    if let Err(err) = spec.validate() {
+        msg.reply(&ctx.http, format!("{} Embed wizard cancelled.", err)).await?;
+        return Ok(());
+    }
+    msg.channel_id.send_message(&ctx.http, |m| m.content("**Preview:**").set_embed(spec.build())).await?;
+
+    loop {
+        let Some(answer) = ask(ctx, msg, "Send `post` to post it, `save <name>` to keep it as a template, or `cancel`.").await? else {
+            return Ok(());
+        };
+        if answer.eq_ignore_ascii_case("post") {
+            return post(ctx, msg, channel_id, &spec).await;
+        }
+        if let Some(name) = answer.strip_prefix("save ").map(str::trim).filter(|name| !name.is_empty()) {
+            save_template(ctx, msg, name, spec).await?;
+            return Ok(());
+        }
+    }
+}
+
+/// Parses a message link, or a bare message ID in the current channel.
+fn parse_message(msg: &Message, input: &str) -> Option<(ChannelId, MessageId)> {
+    if input.contains("/channels/") {
+        let mut ids = input.trim_end_matches('/').rsplit('/').map(|part| part.parse::<u64>());
+        let message_id = ids.next()?.ok()?;
+        let channel_id = ids.next()?.ok()?;
+        return Some((ChannelId(channel_id), MessageId(message_id)));
+    }
+    input.parse::<u64>().ok().map(|id| (msg.channel_id, MessageId(id)))
+}
+
+#[command]
+#[description("Replaces the embed of an announcement posted earlier with `rembed`")]
+#[usage("rembed edit <message link or ID> <json or template name>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[min_args(2)]
+async fn edit(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target = args.single::<String>()?;
+    let Some((channel_id, message_id)) = parse_message(msg, &target) else {
+        msg.reply(&ctx.http, "Invalid message link or ID provided.").await?;
+        return Ok(());
+    };
+    let guild_id = msg.guild_id.unwrap();
+    let embeds = store::get::<PostedEmbeds>(ctx).await;
+    let posted = embeds.read().await.get(&guild_id).is_some_and(|posted| posted.contains(&message_id));
+    if !posted {
+        msg.reply(&ctx.http, "I can only edit embeds posted with `rembed send` or `rembed wizard`.").await?;
+        return Ok(());
+    }
+    let channel = channel_id.to_channel(ctx).await.ok().and_then(|channel| channel.guild());
+    let Some(channel) = channel.filter(|channel| channel.guild_id == guild_id) else {
+        msg.reply(&ctx.http, "Couldn't find that message in this server.").await?;
+        return Ok(());
+    };
+    if !can_post(ctx, msg, &channel).await {
+        msg.reply(&ctx.http, format!("You need View Channel, Send Messages and Embed Links in <#{}>.", channel.id)).await?;
+        return Ok(());
+    }
+
+    let spec = match spec_from(ctx, msg, args.rest()).await {
+        Ok(spec) => spec,
+        Err(err) => {
+            msg.reply(&ctx.http, err).await?;
+            return Ok(());
+        }
+    };
+    let server = msg.guild_id.and_then(|guild_id| guild_id.name(&ctx.cache)).unwrap_or_default();
+    let spec = spec.fill(&format!("<@{}>", msg.author.id), &server);
+    let reply = match channel_id.edit_message(&ctx.http, message_id, |m| m.set_embed(spec.build())).await {
+        Ok(_) => "Announcement updated.",
+        Err(_) => "Couldn't edit that message. It may have been deleted.",
+    };
+    msg.reply(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+async fn save_template(ctx: &Context, msg: &Message, name: &str, spec: EmbedSpec) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let templates = store::get::<EmbedTemplates>(ctx).await;
+    templates.write(|all| all.entry(guild_id).or_default().insert(name.to_lowercase(), spec)).await?;
+    msg.reply(&ctx.http, format!("Saved template `{}`. Post it with `rembed send {}`.", name.to_lowercase(), name.to_lowercase())).await?;
+    Ok(())
+}
+
+#[command("template")]
+#[description("Saves, lists, shows or deletes reusable embed templates")]
+#[usage("rembed template <save <name> <json>/list/show <name>/delete <name>>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[min_args(1)]
+async fn template_cmd(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let operation = args.single::<String>()?.to_lowercase();
+    let name = args.single::<String>().unwrap_or_default().to_lowercase();
+
+    match operation.as_str() {
+        "list" => {
+            let templates = store::get::<EmbedTemplates>(ctx).await;
+            let mut names = templates.read().await.get(&guild_id).map(|t| t.keys().cloned().collect::<Vec<_>>()).unwrap_or_default();
+            names.sort();
+            let reply = if names.is_empty() {
+                "No templates saved yet.".to_string()
+            } else {
+                format!("Templates: {}", names.iter().map(|name| format!("`{}`", name)).collect::<Vec<_>>().join(", "))
+            };
+            msg.reply(&ctx.http, reply).await?;
+        }
+        _ if name.is_empty() => {
+            msg.reply(&ctx.http, "Give the template a name.").await?;
+        }
+        "save" => match EmbedSpec::parse(args.rest()) {
+            Ok(spec) => save_template(ctx, msg, &name, spec).await?,
+            Err(err) => {
+                msg.reply(&ctx.http, err).await?;
+            }
+        },
+        "show" => match template(ctx, msg, &name).await {
+            Some(spec) => {
+                // Sent as a file, since the JSON of a large embed doesn't fit in a message
+                let file = AttachmentType::Bytes { data: Cow::Owned(spec.to_json().into_bytes()), filename: format!("{}.json", name) };
+                msg.channel_id.send_message(&ctx.http, |m| m.add_file(file).set_embed(spec.build())).await?;
+            }
+            None => {
+                msg.reply(&ctx.http, format!("There is no template called `{}`.", name)).await?;
+            }
+        },
+        "delete" => {
+            let templates = store::get::<EmbedTemplates>(ctx).await;
+            let removed = templates.write(|all| all.get_mut(&guild_id).and_then(|t| t.remove(&name))).await?;
+            let reply = match removed {
+                Some(_) => format!("Deleted template `{}`.", name),
+                None => format!("There is no template called `{}`.", name),
+            };
+            msg.reply(&ctx.http, reply).await?;
+        }
+        _ => {
+            msg.reply(&ctx.http, "Invalid operation. Use `save`, `list`, `show` or `delete`").await?;
+        }
+    }
+
+    Ok(())
+}
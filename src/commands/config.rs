@@ -0,0 +1,1131 @@
+use serenity::client::Context;
+use serenity::framework::standard::{
+    macros::{command, group},
+    Args, CommandResult,
+};
+use serenity::model::channel::Message;
+use serenity::builder::CreateEmbed;
+use regex::Regex;
+use serenity::model::prelude::{ChannelId, ChannelType, GuildChannel, GuildId, RoleId};
+use crate::config::EMBED_COLOR;
+use crate::antiraid::AntiRaidSettings;
+use crate::automod::{AutomodAction, AutomodSettings, ThresholdRule};
+use crate::lockdown::LockdownSettings;
+use crate::memberlog::{MemberEvent, MemberLogSettings};
+use crate::msglog::MessageLogSettings;
+use crate::screening::ScreeningSettings;
+use crate::stickyroles::StickyRoleSettings;
+use crate::tickets::{self, TicketCategory, TicketSettings};
+use crate::settings;
+
+#[group]
+#[commands(config)]
+struct Config;
+
+/// Parses the next argument as a channel, rejecting channels that aren't in this server.
+pub(super) fn guild_channel(ctx: &Context, guild_id: GuildId, args: &mut Args) -> Option<GuildChannel> {
+    let channel_id = args.single::<ChannelId>().ok()?;
+    ctx.cache.guild_channel(channel_id).filter(|channel| channel.guild_id == guild_id)
+}
+
+fn channel_mention(channel: Option<ChannelId>) -> String {
+    channel.map_or_else(|| "Not set".to_string(), |channel| format!("<#{}>", channel))
+}
+
+fn automod_summary(automod: &AutomodSettings) -> String {
+    let rule = |rule: &ThresholdRule| format!("{} → {}", rule.limit, rule.action);
+    format!(
+        "Enabled: {}\nFlood: {} per {}s\nMentions: {}\nDuplicates: {}\nCaps: {}%\nEmoji: {}\nTimeout: {}s\nExempt roles: {}",
+        automod.enabled,
+        rule(&automod.flood),
+        automod.flood_window_secs,
+        rule(&automod.mentions),
+        rule(&automod.duplicates),
+        rule(&automod.caps),
+        rule(&automod.emoji),
+        automod.timeout_secs,
+        role_list(&automod.exempt_roles),
+    )
+}
+
+fn antiraid_summary(antiraid: &AntiRaidSettings) -> String {
+    format!(
+        "Automatic: {}\nThreshold: {} joins per {}s\nKick joins: {}\nRaise verification: {}\nLock channels: {}\nAuto-disable after: {}s\nAlert role: {}",
+        antiraid.enabled,
+        antiraid.join_threshold,
+        antiraid.window_secs,
+        antiraid.kick_joins,
+        antiraid.raise_verification,
+        antiraid.lockdown,
+        antiraid.auto_disable_secs,
+        antiraid.alert_role.map_or_else(|| "None".to_string(), |role| format!("<@&{}>", role)),
+    )
+}
+
+fn screening_summary(screening: &ScreeningSettings) -> String {
+    let patterns = if screening.name_patterns.is_empty() {
+        "None".to_string()
+    } else {
+        screening.name_patterns.iter().map(|p| format!("`{}`", p)).collect::<Vec<_>>().join(", ")
+    };
+    format!(
+        "Enabled: {}\nMinimum account age: {}h\nFlag default avatars: {}\nName patterns: {}\nQuarantine role: {}\nReview channel: {}",
+        screening.enabled,
+        screening.min_account_age_hours,
+        screening.flag_default_avatar,
+        patterns,
+        screening.quarantine_role.map_or_else(|| "None".to_string(), |role| format!("<@&{}>", role)),
+        channel_mention(screening.review_channel),
+    )
+}
+
+fn lockdown_summary(lockdown: &LockdownSettings) -> String {
+    format!("Channels: {}\nNotice: {}", channel_list(&lockdown.channels), lockdown.notice.as_deref().unwrap_or("Default"))
+}
+
+fn message_log_summary(message_log: &MessageLogSettings) -> String {
+    format!("Channel: {}\nIgnored channels: {}", channel_mention(message_log.channel), channel_list(&message_log.ignored_channels))
+}
+
+fn member_log_summary(member_log: &MemberLogSettings) -> String {
+    MemberEvent::ALL
+        .iter()
+        .map(|event| format!("{}: {}", event.name(), channel_mention(member_log.channel(*event))))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn sticky_roles_summary(sticky: &StickyRoleSettings) -> String {
+    let roles = if sticky.roles.is_empty() {
+        "Punishment roles (quarantine role and roles named like mute or jail)".to_string()
+    } else {
+        role_list(&sticky.roles)
+    };
+    format!("Enabled: {}\nRoles: {}\nRetention: {} days", sticky.enabled, roles, sticky.retention_days)
+}
+
+fn tickets_summary(tickets: &TicketSettings) -> String {
+    let categories = if tickets.categories.is_empty() {
+        "None".to_string()
+    } else {
+        tickets.categories.iter().map(|category| category.name.as_str()).collect::<Vec<_>>().join(", ")
+    };
+    format!(
+        "Categories: {}\nSupport roles: {}\nOpen as: {}\nParent category: {}\nTranscript log: {}\nOpen tickets per member: {}",
+        categories,
+        role_list(&tickets.support_roles),
+        if tickets.threads { "private threads" } else { "private channels" },
+        channel_mention(tickets.parent),
+        channel_mention(tickets.log_channel),
+        tickets.max_open
+    )
+}
+
+fn channel_list(channels: &[ChannelId]) -> String {
+    if channels.is_empty() {
+        "None".to_string()
+    } else {
+        channels.iter().map(|channel| format!("<#{}>", channel)).collect::<Vec<_>>().join(", ")
+    }
+}
+
+fn role_list(roles: &[RoleId]) -> String {
+    if roles.is_empty() {
+        "None".to_string()
+    } else {
+        roles.iter().map(|role| format!("<@&{}>", role)).collect::<Vec<_>>().join(", ")
+    }
+}
+
+#[command]
+#[description("Shows this server's bot settings")]
+#[usage("rconfig")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[sub_commands(trusted, modlog, staff, modmail, suggestions, reports, tickets, changelog, messagelog, memberlog, stickyroles, automod, linkscan, antiraid, screening, lockdown)]
+async fn config(ctx: &Context, msg: &Message) -> CommandResult {
+    let settings = settings::get(ctx, msg.guild_id.unwrap()).await;
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Server Settings");
+    embed.color(EMBED_COLOR);
+    embed.field("Trusted Roles", role_list(&settings.trusted_roles), false);
+    embed.field("Mod Log", channel_mention(settings.mod_log_channel), false);
+    embed.field("Staff Channels", channel_list(&settings.staff_channels), false);
+    embed.field("Modmail Category", channel_mention(settings.modmail.category), false);
+    embed.field("Bug Reports", channel_mention(settings.reports.channel), false);
+    embed.field(
+        "Suggestions",
+        format!("Channel: {}\nThreads: {}", channel_mention(settings.suggestions.channel), settings.suggestions.threads),
+        false,
+    );
+    embed.field("Tickets", tickets_summary(&settings.tickets), false);
+    embed.field("Message Log", message_log_summary(&settings.message_log), false);
+    embed.field("Member Log", member_log_summary(&settings.member_log), false);
+    embed.field("Sticky Roles", sticky_roles_summary(&settings.sticky_roles), false);
+    embed.field("Automod", automod_summary(&settings.automod), false);
+    embed.field("Anti-Raid", antiraid_summary(&settings.antiraid), false);
+    embed.field("Join Screening", screening_summary(&settings.screening), false);
+    embed.field("Lockdown Channels", lockdown_summary(&settings.lockdown), false);
+    embed.field("Link Scanner", if settings.linkscan.enabled { "Enabled" } else { "Disabled" }, true);
+    embed.field("Changelog Announcements", channel_mention(settings.changelog_channel), true);
+    embed.footer(|f| {
+        f.text(format!("Requested by {}", msg.author.name));
+        f.icon_url(msg.author.face());
+        f
+    });
+
+    msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Manages roles that skip confirmation prompts for moderation commands")]
+#[usage("rconfig trusted <add/remove> <role mention or ID>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+async fn trusted(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    if args.is_empty() {
+        let settings = settings::get(ctx, guild_id).await;
+        msg.reply(&ctx.http, format!("Trusted roles: {}", role_list(&settings.trusted_roles))).await?;
+        return Ok(());
+    }
+
+    let operation = args.single::<String>()?;
+    let role_id = match args.single::<RoleId>() {
+        Ok(role_id) => role_id,
+        Err(_) => {
+            msg.reply(&ctx.http, "Invalid role provided.").await?;
+            return Ok(());
+        }
+    };
+
+    let reply = match operation.as_str() {
+        "add" => {
+            settings::update(ctx, guild_id, |s| {
+                if !s.trusted_roles.contains(&role_id) {
+                    s.trusted_roles.push(role_id);
+                }
+            })
+            .await?;
+            format!("<@&{}> will now skip confirmation prompts.", role_id)
+        }
+        "remove" => {
+            settings::update(ctx, guild_id, |s| s.trusted_roles.retain(|role| *role != role_id)).await?;
+            format!("<@&{}> will no longer skip confirmation prompts.", role_id)
+        }
+        _ => "Invalid operation. Use `add` or `remove`".to_string(),
+    };
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| m.content(reply).allowed_mentions(|a| a.empty_parse()))
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Sets the channel that receives automated moderation reports")]
+#[usage("rconfig modlog <channel mention or ID/off>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[num_args(1)]
+async fn modlog(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel = if args.current() == Some("off") {
+        None
+    } else {
+        match guild_channel(ctx, guild_id, &mut args) {
+            Some(channel) => Some(channel.id),
+            None => {
+                msg.reply(&ctx.http, "Invalid channel provided.").await?;
+                return Ok(());
+            }
+        }
+    };
+
+    settings::update(ctx, guild_id, |s| s.mod_log_channel = channel).await?;
+    msg.reply(&ctx.http, format!("Mod log channel: {}", channel_mention(channel))).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Manages the channels where moderator notes and dossiers can be viewed")]
+#[usage("rconfig staff <add/remove> <channel mention or ID>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+async fn staff(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    if !args.is_empty() {
+        let operation = args.single::<String>()?;
+        // Channels that were deleted can still be removed by ID
+        let channel = if operation == "remove" {
+            args.single::<ChannelId>().ok()
+        } else {
+            guild_channel(ctx, guild_id, &mut args).map(|channel| channel.id)
+        };
+        let Some(channel_id) = channel else {
+            msg.reply(&ctx.http, "Invalid channel provided.").await?;
+            return Ok(());
+        };
+
+        match operation.as_str() {
+            "add" | "remove" => {
+                settings::update(ctx, guild_id, |s| {
+                    s.staff_channels.retain(|channel| *channel != channel_id);
+                    if operation == "add" {
+                        s.staff_channels.push(channel_id);
+                    }
+                })
+                .await?;
+            }
+            _ => {
+                msg.reply(&ctx.http, "Invalid operation. Use `add` or `remove`").await?;
+                return Ok(());
+            }
+        }
+    }
+
+    let settings = settings::get(ctx, guild_id).await;
+    msg.reply(&ctx.http, format!("Staff channels: {}", channel_list(&settings.staff_channels))).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Sets the category that modmail threads are opened in and the roles that can reply to them")]
+#[usage("rconfig modmail <category ID/off> | role <add/remove> <role mention or ID>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[min_args(1)]
+async fn modmail(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let result: Result<(), &str> = match args.current() {
+        Some("off") => {
+            settings::update(ctx, guild_id, |s| s.modmail.category = None).await?;
+            Ok(())
+        }
+        Some("role") => {
+            args.advance();
+            let operation = args.single::<String>().unwrap_or_default();
+            match (operation.as_str(), args.single::<RoleId>()) {
+                ("add" | "remove", Ok(role_id)) => {
+                    settings::update(ctx, guild_id, |s| {
+                        s.modmail.staff_roles.retain(|role| *role != role_id);
+                        if operation == "add" {
+                            s.modmail.staff_roles.push(role_id);
+                        }
+                    })
+                    .await?;
+                    Ok(())
+                }
+                _ => Err("Usage: `rconfig modmail role <add/remove> <role mention or ID>`"),
+            }
+        }
+        _ => {
+            let category = guild_channel(ctx, guild_id, &mut args).filter(|channel| channel.kind == ChannelType::Category);
+            match category {
+                Some(category) => {
+                    settings::update(ctx, guild_id, |s| s.modmail.category = Some(category.id)).await?;
+                    Ok(())
+                }
+                None => Err("Invalid category provided. Use the category's ID."),
+            }
+        }
+    };
+
+    match result {
+        Ok(()) => {
+            let modmail = settings::get(ctx, guild_id).await.modmail;
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.content(format!(
+                        "Modmail category: {}\nStaff roles: {} (and anyone with Manage Messages)",
+                        channel_mention(modmail.category),
+                        role_list(&modmail.staff_roles)
+                    ))
+                    .allowed_mentions(|a| a.empty_parse())
+                })
+                .await?;
+        }
+        Err(err) => {
+            msg.reply(&ctx.http, err).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[command]
+#[description("Sets the channel suggestions are posted in, and whether each one gets a discussion thread")]
+#[usage("rconfig suggestions <channel mention or ID/off> | threads <on/off>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[min_args(1)]
+async fn suggestions(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let reply = match args.current() {
+        Some("off") => {
+            settings::update(ctx, guild_id, |s| s.suggestions.channel = None).await?;
+            "Suggestions are now disabled.".to_string()
+        }
+        Some("threads") => {
+            args.advance();
+            match parse_toggle(args.single::<String>().ok()) {
+                Some(threads) => {
+                    settings::update(ctx, guild_id, |s| s.suggestions.threads = threads).await?;
+                    format!("Suggestion threads: {}", threads)
+                }
+                None => "Usage: `rconfig suggestions threads <on/off>`".to_string(),
+            }
+        }
+        _ => match guild_channel(ctx, guild_id, &mut args) {
+            Some(channel) => {
+                settings::update(ctx, guild_id, |s| s.suggestions.channel = Some(channel.id)).await?;
+                format!("Suggestions will be posted in <#{}>.", channel.id)
+            }
+            None => "Invalid channel provided.".to_string(),
+        },
+    };
+    msg.reply(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Sets the channel bug reports are filed and tracked in")]
+#[usage("rconfig reports <channel mention or ID/off>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[num_args(1)]
+async fn reports(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel = if args.current() == Some("off") {
+        None
+    } else {
+        match guild_channel(ctx, guild_id, &mut args) {
+            Some(channel) => Some(channel.id),
+            None => {
+                msg.reply(&ctx.http, "Invalid channel provided.").await?;
+                return Ok(());
+            }
+        }
+    };
+
+    settings::update(ctx, guild_id, |s| s.reports.channel = channel).await?;
+    msg.reply(&ctx.http, format!("Bug report channel: {}", channel_mention(channel))).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Sets the channel new bot versions are announced in, if the bot owner turned announcements on")]
+#[usage("rconfig changelog <channel mention or ID/off>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[num_args(1)]
+async fn changelog(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let channel = if args.current() == Some("off") {
+        None
+    } else {
+        match guild_channel(ctx, guild_id, &mut args) {
+            Some(channel) => Some(channel.id),
+            None => {
+                msg.reply(&ctx.http, "Invalid channel provided.").await?;
+                return Ok(());
+            }
+        }
+    };
+
+    settings::update(ctx, guild_id, |s| s.changelog_channel = channel).await?;
+    msg.reply(&ctx.http, format!("Changelog announcement channel: {}", channel_mention(channel))).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Configures ticket categories, support roles, where tickets open and how many a member may have")]
+#[usage("rconfig tickets category <add/remove> <name> [description] | role <add/remove> <role> | mode <channel/thread> | parent <category ID/off> | log <channel/off> | limit <number>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+async fn tickets(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let result: Result<(), &str> = if args.is_empty() {
+        Ok(())
+    } else {
+        let setting = args.single::<String>()?.to_lowercase();
+        match setting.as_str() {
+            "category" => {
+                let operation = args.single::<String>().unwrap_or_default();
+                let name = args.single_quoted::<String>().unwrap_or_default();
+                let description = Some(args.rest().to_string()).filter(|description| !description.is_empty());
+                let categories = settings::get(ctx, guild_id).await.tickets.categories;
+                let full = categories.len() >= tickets::MAX_CATEGORIES
+                    && !categories.iter().any(|category| category.name.eq_ignore_ascii_case(&name));
+                match operation.as_str() {
+                    _ if name.is_empty() || name.chars().count() > 50 => {
+                        Err("Usage: `rconfig tickets category <add/remove> <name> [description]`. Names are at most 50 characters.")
+                    }
+                    "add" if description.as_ref().is_some_and(|d| d.chars().count() > tickets::MAX_DESCRIPTION) => {
+                        Err("Category descriptions are at most 150 characters.")
+                    }
+                    "add" if full => {
+                        Err("A panel can offer at most 25 categories. Remove one first.")
+                    }
+                    "add" => {
+                        settings::update(ctx, guild_id, |s| {
+                            s.tickets.categories.retain(|category| !category.name.eq_ignore_ascii_case(&name));
+                            s.tickets.categories.push(TicketCategory { name, description });
+                        })
+                        .await?;
+                        Ok(())
+                    }
+                    "remove" => {
+                        settings::update(ctx, guild_id, |s| {
+                            s.tickets.categories.retain(|category| !category.name.eq_ignore_ascii_case(&name))
+                        })
+                        .await?;
+                        Ok(())
+                    }
+                    _ => Err("Invalid operation. Use `add` or `remove`"),
+                }
+            }
+            "role" => {
+                let operation = args.single::<String>().unwrap_or_default();
+                match (operation.as_str(), args.single::<RoleId>()) {
+                    ("add" | "remove", Ok(role_id)) => {
+                        settings::update(ctx, guild_id, |s| {
+                            s.tickets.support_roles.retain(|role| *role != role_id);
+                            if operation == "add" {
+                                s.tickets.support_roles.push(role_id);
+                            }
+                        })
+                        .await?;
+                        Ok(())
+                    }
+                    _ => Err("Usage: `rconfig tickets role <add/remove> <role mention or ID>`"),
+                }
+            }
+            "mode" => {
+                let threads = match args.single::<String>().unwrap_or_default().as_str() {
+                    "channel" => Some(false),
+                    "thread" => Some(true),
+                    _ => None,
+                };
+                match threads {
+                    Some(threads) => {
+                        settings::update(ctx, guild_id, |s| s.tickets.threads = threads).await?;
+                        Ok(())
+                    }
+                    None => Err("Usage: `rconfig tickets mode <channel/thread>`"),
+                }
+            }
+            "parent" => {
+                if args.current() == Some("off") {
+                    settings::update(ctx, guild_id, |s| s.tickets.parent = None).await?;
+                    Ok(())
+                } else {
+                    let category = guild_channel(ctx, guild_id, &mut args).filter(|channel| channel.kind == ChannelType::Category);
+                    match category {
+                        Some(category) => {
+                            settings::update(ctx, guild_id, |s| s.tickets.parent = Some(category.id)).await?;
+                            Ok(())
+                        }
+                        None => Err("Invalid category provided. Use the category's ID."),
+                    }
+                }
+            }
+            "log" => {
+                if args.current() == Some("off") {
+                    settings::update(ctx, guild_id, |s| s.tickets.log_channel = None).await?;
+                    Ok(())
+                } else {
+                    match guild_channel(ctx, guild_id, &mut args) {
+                        Some(channel) => {
+                            settings::update(ctx, guild_id, |s| s.tickets.log_channel = Some(channel.id)).await?;
+                            Ok(())
+                        }
+                        None => Err("Invalid channel provided."),
+                    }
+                }
+            }
+            "limit" => match args.single::<u32>() {
+                Ok(limit) if limit > 0 => {
+                    settings::update(ctx, guild_id, |s| s.tickets.max_open = limit).await?;
+                    Ok(())
+                }
+                _ => Err("Usage: `rconfig tickets limit <number>`"),
+            },
+            _ => Err("Unknown ticket setting."),
+        }
+    };
+
+    match result {
+        Ok(()) => {
+            let settings = settings::get(ctx, guild_id).await;
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| e.title("Tickets").description(tickets_summary(&settings.tickets)).color(EMBED_COLOR))
+                })
+                .await?;
+        }
+        Err(err) => {
+            msg.reply(&ctx.http, err).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[command]
+#[description("Sets the channel that receives message edit and deletion logs, and the channels it ignores")]
+#[usage("rconfig messagelog <channel mention or ID/off> | ignore <add/remove> <channel>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+async fn messagelog(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let result: Result<(), &str> = match args.current() {
+        None => Ok(()),
+        Some("off") => {
+            settings::update(ctx, guild_id, |s| s.message_log.channel = None).await?;
+            Ok(())
+        }
+        Some("ignore") => {
+            args.advance();
+            let operation = args.single::<String>().unwrap_or_default();
+            // Channels that were deleted can still be removed by ID
+            let channel = match operation.as_str() {
+                "add" => guild_channel(ctx, guild_id, &mut args).map(|channel| channel.id),
+                "remove" => args.single::<ChannelId>().ok(),
+                _ => None,
+            };
+            match channel {
+                Some(channel_id) => {
+                    settings::update(ctx, guild_id, |s| {
+                        s.message_log.ignored_channels.retain(|channel| *channel != channel_id);
+                        if operation == "add" {
+                            s.message_log.ignored_channels.push(channel_id);
+                        }
+                    })
+                    .await?;
+                    Ok(())
+                }
+                None => Err("Usage: `rconfig messagelog ignore <add/remove> <channel mention or ID>`"),
+            }
+        }
+        Some(_) => match guild_channel(ctx, guild_id, &mut args) {
+            Some(channel) => {
+                settings::update(ctx, guild_id, |s| s.message_log.channel = Some(channel.id)).await?;
+                Ok(())
+            }
+            None => Err("Invalid channel provided."),
+        },
+    };
+
+    match result {
+        Ok(()) => {
+            let settings = settings::get(ctx, guild_id).await;
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| e.title("Message Log Settings").description(message_log_summary(&settings.message_log)).color(EMBED_COLOR))
+                })
+                .await?;
+        }
+        Err(err) => {
+            msg.reply(&ctx.http, err).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[command]
+#[description("Routes member join, leave, nickname, role, avatar and ban logs to channels")]
+#[usage("rconfig memberlog <joins/leaves/nicknames/roles/avatars/bans/all> <channel mention or ID/off>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+async fn memberlog(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    if !args.is_empty() {
+        let target = args.single::<String>()?;
+        let events = match MemberEvent::parse(&target) {
+            Some(event) => vec![event],
+            None if target.eq_ignore_ascii_case("all") => MemberEvent::ALL.to_vec(),
+            None => {
+                msg.reply(&ctx.http, "Unknown event. Use joins, leaves, nicknames, roles, avatars, bans or all.").await?;
+                return Ok(());
+            }
+        };
+        let channel = if args.current() == Some("off") {
+            None
+        } else {
+            match guild_channel(ctx, guild_id, &mut args) {
+                Some(channel) => Some(channel.id),
+                None => {
+                    msg.reply(&ctx.http, "Invalid channel provided.").await?;
+                    return Ok(());
+                }
+            }
+        };
+
+        settings::update(ctx, guild_id, |s| {
+            for event in events {
+                *s.member_log.channel_mut(event) = channel;
+            }
+        })
+        .await?;
+    }
+
+    let settings = settings::get(ctx, guild_id).await;
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| e.title("Member Log Channels").description(member_log_summary(&settings.member_log)).color(EMBED_COLOR))
+        })
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Configures which roles are given back to members who leave and rejoin")]
+#[usage("rconfig stickyroles <on/off> | <add/remove> <role> | retention <days>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+async fn stickyroles(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let result: Result<(), &str> = if args.is_empty() {
+        Ok(())
+    } else {
+        let setting = args.single::<String>()?.to_lowercase();
+        match setting.as_str() {
+            "on" | "off" => {
+                settings::update(ctx, guild_id, |s| s.sticky_roles.enabled = setting == "on").await?;
+                Ok(())
+            }
+            "add" | "remove" => match args.single::<RoleId>() {
+                Ok(role_id) => {
+                    settings::update(ctx, guild_id, |s| {
+                        s.sticky_roles.roles.retain(|role| *role != role_id);
+                        if setting == "add" {
+                            s.sticky_roles.roles.push(role_id);
+                        }
+                    })
+                    .await?;
+                    Ok(())
+                }
+                Err(_) => Err("Usage: `rconfig stickyroles <add/remove> <role mention or ID>`"),
+            },
+            "retention" => match args.single::<u64>() {
+                Ok(days) if days > 0 => {
+                    settings::update(ctx, guild_id, |s| s.sticky_roles.retention_days = days).await?;
+                    Ok(())
+                }
+                _ => Err("Usage: `rconfig stickyroles retention <days>`"),
+            },
+            _ => Err("Unknown sticky roles setting."),
+        }
+    };
+
+    match result {
+        Ok(()) => {
+            let settings = settings::get(ctx, guild_id).await;
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| e.title("Sticky Roles").description(sticky_roles_summary(&settings.sticky_roles)).color(EMBED_COLOR))
+                })
+                .await?;
+        }
+        Err(err) => {
+            msg.reply(&ctx.http, err).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[command]
+#[description("Configures automatic spam, mention-flood and duplicate-message detection")]
+#[usage("rconfig automod <on/off> | <flood/mentions/duplicates/caps/emoji> <limit> <off/delete/warn/timeout/kick> [flood window seconds] | timeout <seconds> | exempt <add/remove> <role>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+async fn automod(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    if args.is_empty() {
+        let settings = settings::get(ctx, guild_id).await;
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| e.title("Automod Settings").description(automod_summary(&settings.automod)).color(EMBED_COLOR))
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let setting = args.single::<String>()?.to_lowercase();
+    let result: Result<(), &str> = match setting.as_str() {
+        "on" | "off" => {
+            let enabled = setting == "on";
+            settings::update(ctx, guild_id, |s| s.automod.enabled = enabled).await?;
+            Ok(())
+        }
+        "flood" | "mentions" | "duplicates" | "caps" | "emoji" => {
+            let limit = args.single::<usize>().ok();
+            let action = args.single::<String>().ok().and_then(|a| AutomodAction::parse(&a));
+            let window = args.single::<u64>().ok();
+            match (limit, action) {
+                (Some(limit), Some(action)) => {
+                    let rule = ThresholdRule { limit, action };
+                    settings::update(ctx, guild_id, |s| {
+                        let automod = &mut s.automod;
+                        match setting.as_str() {
+                            "flood" => {
+                                automod.flood = rule;
+                                if let Some(window) = window {
+                                    automod.flood_window_secs = window.max(1);
+                                }
+                            }
+                            "mentions" => automod.mentions = rule,
+                            "duplicates" => automod.duplicates = rule,
+                            "caps" => automod.caps = rule,
+                            _ => automod.emoji = rule,
+                        }
+                    })
+                    .await?;
+                    Ok(())
+                }
+                _ => Err("Usage: `rconfig automod <rule> <limit> <off/delete/warn/timeout/kick>`"),
+            }
+        }
+        "timeout" => match args.single::<u64>() {
+            Ok(secs) if (1..=28 * 24 * 60 * 60).contains(&secs) => {
+                settings::update(ctx, guild_id, |s| s.automod.timeout_secs = secs).await?;
+                Ok(())
+            }
+            _ => Err("Timeouts must be between 1 second and 28 days."),
+        },
+        "exempt" => {
+            let operation = args.single::<String>().unwrap_or_default();
+            match (operation.as_str(), args.single::<RoleId>()) {
+                ("add", Ok(role_id)) => {
+                    settings::update(ctx, guild_id, |s| {
+                        if !s.automod.exempt_roles.contains(&role_id) {
+                            s.automod.exempt_roles.push(role_id);
+                        }
+                    })
+                    .await?;
+                    Ok(())
+                }
+                ("remove", Ok(role_id)) => {
+                    settings::update(ctx, guild_id, |s| s.automod.exempt_roles.retain(|role| *role != role_id)).await?;
+                    Ok(())
+                }
+                _ => Err("Usage: `rconfig automod exempt <add/remove> <role>`"),
+            }
+        }
+        _ => Err("Unknown automod setting."),
+    };
+
+    match result {
+        Ok(()) => {
+            let settings = settings::get(ctx, guild_id).await;
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| e.title("Automod Updated").description(automod_summary(&settings.automod)).color(EMBED_COLOR))
+                })
+                .await?;
+        }
+        Err(err) => {
+            msg.reply(&ctx.http, err).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[command]
+#[description("Turns phishing link detection on or off")]
+#[usage("rconfig linkscan <on/off>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[num_args(1)]
+async fn linkscan(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let enabled = match args.single::<String>()?.to_lowercase().as_str() {
+        "on" => true,
+        "off" => false,
+        _ => {
+            msg.reply(&ctx.http, "Usage: `rconfig linkscan <on/off>`").await?;
+            return Ok(());
+        }
+    };
+
+    settings::update(ctx, msg.guild_id.unwrap(), |s| s.linkscan.enabled = enabled).await?;
+    msg.reply(&ctx.http, format!("Link scanning is now {}.", if enabled { "enabled" } else { "disabled" })).await?;
+
+    Ok(())
+}
+
+fn parse_toggle(value: Option<String>) -> Option<bool> {
+    match value?.to_lowercase().as_str() {
+        "on" => Some(true),
+        "off" => Some(false),
+        _ => None,
+    }
+}
+
+#[command]
+#[description("Configures automatic raid detection and what raid mode does")]
+#[usage("rconfig antiraid <on/off> | threshold <joins> <seconds> | <kick/verification/lockdown> <on/off> | duration <seconds> | alertrole <role/off>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+async fn antiraid(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let result: Result<(), &str> = if args.is_empty() {
+        Ok(())
+    } else {
+        let setting = args.single::<String>()?.to_lowercase();
+        match setting.as_str() {
+            "on" | "off" => {
+                let enabled = setting == "on";
+                settings::update(ctx, guild_id, |s| s.antiraid.enabled = enabled).await?;
+                Ok(())
+            }
+            "threshold" => match (args.single::<usize>(), args.single::<u64>()) {
+                (Ok(joins), Ok(secs)) if joins >= 2 && secs >= 1 => {
+                    settings::update(ctx, guild_id, |s| {
+                        s.antiraid.join_threshold = joins;
+                        s.antiraid.window_secs = secs;
+                    })
+                    .await?;
+                    Ok(())
+                }
+                _ => Err("Usage: `rconfig antiraid threshold <joins (2 or more)> <seconds>`"),
+            },
+            "kick" | "verification" | "lockdown" => match parse_toggle(args.single::<String>().ok()) {
+                Some(enabled) => {
+                    settings::update(ctx, guild_id, |s| match setting.as_str() {
+                        "kick" => s.antiraid.kick_joins = enabled,
+                        "verification" => s.antiraid.raise_verification = enabled,
+                        _ => s.antiraid.lockdown = enabled,
+                    })
+                    .await?;
+                    Ok(())
+                }
+                None => Err("Usage: `rconfig antiraid <kick/verification/lockdown> <on/off>`"),
+            },
+            "duration" => match args.single::<u64>() {
+                Ok(secs) => {
+                    settings::update(ctx, guild_id, |s| s.antiraid.auto_disable_secs = secs).await?;
+                    Ok(())
+                }
+                Err(_) => Err("Usage: `rconfig antiraid duration <seconds, 0 to never expire>`"),
+            },
+            "alertrole" => {
+                let role = if args.current() == Some("off") {
+                    Ok(None)
+                } else {
+                    args.single::<RoleId>().map(Some)
+                };
+                match role {
+                    Ok(role) => {
+                        settings::update(ctx, guild_id, |s| s.antiraid.alert_role = role).await?;
+                        Ok(())
+                    }
+                    Err(_) => Err("Usage: `rconfig antiraid alertrole <role mention or ID/off>`"),
+                }
+            }
+            _ => Err("Unknown anti-raid setting."),
+        }
+    };
+
+    match result {
+        Ok(()) => {
+            let settings = settings::get(ctx, guild_id).await;
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| e.title("Anti-Raid Settings").description(antiraid_summary(&settings.antiraid)).color(EMBED_COLOR))
+                })
+                .await?;
+        }
+        Err(err) => {
+            msg.reply(&ctx.http, err).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[command]
+#[description("Configures screening of new members for young accounts, default avatars and suspicious names")]
+#[usage("rconfig screening <on/off> | age <hours> | avatar <on/off> | pattern <add/remove> <regex> | role <role/off> | channel <channel/off>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+async fn screening(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    args.quoted();
+
+    let result: Result<(), String> = if args.is_empty() {
+        Ok(())
+    } else {
+        let setting = args.single::<String>()?.to_lowercase();
+        match setting.as_str() {
+            "on" | "off" => {
+                let enabled = setting == "on";
+                settings::update(ctx, guild_id, |s| s.screening.enabled = enabled).await?;
+                Ok(())
+            }
+            "age" => match args.single::<u64>() {
+                Ok(hours) => {
+                    settings::update(ctx, guild_id, |s| s.screening.min_account_age_hours = hours).await?;
+                    Ok(())
+                }
+                Err(_) => Err("Usage: `rconfig screening age <hours, 0 to disable>`".to_string()),
+            },
+            "avatar" => match parse_toggle(args.single::<String>().ok()) {
+                Some(enabled) => {
+                    settings::update(ctx, guild_id, |s| s.screening.flag_default_avatar = enabled).await?;
+                    Ok(())
+                }
+                None => Err("Usage: `rconfig screening avatar <on/off>`".to_string()),
+            },
+            "pattern" => {
+                let operation = args.single::<String>().unwrap_or_default();
+                let pattern = args.single_quoted::<String>().unwrap_or_default();
+                match operation.as_str() {
+                    _ if pattern.is_empty() => Err("Usage: `rconfig screening pattern <add/remove> <regex>`".to_string()),
+                    "add" => match Regex::new(&pattern) {
+                        Ok(_) => {
+                            settings::update(ctx, guild_id, |s| s.screening.name_patterns.push(pattern)).await?;
+                            Ok(())
+                        }
+                        Err(err) => Err(format!("Invalid regex: {}", err)),
+                    },
+                    "remove" => {
+                        settings::update(ctx, guild_id, |s| s.screening.name_patterns.retain(|p| *p != pattern)).await?;
+                        Ok(())
+                    }
+                    _ => Err("Usage: `rconfig screening pattern <add/remove> <regex>`".to_string()),
+                }
+            }
+            "role" => {
+                let role = if args.current() == Some("off") { Ok(None) } else { args.single::<RoleId>().map(Some) };
+                match role {
+                    Ok(role) => {
+                        settings::update(ctx, guild_id, |s| s.screening.quarantine_role = role).await?;
+                        Ok(())
+                    }
+                    Err(_) => Err("Usage: `rconfig screening role <role mention or ID/off>`".to_string()),
+                }
+            }
+            "channel" => {
+                let channel = if args.current() == Some("off") {
+                    Some(None)
+                } else {
+                    guild_channel(ctx, guild_id, &mut args).map(|channel| Some(channel.id))
+                };
+                match channel {
+                    Some(channel) => {
+                        settings::update(ctx, guild_id, |s| s.screening.review_channel = channel).await?;
+                        Ok(())
+                    }
+                    None => Err("Usage: `rconfig screening channel <channel mention or ID/off>`".to_string()),
+                }
+            }
+            _ => Err("Unknown screening setting.".to_string()),
+        }
+    };
+
+    match result {
+        Ok(()) => {
+            let settings = settings::get(ctx, guild_id).await;
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| e.title("Join Screening Settings").description(screening_summary(&settings.screening)).color(EMBED_COLOR))
+                })
+                .await?;
+        }
+        Err(err) => {
+            msg.reply(&ctx.http, err).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[command]
+#[description("Configures the channels locked by `rlock all` and raid mode, and the notice posted in them")]
+#[usage("rconfig lockdown <add/remove> <channel> | notice <text/off>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+async fn lockdown(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let result: Result<(), &str> = if args.is_empty() {
+        Ok(())
+    } else {
+        let setting = args.single::<String>()?.to_lowercase();
+        match setting.as_str() {
+            "add" | "remove" => {
+                // Channels that were deleted can still be removed by ID
+                let channel = if setting == "add" {
+                    guild_channel(ctx, guild_id, &mut args).map(|channel| channel.id)
+                } else {
+                    args.single::<ChannelId>().ok()
+                };
+                match channel {
+                    Some(channel_id) => {
+                        settings::update(ctx, guild_id, |s| {
+                            s.lockdown.channels.retain(|channel| *channel != channel_id);
+                            if setting == "add" {
+                                s.lockdown.channels.push(channel_id);
+                            }
+                        })
+                        .await?;
+                        Ok(())
+                    }
+                    None => Err("Usage: `rconfig lockdown <add/remove> <channel mention or ID>`"),
+                }
+            }
+            "notice" => match args.remains() {
+                Some(notice) => {
+                    let notice = (!notice.eq_ignore_ascii_case("off")).then(|| notice.to_string());
+                    settings::update(ctx, guild_id, |s| s.lockdown.notice = notice).await?;
+                    Ok(())
+                }
+                None => Err("Usage: `rconfig lockdown notice <text/off>`"),
+            },
+            _ => Err("Unknown lockdown setting."),
+        }
+    };
+
+    match result {
+        Ok(()) => {
+            let settings = settings::get(ctx, guild_id).await;
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| e.title("Lockdown Settings").description(lockdown_summary(&settings.lockdown)).color(EMBED_COLOR))
+                })
+                .await?;
+        }
+        Err(err) => {
+            msg.reply(&ctx.http, err).await?;
+        }
+    }
+
+    Ok(())
+}
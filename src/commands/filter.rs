@@ -0,0 +1,220 @@
+use serenity::client::Context;
+use serenity::framework::standard::{
+    macros::{command, group},
+    Args, CommandResult,
+};
+use serenity::model::channel::Message;
+use serenity::builder::CreateEmbed;
+use serenity::model::prelude::{ChannelId, RoleId};
+use crate::automod::AutomodAction;
+use crate::config::EMBED_COLOR;
+use crate::filter::{normalize, FilterRule, RuleKind};
+use crate::settings;
+use crate::utils::truncate;
+
+#[group]
+#[commands(filter)]
+struct Filter;
+
+fn describe_rule(rule: &FilterRule) -> String {
+    let mut line = format!("`#{}` {} `{}` → {}", rule.id, rule.kind, truncate(&rule.pattern, 60), rule.action);
+    let exemptions = rule
+        .exempt_channels
+        .iter()
+        .map(|channel| format!("<#{}>", channel))
+        .chain(rule.exempt_roles.iter().map(|role| format!("<@&{}>", role)))
+        .collect::<Vec<_>>();
+    if !exemptions.is_empty() {
+        line.push_str(&format!(" (exempt: {})", exemptions.join(", ")));
+    }
+    line
+}
+
+#[command]
+#[description("Manages this server's word and pattern filter")]
+#[usage("rfilter <add/remove/list/test>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[sub_commands(add, remove, list, test)]
+async fn filter(ctx: &Context, msg: &Message) -> CommandResult {
+    msg.reply(&ctx.http, "Usage: `rfilter <add/remove/list/test>`. See `rhelp filter` for details.").await?;
+    Ok(())
+}
+
+#[command]
+#[description("Adds a filter rule. Patterns containing spaces must be quoted. Invite rules take a comma-separated list of allowed invite codes, or `none`.")]
+#[usage("rfilter add <word/wildcard/regex/invite> <pattern> [off/delete/warn/timeout/kick] [--channel #channel]... [--role @role]...")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[min_args(2)]
+async fn add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    args.quoted();
+
+    let Some(kind) = RuleKind::parse(&args.single::<String>()?) else {
+        msg.reply(&ctx.http, "Rule type must be `word`, `wildcard`, `regex` or `invite`.").await?;
+        return Ok(());
+    };
+    let mut pattern = args.single_quoted::<String>()?;
+    if kind == RuleKind::Invite && pattern.eq_ignore_ascii_case("none") {
+        pattern.clear();
+    }
+    if let Err(err) = FilterRule::regex_source(kind, &pattern) {
+        msg.reply(&ctx.http, format!("Invalid pattern: {}", err)).await?;
+        return Ok(());
+    }
+
+    let mut action = AutomodAction::Delete;
+    let mut exempt_channels = Vec::new();
+    let mut exempt_roles = Vec::new();
+    while !args.is_empty() {
+        let token = args.single::<String>()?;
+        match token.as_str() {
+            "--channel" => match args.single::<ChannelId>() {
+                Ok(channel) => exempt_channels.push(channel),
+                Err(_) => {
+                    msg.reply(&ctx.http, "`--channel` expects a channel mention or ID.").await?;
+                    return Ok(());
+                }
+            },
+            "--role" => match args.single::<RoleId>() {
+                Ok(role) => exempt_roles.push(role),
+                Err(_) => {
+                    msg.reply(&ctx.http, "`--role` expects a role mention or ID.").await?;
+                    return Ok(());
+                }
+            },
+            other => match AutomodAction::parse(other) {
+                Some(parsed) => action = parsed,
+                None => {
+                    msg.reply(&ctx.http, format!("Unknown option `{}`.", other)).await?;
+                    return Ok(());
+                }
+            },
+        }
+    }
+
+    let rule = settings::update(ctx, guild_id, |s| {
+        s.filter.next_id += 1;
+        let rule = FilterRule {
+            id: s.filter.next_id,
+            kind,
+            pattern,
+            action,
+            exempt_channels,
+            exempt_roles,
+        };
+        s.filter.rules.push(rule.clone());
+        rule
+    })
+    .await?;
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.content(format!("Added filter rule {}", describe_rule(&rule)))
+                .allowed_mentions(|a| a.empty_parse())
+        })
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Removes a filter rule by its ID")]
+#[usage("rfilter remove <rule ID>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[num_args(1)]
+async fn remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let id = match args.single::<String>()?.trim_start_matches('#').parse::<u32>() {
+        Ok(id) => id,
+        Err(_) => {
+            msg.reply(&ctx.http, "Invalid rule ID.").await?;
+            return Ok(());
+        }
+    };
+
+    let removed = settings::update(ctx, guild_id, |s| {
+        let before = s.filter.rules.len();
+        s.filter.rules.retain(|rule| rule.id != id);
+        before != s.filter.rules.len()
+    })
+    .await?;
+
+    let reply = if removed {
+        format!("Removed filter rule #{}.", id)
+    } else {
+        format!("No filter rule with ID #{} exists.", id)
+    };
+    msg.reply(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Lists this server's filter rules")]
+#[usage("rfilter list")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+async fn list(ctx: &Context, msg: &Message) -> CommandResult {
+    let filter = settings::get(ctx, msg.guild_id.unwrap()).await.filter;
+
+    let description = if filter.rules.is_empty() {
+        "No filter rules have been added.".to_string()
+    } else {
+        truncate(&filter.rules.iter().map(describe_rule).collect::<Vec<_>>().join("\n"), 4000)
+    };
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Filter Rules");
+    embed.description(description);
+    embed.color(EMBED_COLOR);
+    embed.footer(|f| {
+        f.text(format!("Requested by {}", msg.author.name));
+        f.icon_url(msg.author.face());
+        f
+    });
+
+    msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Shows which filter rules a piece of text would trigger, ignoring exemptions")]
+#[usage("rfilter test <text>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[min_args(1)]
+async fn test(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let filter = settings::get(ctx, msg.guild_id.unwrap()).await.filter;
+    let text = args.rest();
+    let normalized = normalize(text);
+
+    let matched = filter
+        .rules
+        .iter()
+        .filter(|rule| rule.matches(text, &normalized))
+        .map(describe_rule)
+        .collect::<Vec<_>>();
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Filter Test");
+    embed.field("Normalized", format!("```\n{}\n```", truncate(&normalized, 1000)), false);
+    embed.field(
+        "Matching Rules",
+        if matched.is_empty() { "None".to_string() } else { truncate(&matched.join("\n"), 1000) },
+        false,
+    );
+    embed.color(EMBED_COLOR);
+    embed.footer(|f| {
+        f.text(format!("Requested by {}", msg.author.name));
+        f.icon_url(msg.author.face());
+        f
+    });
+
+    msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+
+    Ok(())
+}
@@ -84,7 +84,7 @@ async fn guess(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
         msg.channel_id.say(&ctx.http, format!("Enter your guess ({} attempts remaining):", attempts)).await?;
 
         // Wait for a message from the user
-        let response = match msg.author.await_reply(&ctx).await {
+        let response = match msg.author.await_reply(ctx).await {
             Some(response) => response,
             None => {
                 msg.channel_id.say(&ctx.http, "No response received. Exiting game.").await?;
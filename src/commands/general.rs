@@ -5,12 +5,17 @@ use serenity::builder::CreateEmbed;
 use serenity::client::Context;
 use anyhow::Context as _;
 use serenity::model::prelude::UserId;
-use serenity::model::prelude::ChannelId;
+use serenity::model::Permissions;
 use crate::config::EMBED_COLOR;
+use crate::perms;
+use crate::reports::{self, NewReport, Severity};
+use crate::resolve;
+use crate::suggestions;
+use crate::utils::truncate;
 
 
 #[group]
-#[commands(ping, say, userinfo, botinfo, invite, report, suggest)]
+#[commands(ping, say, userinfo, botinfo, changelog, invite, report, suggest)]
 struct General;
 
 #[command]
@@ -36,15 +41,35 @@ async fn ping(ctx: &Context, msg: &Message) -> CommandResult {
 }
 
 #[command]
-#[description("Makes the bot say anything")]
-#[usage("rsay <message>")]
+#[description("Makes the bot say anything. Mentions don't ping unless `--ping` is used by someone allowed to mention everyone")]
+#[usage("rsay [--ping] <message>")]
 async fn say(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
     instrument_command!("say", msg, {
         args.trimmed().quoted();
 
+        let ping = args.current() == Some("--ping");
+        if ping {
+            args.advance();
+            let allowed = match (msg.guild_id, msg.member(ctx).await) {
+                (Some(_), Ok(member)) => member.permissions(&ctx.cache).is_ok_and(|permissions| permissions.mention_everyone()),
+                _ => false,
+            };
+            if !allowed {
+                msg.reply(ctx, "You need the Mention Everyone permission to use `--ping`.").await?;
+                return Ok(());
+            }
+        }
+
         let reply_content = args.remains().unwrap_or("*(silence)*");
 
-        msg.reply(ctx, reply_content)
+        msg.channel_id
+            .send_message(ctx, |m| {
+                m.content(reply_content).reference_message(msg);
+                if !ping {
+                    m.allowed_mentions(|am| am.empty_parse().replied_user(true));
+                }
+                m
+            })
             .await
             .context("failed to send response message")?;
         Ok(())
@@ -53,15 +78,15 @@ async fn say(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
 
 #[command]
 #[description("Displays information about a user")] 
-#[usage("ruserinfo @barry")]
-async fn userinfo(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    // Parse the mentioned user
-    let user_id = match args.single::<UserId>() {
-        Ok(id) => id,
-        Err(_) => {
-            msg.reply(&ctx.http, "Invalid user provided.").await?;
-            return Ok(());
-        }
+#[usage("ruserinfo [user mention, ID or name]")]
+async fn userinfo(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    // Default to the author, otherwise resolve the given user
+    let user_id = match args.rest() {
+        "" => msg.author.id,
+        input => match resolve::member(ctx, msg, input).await? {
+            Some(user_id) => user_id,
+            None => return Ok(()),
+        },
     };
 
     // Fetch the user's information
@@ -82,19 +107,15 @@ async fn userinfo(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
     embed.color(EMBED_COLOR);
     embed.thumbnail(user.face());
 
-    embed.field("User", format!("{}", user.name), true);
+    embed.field("User", user.name.clone(), true);
     embed.field("ID", user.id, true);
     embed.field("Bot", user.bot, true);
     embed.field("Account Created", created_at.to_string(), true);
     embed.field("Server Joined", if let Some(guild_id) = msg.guild_id {
         match guild_id.member(&ctx.http, user_id).await {
-            Ok(member_option) => {
-                if let member = &member_option {
-                    if let Some(joined_at) = &member.joined_at {
-                        joined_at.to_string()
-                    } else {
-                        "N/A".to_string()
-                    }
+            Ok(member) => {
+                if let Some(joined_at) = &member.joined_at {
+                    joined_at.to_string()
                 } else {
                     "N/A".to_string()
                 }
@@ -113,18 +134,16 @@ async fn userinfo(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
 
     if let Some(guild_id) = msg.guild_id {
         match guild_id.member(&ctx.http, user_id).await {
-            Ok(member_option) => {
-                if let member = &member_option {
-                    // Additional information available only if the user is a member of the guild
-                    embed.field("Nickname", &member.display_name(), true);
-                    if let Some(guild) = guild_id.to_guild_cached(&ctx.cache) {
-                        let roles = member.roles.iter()
-                            .filter_map(|role_id| guild.roles.get(role_id).map(|role| role.name.clone()))
-                            .filter(|r| !r.is_empty())
-                            .collect::<Vec<_>>()
-                            .join(", ");
-                        embed.field("Roles", roles, true);
-                    }
+            Ok(member) => {
+                // Additional information available only if the user is a member of the guild
+                embed.field("Nickname", member.display_name(), true);
+                if let Some(guild) = guild_id.to_guild_cached(&ctx.cache) {
+                    let roles = member.roles.iter()
+                        .filter_map(|role_id| guild.roles.get(role_id).map(|role| role.name.clone()))
+                        .filter(|r| !r.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    embed.field("Roles", roles, true);
                 }
             }
             Err(_) => {
@@ -154,10 +173,14 @@ async fn botinfo(ctx: &Context, msg: &Message) -> CommandResult {
     let bot_desc = "A small project of mine written in Rust hence the name Rusty ";
     let server_count = ctx.cache.guild_count();
     let user_count = ctx.cache.user_count();
-    let bot_version = "`0.15`"; 
+    let current = crate::changelog::current();
+    let bot_version = format!("`{}`", crate::changelog::VERSION);
     let website_github_link = "[Website GitHub Repository](https://example.com)"; // placeholder
     let github_link = "[GitHub Repository](https://example.com)"; // placeholder
-    let recent_updates = "`Added Info commands`";
+    let recent_updates = match current {
+        Some(release) => format!("{}\n\nSee `rchangelog` for every release.", truncate(release.notes, 900)),
+        None => "No release notes.".to_string(),
+    };
 
     let mut embed = CreateEmbed::default();
     embed.title("Bot Info");
@@ -187,89 +210,139 @@ async fn botinfo(ctx: &Context, msg: &Message) -> CommandResult {
 }
 
 #[command]
-#[description("Get an invite link for the bot that requires Administrator permission to join a server.")]
+#[description("Shows the bot's release notes, newest first, or the notes for one version")]
+#[usage("rchangelog [version]")]
+async fn changelog(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let releases = match args.rest().trim() {
+        "" => crate::changelog::RELEASES.iter().collect::<Vec<_>>(),
+        version => match crate::changelog::find(version) {
+            Some(release) => vec![release],
+            None => {
+                msg.reply(ctx, format!("There are no release notes for version `{}`.", version)).await?;
+                return Ok(());
+            }
+        },
+    };
+    if releases.is_empty() {
+        msg.reply(ctx, "There are no release notes yet.").await?;
+        return Ok(());
+    }
+
+    super::show_pages(ctx, msg, releases.iter().map(|release| release.embed()).collect()).await
+}
+
+#[command]
+#[description("Get invite links for the bot: a minimal one covering every command and a full one that also covers automatic features")]
 #[usage("rinvite")]
 async fn invite(ctx: &Context, msg: &Message) -> CommandResult {
-    let invite_link = "https://discord.com/api/oauth2/authorize?client_id=1143479237882417282&permissions=8&scope=bot";
-    
+    let application = ctx.http.get_current_application_info().await?;
+    let (minimal, full) = perms::invite_permissions();
+    let link = |permissions: Permissions| {
+        format!(
+            "https://discord.com/api/oauth2/authorize?client_id={}&permissions={}&scope=bot",
+            application.id,
+            permissions.bits()
+        )
+    };
+
     let mut embed = CreateEmbed::default();
-    embed.title("Bot Invite Link");
-    embed.description(format!("Click [here]({}) to invite the bot to your server.", invite_link));
-    embed.colour(EMBED_COLOR); // Orange color
+    embed.title("Bot Invite Links");
+    embed.description(format!(
+        "[Minimal]({}): enough for every command.\n[Full]({}): also covers automod, anti-raid, join screening, sticky roles, invite tracking, modmail and `--ping`.",
+        link(minimal),
+        link(full)
+    ));
+    embed.field("Minimal Permissions", perms::names(minimal), false);
+    embed.field("Added by Full", perms::names(full - minimal), false);
+    embed.colour(EMBED_COLOR);
     embed.footer(|f| {
-        f.text(format!("Command Requested by: {}", msg.author.name));
+        f.text(format!("Requested by {}", msg.author.name));
         f.icon_url(msg.author.face());
         f
     });
-    
-    if let Err(why) = msg.channel_id.send_message(ctx, |m| m.set_embed(embed.clone())).await {
-        eprintln!("Error sending message: {:?}", why);
-    }
-    
+
+    msg.channel_id.send_message(ctx, |m| m.set_embed(embed)).await?;
+
     Ok(())
 }
 
 #[command]
-#[description("Report an issue with the bot")]
-#[usage("rreport <issue>")]
+#[description("Report a bug. Attach screenshots to the message to include them. Track reports with `rreports`")]
+#[usage("rreport [--severity <low/medium/high/critical>] [--label <label>] <issue>")]
+#[only_in(guilds)]
 #[min_args(1)]
-async fn report(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let issue = args.rest(); // Get the entire string after the command
-    let channel_id = ChannelId(1145565222875181136); // Change this to the correct ChannelId
-
-    let user = &msg.author;
-    let display_avatar_url = user.face();
+async fn report(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let mut severity = Severity::Medium;
+    let mut labels = Vec::new();
+    while let Some(flag) = args.current().filter(|arg| arg.starts_with("--")) {
+        let flag = flag.to_string();
+        args.advance();
+        let value = args.single::<String>().ok();
+        match (flag.as_str(), value) {
+            ("--severity", Some(value)) => match Severity::parse(&value) {
+                Some(parsed) => severity = parsed,
+                None => {
+                    msg.reply(&ctx.http, "Severity must be low, medium, high or critical.").await?;
+                    return Ok(());
+                }
+            },
+            ("--label", Some(value)) => labels.push(value.to_lowercase()),
+            _ => {
+                msg.reply(&ctx.http, format!("Unknown or incomplete option `{}`.", flag)).await?;
+                return Ok(());
+            }
+        }
+    }
 
-    let mut embed = CreateEmbed::default();
-    embed.color(EMBED_COLOR);
-    embed.title("New Issue Report");
-    embed.description(issue);
-    embed.footer(|f| {
-        f.text(format!("Reported by: {}", user.name));
-        f.icon_url(display_avatar_url);
-        f
-    });
+    let issue = args.rest(); // Get the entire string after the options
+    if issue.is_empty() {
+        msg.reply(&ctx.http, "Please describe the issue.").await?;
+        return Ok(());
+    }
 
-    if let Some(channel) = channel_id.to_channel(&ctx.http).await.unwrap().guild() {
-        if let Err(why) = channel.send_message(&ctx.http, |m| m.set_embed(embed.clone())).await {
-            eprintln!("Error sending message: {:?}", why);
+    match reports::create(ctx, msg, NewReport { description: issue, severity, labels }).await {
+        Ok(Some(report)) => {
+            msg.reply(
+                &ctx.http,
+                format!("Your issue has been filed as report #{} in <#{}>. Thank you for your feedback!", report.id, report.channel_id),
+            )
+            .await?;
+        }
+        Ok(None) => {
+            msg.reply(&ctx.http, "Reports aren't set up in this server. Ask a moderator to run `rconfig reports <channel>`.").await?;
+        }
+        Err(err) => {
+            error!("Failed to file report: {:?}", err);
+            msg.reply(&ctx.http, "Failed to file your report.").await?;
         }
-        msg.reply(&ctx.http, format!("Your issue has been reported in <#{}>. Thank you for your feedback!", channel_id)).await?;
-    } else {
-        msg.reply(&ctx.http, "Failed to find the designated channel.").await?;
     }
 
     Ok(())
 }
 
 #[command]
-#[description("Suggest a feature for the bot")]
+#[description("Suggest an idea for this server. Members vote on it in the suggestion channel")]
 #[usage("rsuggest <suggestion>")]
+#[only_in(guilds)]
 #[min_args(1)]
 async fn suggest(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     let suggestion = args.rest(); // Get the entire string after the command
-    let channel_id = ChannelId(1145567850896044122); // Change this to the correct ChannelId
-
-    let user = &msg.author;
-    let display_avatar_url = user.face();
 
-    let mut embed = CreateEmbed::default();
-    embed.color(EMBED_COLOR);
-    embed.title("New Suggestion");
-    embed.description(suggestion);
-    embed.footer(|f| {
-        f.text(format!("Suggested by: {}", user.name));
-        f.icon_url(display_avatar_url);
-        f
-    });
-
-    if let Some(channel) = channel_id.to_channel(&ctx.http).await.unwrap().guild() {
-        if let Err(why) = channel.send_message(&ctx.http, |m| m.set_embed(embed.clone())).await {
-            eprintln!("Error sending message: {:?}", why);
+    match suggestions::create(ctx, msg, suggestion).await {
+        Ok(Some(suggestion)) => {
+            msg.reply(
+                &ctx.http,
+                format!("Your suggestion #{} has been posted in <#{}>. Thank you for your feedback!", suggestion.id, suggestion.channel_id),
+            )
+            .await?;
+        }
+        Ok(None) => {
+            msg.reply(&ctx.http, "Suggestions aren't set up in this server. Ask a moderator to run `rconfig suggestions <channel>`.").await?;
+        }
+        Err(err) => {
+            error!("Failed to create suggestion: {:?}", err);
+            msg.reply(&ctx.http, "Failed to post your suggestion.").await?;
         }
-        msg.reply(&ctx.http, format!("Your suggestion has been sent to <#{}>. Thank you for your feedback!", channel_id)).await?;
-    } else {
-        msg.reply(&ctx.http, "Failed to find the designated channel.").await?;
     }
 
     Ok(())
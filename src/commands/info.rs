@@ -0,0 +1,438 @@
+use std::time::Duration;
+
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::framework::standard::{
+    macros::{command, group},
+    Args, CommandResult,
+};
+use serenity::model::channel::{Channel, ChannelType, Message, PermissionOverwriteType};
+use serenity::model::guild::PremiumTier;
+use serenity::model::prelude::{ChannelId, GuildId, User};
+use serenity::model::Timestamp;
+use crate::config::EMBED_COLOR;
+use crate::resolve;
+use crate::utils::{format_duration, truncate};
+
+/// Sizes offered for avatar and banner downloads.
+const IMAGE_SIZES: &[u16] = &[128, 256, 512, 1024, 2048, 4096];
+
+#[group]
+#[commands(serverinfo, roleinfo, channelinfo, avatar, banner)]
+struct Info;
+
+fn timestamp(time: Timestamp) -> String {
+    format!("<t:{0}:F> (<t:{0}:R>)", time.unix_timestamp())
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "Yes"
+    } else {
+        "No"
+    }
+}
+
+fn requested_by(embed: &mut CreateEmbed, msg: &Message) {
+    embed.footer(|f| {
+        f.text(format!("Requested by {}", msg.author.name));
+        f.icon_url(msg.author.face());
+        f
+    });
+}
+
+/// One line of download links per format for a CDN image, e.g. `avatars/<user>/<hash>`.
+fn image_links(path: &str, hash: &str) -> Vec<(&'static str, String)> {
+    let mut formats = vec!["png", "jpg", "webp"];
+    if hash.starts_with("a_") {
+        formats.push("gif");
+    }
+    formats
+        .into_iter()
+        .map(|format| {
+            let links = IMAGE_SIZES
+                .iter()
+                .map(|size| format!("[{}](https://cdn.discordapp.com/{}/{}.{}?size={})", size, path, hash, format, size))
+                .collect::<Vec<_>>()
+                .join(" | ");
+            (format, links)
+        })
+        .collect()
+}
+
+/// Builds an embed showing a CDN image with links to every size and format.
+fn image_embed(title: String, path: &str, hash: &str) -> CreateEmbed {
+    let preview_format = if hash.starts_with("a_") { "gif" } else { "png" };
+    let mut embed = CreateEmbed::default();
+    embed.title(title);
+    embed.image(format!("https://cdn.discordapp.com/{}/{}.{}?size=1024", path, hash, preview_format));
+    for (format, links) in image_links(path, hash) {
+        embed.field(format.to_uppercase(), links, false);
+    }
+    embed.color(EMBED_COLOR);
+    embed
+}
+
+fn tier_name(tier: PremiumTier) -> &'static str {
+    match tier {
+        PremiumTier::Tier1 => "Level 1",
+        PremiumTier::Tier2 => "Level 2",
+        PremiumTier::Tier3 => "Level 3",
+        _ => "None",
+    }
+}
+
+/// Turns a feature flag like `ANIMATED_ICON` into `Animated icon`.
+fn feature_name(feature: &str) -> String {
+    let lower = feature.replace('_', " ").to_lowercase();
+    let mut chars = lower.chars();
+    chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+}
+
+/// Resolves the user argument, defaulting to the author when no argument is given.
+async fn target_user(ctx: &Context, msg: &Message, args: &Args) -> serenity::Result<Option<User>> {
+    let user_id = match args.rest() {
+        "" => return Ok(Some(msg.author.clone())),
+        input => match resolve::member(ctx, msg, input).await? {
+            Some(user_id) => user_id,
+            None => return Ok(None),
+        },
+    };
+    let user = user_id.to_user(&ctx.http).await.ok();
+    if user.is_none() {
+        msg.reply(&ctx.http, "Failed to fetch user information.").await?;
+    }
+    Ok(user)
+}
+
+#[command]
+#[description("Displays information about this server")]
+#[usage("rserverinfo")]
+#[only_in(guilds)]
+#[num_args(0)]
+async fn serverinfo(ctx: &Context, msg: &Message) -> CommandResult {
+    let Some(guild) = msg.guild_id.unwrap().to_guild_cached(&ctx.cache) else {
+        msg.reply(&ctx.http, "Failed to fetch server information.").await?;
+        return Ok(());
+    };
+
+    let (mut text, mut voice, mut categories, mut other) = (0, 0, 0, 0);
+    for channel in guild.channels.values() {
+        match channel {
+            Channel::Guild(channel) => match channel.kind {
+                ChannelType::Text | ChannelType::News => text += 1,
+                ChannelType::Voice | ChannelType::Stage => voice += 1,
+                _ => other += 1,
+            },
+            Channel::Category(_) => categories += 1,
+            _ => other += 1,
+        }
+    }
+    let animated = guild.emojis.values().filter(|emoji| emoji.animated).count();
+
+    let features = if guild.features.is_empty() {
+        "None".to_string()
+    } else {
+        let mut features = guild.features.iter().map(|feature| feature_name(feature)).collect::<Vec<_>>();
+        features.sort();
+        truncate(&features.join(", "), 1000)
+    };
+
+    let mut embed = CreateEmbed::default();
+    embed.title(format!("Server Info for {}", guild.name));
+    if let Some(description) = &guild.description {
+        embed.description(description);
+    }
+    embed.color(EMBED_COLOR);
+    if let Some(icon) = guild.icon_url() {
+        embed.thumbnail(&icon);
+    }
+    if let Some(banner) = guild.banner_url() {
+        embed.image(banner);
+    }
+
+    embed.field("Owner", format!("<@{}>", guild.owner_id), true);
+    embed.field("ID", guild.id, true);
+    embed.field("Created", timestamp(guild.id.created_at()), false);
+    embed.field("Members", guild.member_count, true);
+    embed.field(
+        "Channels",
+        format!("Text: {}\nVoice: {}\nCategories: {}\nOther: {}", text, voice, categories, other),
+        true,
+    );
+    embed.field("Roles", guild.roles.len(), true);
+    embed.field(
+        "Emojis",
+        format!("Static: {}\nAnimated: {}\nStickers: {}", guild.emojis.len() - animated, animated, guild.stickers.len()),
+        true,
+    );
+    embed.field(
+        "Boosts",
+        format!("{} ({} boosts)", tier_name(guild.premium_tier), guild.premium_subscription_count),
+        true,
+    );
+    embed.field("Verification Level", format!("{:?}", guild.verification_level), true);
+    if let Some(code) = &guild.vanity_url_code {
+        embed.field("Vanity Invite", format!("discord.gg/{}", code), true);
+    }
+    embed.field("Features", features, false);
+
+    let mut images = Vec::new();
+    if let Some(icon) = guild.icon_url() {
+        images.push(format!("[Icon]({})", icon));
+    }
+    if let Some(banner) = guild.banner_url() {
+        images.push(format!("[Banner]({})", banner));
+    }
+    if let Some(splash) = guild.splash_url() {
+        images.push(format!("[Invite Splash]({})", splash));
+    }
+    if !images.is_empty() {
+        embed.field("Images", images.join(" | "), false);
+    }
+    requested_by(&mut embed, msg);
+
+    msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Displays information about a role")]
+#[usage("rroleinfo <role mention, ID or name>")]
+#[only_in(guilds)]
+#[min_args(1)]
+async fn roleinfo(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else {
+        msg.reply(&ctx.http, "Failed to fetch server information.").await?;
+        return Ok(());
+    };
+
+    let role = match resolve::role(&guild, args.rest()) {
+        Ok(role_id) => &guild.roles[&role_id],
+        Err(err) => {
+            msg.reply(&ctx.http, err).await?;
+            return Ok(());
+        }
+    };
+
+    let members = guild.members.values().filter(|member| member.roles.contains(&role.id)).count();
+    let permissions = if role.permissions.administrator() {
+        "Administrator (all permissions)".to_string()
+    } else if role.permissions.is_empty() {
+        "None".to_string()
+    } else {
+        truncate(&role.permissions.get_permission_names().join(", "), 1000)
+    };
+
+    let mut embed = CreateEmbed::default();
+    embed.title(format!("Role Info for {}", role.name));
+    embed.color(if role.colour.0 == 0 { EMBED_COLOR } else { role.colour });
+    embed.field("ID", role.id, true);
+    embed.field("Colour", format!("#{}", role.colour.hex()), true);
+    embed.field("Position", format!("{} of {}", role.position, guild.roles.len() - 1), true);
+    embed.field("Members", members, true);
+    embed.field("Mentionable", yes_no(role.mentionable), true);
+    embed.field("Hoisted", yes_no(role.hoist), true);
+    embed.field("Managed", yes_no(role.managed), true);
+    embed.field("Created", timestamp(role.id.created_at()), false);
+    embed.field("Permissions", permissions, false);
+    requested_by(&mut embed, msg);
+
+    msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Displays information about a channel. Defaults to the current channel")]
+#[usage("rchannelinfo [channel mention or ID]")]
+#[only_in(guilds)]
+async fn channelinfo(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let channel_id = if args.is_empty() { Ok(msg.channel_id) } else { args.single::<ChannelId>() };
+    let channel = match channel_id {
+        Ok(channel_id) => channel_id.to_channel(&ctx).await.ok(),
+        Err(_) => None,
+    };
+
+    let mut embed = CreateEmbed::default();
+    embed.color(EMBED_COLOR);
+    match channel {
+        Some(Channel::Guild(channel)) if channel.guild_id == guild_id => {
+            embed.title(format!("Channel Info for #{}", channel.name));
+            embed.field("ID", channel.id, true);
+            embed.field("Type", channel.kind.name().replace('_', " "), true);
+            if let Some(parent) = channel.parent_id {
+                embed.field("Parent", format!("<#{}>", parent), true);
+            }
+            embed.field("Created", timestamp(channel.id.created_at()), false);
+            if let Some(topic) = channel.topic.as_deref().filter(|topic| !topic.is_empty()) {
+                embed.field("Topic", truncate(topic, 1000), false);
+            }
+            let slowmode = channel.rate_limit_per_user.filter(|secs| *secs > 0);
+            embed.field("Slowmode", slowmode.map_or_else(|| "Off".to_string(), |secs| format_duration(Duration::from_secs(secs))), true);
+            embed.field("NSFW", yes_no(channel.nsfw), true);
+            if let Some(bitrate) = channel.bitrate {
+                embed.field("Bitrate", format!("{} kbps", bitrate / 1000), true);
+            }
+            if let Some(limit) = channel.user_limit.filter(|limit| *limit > 0) {
+                embed.field("User Limit", limit, true);
+            }
+            embed.field("Permission Overwrites", overwrites_summary(ctx, guild_id, &channel.permission_overwrites), false);
+        }
+        Some(Channel::Category(category)) if category.guild_id == guild_id => {
+            embed.title(format!("Category Info for {}", category.name));
+            embed.field("ID", category.id, true);
+            embed.field("Type", "category", true);
+            embed.field("Created", timestamp(category.id.created_at()), false);
+            embed.field("Permission Overwrites", overwrites_summary(ctx, guild_id, &category.permission_overwrites), false);
+        }
+        _ => {
+            msg.reply(&ctx.http, "Invalid channel provided.").await?;
+            return Ok(());
+        }
+    }
+    requested_by(&mut embed, msg);
+
+    msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+
+    Ok(())
+}
+
+/// One line per overwrite listing what it allows and denies.
+fn overwrites_summary(
+    ctx: &Context,
+    guild_id: GuildId,
+    overwrites: &[serenity::model::channel::PermissionOverwrite],
+) -> String {
+    if overwrites.is_empty() {
+        return "None".to_string();
+    }
+
+    let lines = overwrites
+        .iter()
+        .map(|overwrite| {
+            let target = match overwrite.kind {
+                PermissionOverwriteType::Role(role_id) if role_id.0 == guild_id.0 => "@everyone".to_string(),
+                PermissionOverwriteType::Role(role_id) => {
+                    ctx.cache.role(guild_id, role_id).map_or_else(|| format!("<@&{}>", role_id), |role| role.name)
+                }
+                PermissionOverwriteType::Member(user_id) => format!("<@{}>", user_id),
+                _ => "Unknown".to_string(),
+            };
+            let mut line = format!("**{}**", target);
+            if !overwrite.allow.is_empty() {
+                line.push_str(&format!(" ✅ {}", overwrite.allow.get_permission_names().join(", ")));
+            }
+            if !overwrite.deny.is_empty() {
+                line.push_str(&format!(" ❌ {}", overwrite.deny.get_permission_names().join(", ")));
+            }
+            line
+        })
+        .collect::<Vec<_>>();
+    truncate(&lines.join("\n"), 1000)
+}
+
+#[command]
+#[description("Shows a user's avatar with links to every size and format, including their server avatar")]
+#[usage("ravatar [user mention, ID or name]")]
+async fn avatar(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some(user) = target_user(ctx, msg, &args).await? else {
+        return Ok(());
+    };
+
+    let mut embeds = Vec::new();
+    match &user.avatar {
+        Some(hash) => embeds.push(image_embed(format!("Avatar for {}", user.tag()), &format!("avatars/{}", user.id), hash)),
+        None => {
+            let mut embed = CreateEmbed::default();
+            embed.title(format!("Avatar for {}", user.tag()));
+            embed.description("This user has the default avatar.");
+            embed.image(user.default_avatar_url());
+            embed.color(EMBED_COLOR);
+            embeds.push(embed);
+        }
+    }
+
+    if let Some(guild_id) = msg.guild_id {
+        if let Ok(member) = guild_id.member(&ctx, user.id).await {
+            if let Some(hash) = &member.avatar {
+                let path = format!("guilds/{}/users/{}/avatars", guild_id, user.id);
+                embeds.push(image_embed(format!("Server Avatar for {}", user.tag()), &path, hash));
+            }
+        }
+    }
+
+    if let Some(last) = embeds.last_mut() {
+        requested_by(last, msg);
+    }
+    // Two animated avatars' links together exceed the 6000 character limit for one message
+    for embed in embeds {
+        msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+    }
+
+    Ok(())
+}
+
+#[command]
+#[description("Shows a user's profile banner with links to every size and format")]
+#[usage("rbanner [user mention, ID or name]")]
+async fn banner(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some(user) = target_user(ctx, msg, &args).await? else {
+        return Ok(());
+    };
+    // Banners are only included when the user is fetched directly
+    let user = ctx.http.get_user(user.id.0).await?;
+
+    let mut embed = match &user.banner {
+        Some(hash) => image_embed(format!("Banner for {}", user.tag()), &format!("banners/{}", user.id), hash),
+        None => {
+            let mut embed = CreateEmbed::default();
+            embed.title(format!("Banner for {}", user.tag()));
+            embed.description(match user.accent_colour {
+                Some(colour) => format!("This user has no banner. Their accent colour is #{}.", colour.hex()),
+                None => "This user has no banner.".to_string(),
+            });
+            embed.color(user.accent_colour.unwrap_or(EMBED_COLOR));
+            embed
+        }
+    };
+    requested_by(&mut embed, msg);
+
+    msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_links_offer_gif_only_for_animated_hashes() {
+        let formats = |hash: &str| image_links("avatars/1", hash).into_iter().map(|(format, _)| format).collect::<Vec<_>>();
+        assert_eq!(formats("abc"), ["png", "jpg", "webp"]);
+        assert_eq!(formats("a_abc"), ["png", "jpg", "webp", "gif"]);
+
+        let (_, links) = &image_links("avatars/1", "abc")[0];
+        assert_eq!(links.matches("](https://cdn.discordapp.com/avatars/1/abc.png?size=").count(), IMAGE_SIZES.len());
+    }
+
+    #[test]
+    fn image_links_fit_in_one_embed() {
+        let path = format!("guilds/{}/users/{}/avatars", u64::MAX, u64::MAX);
+        let links = image_links(&path, "a_0123456789abcdef0123456789abcdef");
+        assert!(links.iter().all(|(_, links)| links.chars().count() <= 1024));
+        let total = links.iter().map(|(format, links)| format.len() + links.chars().count()).sum::<usize>();
+        assert!(total + 256 + 100 < 6000);
+    }
+
+    #[test]
+    fn feature_name_reads_naturally() {
+        assert_eq!(feature_name("ANIMATED_ICON"), "Animated icon");
+        assert_eq!(feature_name("COMMUNITY"), "Community");
+        assert_eq!(feature_name(""), "");
+    }
+}
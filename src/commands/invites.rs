@@ -0,0 +1,98 @@
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::framework::standard::{
+    macros::{command, group},
+    Args, CommandResult,
+};
+use serenity::model::channel::Message;
+use serenity::model::prelude::UserId;
+use crate::config::EMBED_COLOR;
+use crate::invites;
+
+/// Inviters shown on the leaderboard.
+const LEADERBOARD_SIZE: usize = 10;
+
+#[group]
+#[commands(invites)]
+struct Invites;
+
+#[command]
+#[description("Shows how many people a member invited and how many of them stayed")]
+#[usage("rinvites [user mention or ID] | rinvites leaderboard")]
+#[only_in(guilds)]
+#[sub_commands(leaderboard)]
+async fn invites(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let user_id = if args.is_empty() {
+        msg.author.id
+    } else {
+        match args.single::<UserId>() {
+            Ok(user_id) => user_id,
+            Err(_) => {
+                msg.reply(&ctx.http, "Invalid user provided.").await?;
+                return Ok(());
+            }
+        }
+    };
+    let user = user_id.to_user(&ctx.http).await?;
+
+    let stats = invites::stats(ctx, guild_id).await.remove(&user_id).unwrap_or_default();
+    let joined_via = invites::attribution(ctx, guild_id, user_id).await;
+
+    let mut embed = CreateEmbed::default();
+    embed.title(format!("Invites for {}", user.tag()));
+    embed.color(EMBED_COLOR);
+    embed.thumbnail(user.face());
+    embed.field("Invited", stats.invited, true);
+    embed.field("Stayed", stats.stayed, true);
+    embed.field("Left", stats.invited - stats.stayed, true);
+    embed.field("Joined Via", joined_via.map_or_else(|| "Unknown".to_string(), |a| a.describe()), false);
+    embed.footer(|f| {
+        f.text(format!("Requested by {}", msg.author.name));
+        f.icon_url(msg.author.face());
+        f
+    });
+
+    msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[aliases("top")]
+#[description("Lists the members whose invites brought in the most people who stayed")]
+#[usage("rinvites leaderboard")]
+#[only_in(guilds)]
+#[num_args(0)]
+async fn leaderboard(ctx: &Context, msg: &Message) -> CommandResult {
+    let mut stats = invites::stats(ctx, msg.guild_id.unwrap()).await.into_iter().collect::<Vec<_>>();
+    stats.sort_by(|(_, a), (_, b)| b.stayed.cmp(&a.stayed).then(b.invited.cmp(&a.invited)));
+
+    let description = if stats.is_empty() {
+        "No invites have been tracked yet.".to_string()
+    } else {
+        stats
+            .iter()
+            .take(LEADERBOARD_SIZE)
+            .enumerate()
+            .map(|(rank, (user_id, stats))| {
+                format!("**{}.** <@{}> — {} stayed ({} invited)", rank + 1, user_id, stats.stayed, stats.invited)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Invite Leaderboard");
+    embed.description(description);
+    embed.color(EMBED_COLOR);
+    embed.footer(|f| {
+        f.text(format!("Requested by {}", msg.author.name));
+        f.icon_url(msg.author.face());
+        f
+    });
+
+    msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+
+    Ok(())
+}
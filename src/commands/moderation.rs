@@ -6,35 +6,75 @@ use serenity::framework::standard::{
 use serenity::model::channel::Message;
 use serenity::builder::CreateEmbed;
 use serenity::framework::standard::CommandError;
-use serenity::model::prelude::UserId;
+use serenity::model::prelude::{ChannelId, MessageId, UserId};
+use std::time::Duration;
+use serenity::model::Timestamp;
+use regex::Regex;
 use crate::config::EMBED_COLOR;
+use crate::antiraid;
+use crate::cases::{self, CaseKind};
+use crate::lockdown;
+use crate::modlog;
+use crate::msglog;
+use crate::resolve::{self, MatchQuality};
+use crate::settings;
+use crate::utils::{format_duration, parse_duration};
+use crate::confirm::{confirm, confirm_always};
 
 #[group]
-#[commands(kick, ban, delete)]
+#[commands(kick, ban, warn, delete, raidmode, lock, unlock, slowmode)]
 struct Moderation;
 
+/// Confirms an action against a resolved member. Partial name matches are always confirmed,
+/// even for trusted roles, since they may have picked the wrong member.
+async fn confirm_target(
+    ctx: &Context,
+    msg: &Message,
+    input: &str,
+    quality: MatchQuality,
+    title: &str,
+    summary: &str,
+) -> anyhow::Result<bool> {
+    match quality {
+        MatchQuality::Exact => confirm(ctx, msg, title, summary).await,
+        MatchQuality::Partial => {
+            let summary = format!("{}\n\n`{}` only partially matches this member's name.", summary, input);
+            confirm_always(ctx, msg, title, &summary).await
+        }
+    }
+}
+
 #[command]
 #[description("Kicks a user from the server")]
-#[usage("rkick <user mention> <reason>")]
+#[usage("rkick <user mention, ID or name> <reason>")]
 #[required_permissions(KICK_MEMBERS)]   
 async fn kick(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let user_id = match args.single::<UserId>() {
-        Ok(user_id) => user_id,
+    let input = match args.single_quoted::<String>() {
+        Ok(input) => input,
         Err(err) => {
             let err_msg = format!("Invalid user provided for kick command: {:?}", err);
             error!("{}", &err_msg);
             return Err(CommandError::from(err_msg));
         }
     };
+    let Some((user_id, quality)) = resolve::member_match(ctx, msg, &input).await? else {
+        return Ok(());
+    };
     let reason = args.rest();
 
     // Get the member from the user ID
     if let Ok(member) = msg.guild_id.unwrap().member(&ctx.http, user_id).await {
+        let summary = format!("Kick **{}** ({}) from the server?\nReason: {}", member.user.tag(), user_id, reason);
+        if !confirm_target(ctx, msg, &input, quality, "Confirm Kick", &summary).await? {
+            return Ok(());
+        }
+
         if let Err(err) = member.kick(&ctx.http).await {
             let err_msg = format!("Failed to kick user: {}", err);
             error!("{}", &err_msg);
             return Err(CommandError::from(err_msg));
         }
+        cases::add_case(ctx, member.guild_id, CaseKind::Kick, user_id, Some(msg.author.id), reason).await;
 
 // Successfully kicked the user
         let mut embed = CreateEmbed::default();
@@ -58,26 +98,35 @@ async fn kick(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
 
 #[command]
 #[description("Bans a user from the server")]
-#[usage("rban <user mention or ID> <reason>")]
+#[usage("rban <user mention, ID or name> <reason>")]
 #[required_permissions(BAN_MEMBERS)]
 async fn ban(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let user_id = match args.single::<UserId>() {
-        Ok(user_id) => user_id,
+    let input = match args.single_quoted::<String>() {
+        Ok(input) => input,
         Err(err) => {
             let err_msg = format!("Invalid user provided for ban command: {:?}", err);
             error!("{}", &err_msg);
             return Err(CommandError::from(err_msg));
         }
     };
+    let Some((user_id, quality)) = resolve::member_match(ctx, msg, &input).await? else {
+        return Ok(());
+    };
     let reason = args.rest();
 
     // Get the member from the user ID
     if let Ok(member) = msg.guild_id.unwrap().member(&ctx.http, user_id).await {
+        let summary = format!("Ban **{}** ({}) from the server?\nReason: {}", member.user.tag(), user_id, reason);
+        if !confirm_target(ctx, msg, &input, quality, "Confirm Ban", &summary).await? {
+            return Ok(());
+        }
+
         if let Err(err) = member.ban_with_reason(&ctx.http, 0, reason).await {
             let err_msg = format!("Failed to ban user: {}", err);
             error!("{}", &err_msg);
             return Err(CommandError::from(err_msg));
         }
+        cases::add_case(ctx, member.guild_id, CaseKind::Ban, user_id, Some(msg.author.id), reason).await;
 
         // Successfully banned the user
         let mut embed = CreateEmbed::default();
@@ -99,28 +148,509 @@ async fn ban(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
 }
 
 #[command]
-#[description("Delete a specified number of messages")]
-#[usage("rdelete <count>")]
+#[description("Warns a member and records it in their moderation history")]
+#[usage("rwarn <user mention, ID or name> <reason>")]
+#[required_permissions(MODERATE_MEMBERS)]
+#[only_in(guilds)]
+#[min_args(2)]
+async fn warn(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let input = args.single_quoted::<String>()?;
+    let Some((user_id, quality)) = resolve::member_match(ctx, msg, &input).await? else {
+        return Ok(());
+    };
+    let user = match guild_id.member(&ctx.http, user_id).await {
+        Ok(member) => member.user,
+        Err(_) => {
+            msg.reply(&ctx.http, "That user isn't a member of this server.").await?;
+            return Ok(());
+        }
+    };
+    let reason = args.rest();
+
+    // Warnings skip confirmation unless the member was only guessed from a partial name
+    if quality == MatchQuality::Partial {
+        let summary = format!("Warn **{}** ({})?\nReason: {}", user.tag(), user.id, reason);
+        if !confirm_target(ctx, msg, &input, quality, "Confirm Warn", &summary).await? {
+            return Ok(());
+        }
+    }
+
+    let case = cases::add_case(ctx, guild_id, CaseKind::Warn, user.id, Some(msg.author.id), reason).await;
+    let guild_name = guild_id.name(&ctx.cache).unwrap_or_else(|| "the server".to_string());
+    let notified = user
+        .direct_message(&ctx.http, |m| m.content(format!("You have been warned in **{}**: {}", guild_name, reason)))
+        .await
+        .is_ok();
+
+    let mut embed = CreateEmbed::default();
+    embed.title(match case {
+        Some(id) => format!("User Warned (case #{})", id),
+        None => "User Warned".to_string(),
+    });
+    embed.description(format!("Warned user: {} ({})\nReason: {}", user.tag(), user.id, reason));
+    if !notified {
+        embed.field("Note", "Couldn't DM the user.", false);
+    }
+    embed.color(EMBED_COLOR);
+    embed.footer(|f| {
+        f.text(format!("Requested by {}", msg.author.name));
+        f.icon_url(msg.author.face());
+        f
+    });
+
+    msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+    info!("User warned: {}", user.id);
+
+    Ok(())
+}
+
+#[command]
+#[description("Delete a number of recent messages, optionally filtered by author or content")]
+#[usage("rdelete <count> [--user @user] [--bots] [--contains <word>] [--regex <pattern>] [--attachments] [--embeds] [--links] [--before <message id>] [--after <message id>]")]
 #[required_permissions(MANAGE_MESSAGES)]
+#[only_in(guilds)]
 async fn delete(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let count_to_delete = args.single::<u64>()?;
-    
-    if count_to_delete == 0 || count_to_delete > 100 {
-        let _ = msg.reply(&ctx.http, "You can only delete between 1 and 100 messages at a time.").await?;
+    let count_to_delete = match args.single::<usize>() {
+        Ok(count) if (1..=MAX_PURGE).contains(&count) => count,
+        _ => {
+            msg.reply(&ctx.http, format!("You can only delete between 1 and {} messages at a time.", MAX_PURGE)).await?;
+            return Ok(());
+        }
+    };
+
+    let filter = match PurgeFilter::parse(&mut args) {
+        Ok(filter) => filter,
+        Err(err) => {
+            msg.reply(&ctx.http, err).await?;
+            return Ok(());
+        }
+    };
+
+    // Walk backwards through the channel history until enough matching messages are found
+    let mut matched = Vec::new();
+    let mut cursor = filter.before.unwrap_or(msg.id);
+    let mut scanned = 0;
+    'fetch: while matched.len() < count_to_delete && scanned < MAX_PURGE_SCAN {
+        let page = msg
+            .channel_id
+            .messages(&ctx.http, |retriever| retriever.before(cursor).limit(100))
+            .await?;
+        let Some(last) = page.last() else { break };
+        cursor = last.id;
+        scanned += page.len();
+
+        for message in &page {
+            if filter.after.is_some_and(|after| message.id <= after) {
+                break 'fetch;
+            }
+            if filter.matches(message) {
+                matched.push(message.clone());
+                if matched.len() == count_to_delete {
+                    break 'fetch;
+                }
+            }
+        }
+
+        if page.len() < 100 {
+            break;
+        }
+    }
+
+    if matched.is_empty() {
+        msg.reply(&ctx.http, "No matching messages were found.").await?;
         return Ok(());
     }
 
-    // Delete the specified number of messages
-    let messages = msg
-        .channel_id
-        .messages(&ctx.http, |retriever| retriever.limit(count_to_delete + 1))
-        .await?;
-    let _ = msg.channel_id.delete_messages(&ctx.http, messages).await;
+    let summary = format!("Delete {} messages from <#{}>?", matched.len(), msg.channel_id);
+    if !confirm(ctx, msg, "Confirm Delete", &summary).await? {
+        return Ok(());
+    }
+
+    // Bulk delete refuses messages older than 14 days, so those are removed one by one
+    let bulk_cutoff = Timestamp::now().unix_timestamp() - BULK_DELETE_MAX_AGE;
+    let (recent, old): (Vec<Message>, Vec<Message>) = matched
+        .into_iter()
+        .partition(|message| message.id.created_at().unix_timestamp() > bulk_cutoff);
+
+    let ids = recent.iter().chain(&old).map(|message| message.id).collect::<Vec<_>>();
+    msglog::suppress(ctx, &ids).await;
+
+    let mut deleted = Vec::new();
+    for chunk in recent.chunks(100) {
+        let chunk_ids = chunk.iter().map(|message| message.id).collect::<Vec<_>>();
+        let result = if chunk_ids.len() == 1 {
+            msg.channel_id.delete_message(&ctx.http, chunk_ids[0]).await
+        } else {
+            msg.channel_id.delete_messages(&ctx.http, &chunk_ids).await
+        };
+        match result {
+            Ok(()) => deleted.extend_from_slice(chunk),
+            Err(err) => error!("Failed to bulk delete messages: {}", err),
+        }
+    }
+    for message in &old {
+        match msg.channel_id.delete_message(&ctx.http, message.id).await {
+            Ok(()) => deleted.push(message.clone()),
+            Err(err) => error!("Failed to delete message {}: {}", message.id, err),
+        }
+    }
+
+    let _ = msg.delete(&ctx.http).await;
+
+    let reply = if deleted.is_empty() {
+        "No matching messages were deleted.".to_string()
+    } else {
+        msglog::log_purge(ctx, msg.guild_id.unwrap(), msg.channel_id, &deleted, &msg.author).await;
+        format!("Successfully deleted {} messages.", deleted.len())
+    };
+    msg.channel_id.say(&ctx.http, reply).await?;
+    info!("Purged {} messages in channel {}", deleted.len(), msg.channel_id);
+
+    Ok(())
+}
+
+#[command]
+#[description("Turns raid mode on or off, or shows its status. While active, new joins are handled according to the anti-raid settings.")]
+#[usage("rraidmode <on/off/status>")]
+#[required_permissions(MANAGE_GUILD)]
+#[only_in(guilds)]
+async fn raidmode(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let operation = args.single::<String>().unwrap_or_else(|_| "status".to_string());
+
+    match operation.to_lowercase().as_str() {
+        "on" => {
+            let reason = format!("Manually enabled by {}", msg.author.tag());
+            if antiraid::activate(ctx, guild_id, reason, Vec::new(), false).await {
+                msg.reply(&ctx.http, "Raid mode is now active.").await?;
+            } else {
+                msg.reply(&ctx.http, "Raid mode is already active.").await?;
+            }
+        }
+        "off" => {
+            if antiraid::deactivate(ctx, guild_id).await {
+                msg.reply(&ctx.http, "Raid mode has been turned off.").await?;
+            } else {
+                msg.reply(&ctx.http, "Raid mode isn't active.").await?;
+            }
+        }
+        "status" => {
+            let mut embed = CreateEmbed::default();
+            embed.title("Raid Mode");
+            embed.color(EMBED_COLOR);
+            match antiraid::current(ctx, guild_id).await {
+                Some(raid) => {
+                    embed.field("Status", "Active", true);
+                    embed.field("Since", format!("<t:{}:R>", raid.since.unix_timestamp()), true);
+                    embed.field("Trigger", raid.reason, false);
+                    let (suspects, total) = antiraid::suspects(ctx, guild_id).await;
+                    embed.field(format!("Suspicious Accounts ({})", total), antiraid::suspect_list(&suspects), false);
+                }
+                None => {
+                    embed.field("Status", "Inactive", true);
+                }
+            }
+            embed.footer(|f| {
+                f.text(format!("Requested by {}", msg.author.name));
+                f.icon_url(msg.author.face());
+                f
+            });
+            msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+        }
+        _ => {
+            msg.reply(&ctx.http, "Usage: `rraidmode <on/off/status>`").await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolves the channel a command applies to from a channel mention or ID, defaulting to the
+/// current channel. Returns `None` if the channel isn't in this server.
+fn target_channel(ctx: &Context, msg: &Message, args: &mut Args) -> Option<ChannelId> {
+    match args.single::<ChannelId>() {
+        Ok(channel_id) => ctx
+            .cache
+            .guild_channel(channel_id)
+            .filter(|channel| Some(channel.guild_id) == msg.guild_id)
+            .map(|channel| channel.id),
+        Err(_) => Some(msg.channel_id),
+    }
+}
+
+/// Resolves the channels a lock command applies to: `all` for the configured lockdown
+/// channels, a channel mention or ID, or the current channel. Returns `None` if the channel
+/// isn't in this server.
+async fn lock_targets(ctx: &Context, msg: &Message, args: &mut Args) -> Option<Vec<ChannelId>> {
+    if args.current().is_some_and(|arg| arg.eq_ignore_ascii_case("all")) {
+        args.advance();
+        return Some(settings::get(ctx, msg.guild_id.unwrap()).await.lockdown.channels);
+    }
 
-    let _ = msg
-        .channel_id
-        .say(&ctx.http, format!("Successfully deleted {} messages.", count_to_delete))
+    target_channel(ctx, msg, args).map(|channel_id| vec![channel_id])
+}
+
+#[command]
+#[description("Stops @everyone from sending messages in a channel, or in every configured lockdown channel with `all`. The original permissions are restored by `unlock`.")]
+#[usage("rlock [channel mention or ID/all] [notice]")]
+#[required_permissions(MANAGE_CHANNELS)]
+#[only_in(guilds)]
+async fn lock(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let Some(targets) = lock_targets(ctx, msg, &mut args).await else {
+        msg.reply(&ctx.http, "Invalid channel provided.").await?;
+        return Ok(());
+    };
+    if targets.is_empty() {
+        msg.reply(&ctx.http, "No lockdown channels are configured. Add some with `rconfig lockdown add <channel>`.").await?;
+        return Ok(());
+    }
+
+    let notice = match args.remains() {
+        Some(notice) => Some(notice.to_string()),
+        None => settings::get(ctx, guild_id).await.lockdown.notice,
+    };
+
+    let mut locked = Vec::new();
+    let mut failed = Vec::new();
+    for channel_id in &targets {
+        match lockdown::lock(ctx, *channel_id).await {
+            Ok(true) => locked.push(*channel_id),
+            Ok(false) => {}
+            Err(err) => {
+                error!("Failed to lock channel {}: {}", channel_id, err);
+                failed.push(*channel_id);
+            }
+        }
+    }
+
+    for channel_id in &locked {
+        let result = channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.title("🔒 Channel Locked");
+                    e.description(notice.as_deref().unwrap_or("This channel has been locked by the moderators."));
+                    e.color(EMBED_COLOR)
+                })
+            })
+            .await;
+        if let Err(err) = result {
+            error!("Failed to post lockdown notice in {}: {}", channel_id, err);
+        }
+    }
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Channels Locked");
+    embed.field("Moderator", format!("{} ({})", msg.author.tag(), msg.author.id), false);
+    embed.field("Channels", channel_list(&locked), false);
+    modlog::log(ctx, guild_id, embed).await;
+
+    msg.reply(&ctx.http, lock_report("Locked", targets.len(), &locked, &failed)).await?;
+    info!("Locked {} channels", locked.len());
+
+    Ok(())
+}
+
+#[command]
+#[description("Restores the permissions a channel had before it was locked, or every locked lockdown channel with `all`")]
+#[usage("runlock [channel mention or ID/all]")]
+#[required_permissions(MANAGE_CHANNELS)]
+#[only_in(guilds)]
+async fn unlock(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let Some(targets) = lock_targets(ctx, msg, &mut args).await else {
+        msg.reply(&ctx.http, "Invalid channel provided.").await?;
+        return Ok(());
+    };
+    if targets.is_empty() {
+        msg.reply(&ctx.http, "No lockdown channels are configured.").await?;
+        return Ok(());
+    }
+
+    let mut unlocked = Vec::new();
+    let mut failed = Vec::new();
+    for channel_id in &targets {
+        match lockdown::unlock(ctx, *channel_id).await {
+            Ok(true) => unlocked.push(*channel_id),
+            Ok(false) => {}
+            Err(err) => {
+                error!("Failed to unlock channel {}: {}", channel_id, err);
+                failed.push(*channel_id);
+            }
+        }
+    }
+
+    for channel_id in &unlocked {
+        let result = channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| e.title("🔓 Channel Unlocked").color(EMBED_COLOR))
+            })
+            .await;
+        if let Err(err) = result {
+            error!("Failed to post unlock notice in {}: {}", channel_id, err);
+        }
+    }
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Channels Unlocked");
+    embed.field("Moderator", format!("{} ({})", msg.author.tag(), msg.author.id), false);
+    embed.field("Channels", channel_list(&unlocked), false);
+    modlog::log(ctx, guild_id, embed).await;
+
+    msg.reply(&ctx.http, lock_report("Unlocked", targets.len(), &unlocked, &failed)).await?;
+    info!("Unlocked {} channels", unlocked.len());
+
+    Ok(())
+}
+
+fn channel_list(channels: &[ChannelId]) -> String {
+    if channels.is_empty() {
+        "None".to_string()
+    } else {
+        channels.iter().map(|channel| format!("<#{}>", channel)).collect::<Vec<_>>().join(", ")
+    }
+}
+
+fn lock_report(verb: &str, requested: usize, changed: &[ChannelId], failed: &[ChannelId]) -> String {
+    let mut report = format!("{} {} of {} channels.", verb, changed.len(), requested);
+    let skipped = requested - changed.len() - failed.len();
+    if skipped > 0 {
+        report.push_str(&format!(" {} were already {}.", skipped, verb.to_lowercase()));
+    }
+    if !failed.is_empty() {
+        report.push_str(&format!(" Failed: {}", channel_list(failed)));
+    }
+    report
+}
+
+/// Discord's maximum slowmode interval.
+const MAX_SLOWMODE: Duration = Duration::from_secs(6 * 60 * 60);
+
+#[command]
+#[description("Sets the slowmode interval of a channel. Use `off` or `0` to disable it.")]
+#[usage("rslowmode <duration, e.g. 10s/5m/1h/off> [channel mention or ID]")]
+#[required_permissions(MANAGE_CHANNELS)]
+#[only_in(guilds)]
+#[min_args(1)]
+async fn slowmode(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let input = args.single::<String>()?;
+    let duration = if input.eq_ignore_ascii_case("off") {
+        Some(Duration::ZERO)
+    } else {
+        parse_duration(&input)
+    };
+    let Some(duration) = duration.filter(|duration| *duration <= MAX_SLOWMODE) else {
+        msg.reply(&ctx.http, "Slowmode must be a duration between 0s and 6h.").await?;
+        return Ok(());
+    };
+    let Some(channel_id) = target_channel(ctx, msg, &mut args) else {
+        msg.reply(&ctx.http, "Invalid channel provided.").await?;
+        return Ok(());
+    };
+
+    channel_id
+        .edit(&ctx.http, |c| c.rate_limit_per_user(duration.as_secs()))
         .await?;
 
+    let reply = if duration.is_zero() {
+        format!("Slowmode disabled in <#{}>.", channel_id)
+    } else {
+        format!("Slowmode in <#{}> set to {}.", channel_id, format_duration(duration))
+    };
+    msg.reply(&ctx.http, reply).await?;
+
     Ok(())
 }
+
+/// Upper bound on how many messages a single `delete` may remove.
+const MAX_PURGE: usize = 1000;
+/// Upper bound on how far back `delete` scans looking for matches.
+const MAX_PURGE_SCAN: usize = 5000;
+/// Discord rejects bulk deletes containing messages older than 14 days.
+const BULK_DELETE_MAX_AGE: i64 = 14 * 24 * 60 * 60;
+
+/// Filters accepted by `delete`, all of which must match for a message to be removed.
+#[derive(Default)]
+struct PurgeFilter {
+    user: Option<UserId>,
+    bots: bool,
+    contains: Option<String>,
+    regex: Option<Regex>,
+    attachments: bool,
+    embeds: bool,
+    links: bool,
+    before: Option<MessageId>,
+    after: Option<MessageId>,
+}
+
+impl PurgeFilter {
+    fn parse(args: &mut Args) -> Result<Self, String> {
+        let mut filter = PurgeFilter::default();
+        args.quoted();
+
+        while !args.is_empty() {
+            let flag = args.single::<String>().map_err(|_| "Invalid filter.".to_string())?;
+            match flag.as_str() {
+                "--user" => {
+                    let user = args.single::<UserId>().map_err(|_| "`--user` expects a user mention or ID.")?;
+                    filter.user = Some(user);
+                }
+                "--bots" => filter.bots = true,
+                "--contains" => {
+                    let word = args.single_quoted::<String>().map_err(|_| "`--contains` expects some text.")?;
+                    filter.contains = Some(word.to_lowercase());
+                }
+                "--regex" => {
+                    let pattern = args.single_quoted::<String>().map_err(|_| "`--regex` expects a pattern.")?;
+                    let regex = Regex::new(&pattern).map_err(|err| format!("Invalid regex: {}", err))?;
+                    filter.regex = Some(regex);
+                }
+                "--attachments" => filter.attachments = true,
+                "--embeds" => filter.embeds = true,
+                "--links" => filter.links = true,
+                "--before" => {
+                    let id = args.single::<u64>().map_err(|_| "`--before` expects a message ID.")?;
+                    filter.before = Some(MessageId(id));
+                }
+                "--after" => {
+                    let id = args.single::<u64>().map_err(|_| "`--after` expects a message ID.")?;
+                    filter.after = Some(MessageId(id));
+                }
+                other => return Err(format!("Unknown filter `{}`.", other)),
+            }
+        }
+
+        Ok(filter)
+    }
+
+    fn matches(&self, message: &Message) -> bool {
+        if self.user.is_some_and(|user| message.author.id != user) {
+            return false;
+        }
+        if self.bots && !message.author.bot {
+            return false;
+        }
+        if let Some(word) = &self.contains {
+            if !message.content.to_lowercase().contains(word.as_str()) {
+                return false;
+            }
+        }
+        if let Some(regex) = &self.regex {
+            if !regex.is_match(&message.content) {
+                return false;
+            }
+        }
+        if self.attachments && message.attachments.is_empty() {
+            return false;
+        }
+        if self.embeds && message.embeds.is_empty() {
+            return false;
+        }
+        if self.links && !(message.content.contains("http://") || message.content.contains("https://")) {
+            return false;
+        }
+
+        true
+    }
+}
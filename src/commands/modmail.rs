@@ -0,0 +1,90 @@
+use serenity::client::Context;
+use serenity::framework::standard::{
+    macros::{command, group},
+    Args, CommandResult,
+};
+use serenity::model::channel::Message;
+use serenity::model::prelude::UserId;
+use crate::modmail::{self, Thread};
+use crate::settings;
+
+#[group]
+#[commands(reply, areply, close)]
+struct Modmail;
+
+/// Whether the author is modmail staff: a modmail staff role or Manage Messages.
+async fn is_staff(ctx: &Context, msg: &Message) -> bool {
+    let config = settings::get(ctx, msg.guild_id.unwrap()).await.modmail;
+    let Ok(member) = msg.member(&ctx).await else { return false };
+    let permissions = member.permissions(&ctx.cache).unwrap_or_default();
+    config.is_staff(&member.roles, permissions)
+}
+
+/// Looks up the modmail thread for the current channel if the author is modmail staff,
+/// replying otherwise.
+async fn current_thread(ctx: &Context, msg: &Message) -> serenity::Result<Option<(UserId, Thread)>> {
+    if !is_staff(ctx, msg).await {
+        msg.reply(&ctx.http, "Only modmail staff can do that.").await?;
+        return Ok(None);
+    }
+    let thread = modmail::thread_for_channel(ctx, msg.channel_id).await;
+    if thread.is_none() {
+        msg.reply(&ctx.http, "This isn't an open modmail thread.").await?;
+    }
+    Ok(thread)
+}
+
+async fn send_reply(ctx: &Context, msg: &Message, args: Args, anonymous: bool) -> CommandResult {
+    let Some((user_id, _)) = current_thread(ctx, msg).await? else {
+        return Ok(());
+    };
+    let text = args.rest();
+    if text.is_empty() && msg.attachments.is_empty() {
+        msg.reply(&ctx.http, "Write a message or attach a file to reply with.").await?;
+        return Ok(());
+    }
+
+    if let Err(err) = modmail::reply(ctx, msg, user_id, text, anonymous).await {
+        error!("Failed to send modmail reply to {}: {:?}", user_id, err);
+        msg.reply(&ctx.http, "Failed to deliver the reply. The user may have DMs closed.").await?;
+    }
+
+    Ok(())
+}
+
+#[command]
+#[description("Replies to the user of a modmail thread, showing your name")]
+#[usage("rreply <message>")]
+#[only_in(guilds)]
+async fn reply(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    send_reply(ctx, msg, args, false).await
+}
+
+#[command]
+#[description("Replies to the user of a modmail thread anonymously")]
+#[usage("rareply <message>")]
+#[only_in(guilds)]
+async fn areply(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    send_reply(ctx, msg, args, true).await
+}
+
+#[command]
+#[description("Closes a modmail thread, saving its transcript and deleting the channel")]
+#[usage("rclose [reason]")]
+#[only_in(guilds)]
+async fn close(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some((user_id, thread)) = current_thread(ctx, msg).await? else {
+        return Ok(());
+    };
+    let reason = match args.rest() {
+        "" => "No reason given",
+        reason => reason,
+    };
+
+    if let Err(err) = modmail::close(ctx, msg, user_id, &thread, reason).await {
+        error!("Failed to close modmail thread for {}: {:?}", user_id, err);
+        msg.reply(&ctx.http, "Failed to close this thread.").await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,210 @@
+use std::borrow::Cow;
+
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::framework::standard::{
+    macros::{command, group},
+    Args, CommandResult,
+};
+use serenity::model::channel::{AttachmentType, Channel, Message};
+use serenity::model::prelude::ChannelId;
+use crate::config::EMBED_COLOR;
+use crate::perms::{self, Explanation};
+use crate::resolve;
+use crate::utils::truncate;
+
+#[group]
+#[commands(perms, checkperms, audit)]
+struct Permissions;
+
+#[command]
+#[description("Shows a member's effective permissions, optionally in a channel, and what granted or denied each one")]
+#[usage("rperms <user mention, ID or name> [channel mention or ID]")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[min_args(1)]
+async fn perms(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let Some(user_id) = resolve::member(ctx, msg, &args.single_quoted::<String>()?).await? else {
+        return Ok(());
+    };
+    let Ok(member) = guild_id.member(&ctx, user_id).await else {
+        msg.reply(&ctx.http, "That user isn't a member of this server.").await?;
+        return Ok(());
+    };
+    let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else {
+        msg.reply(&ctx.http, "Failed to load this server.").await?;
+        return Ok(());
+    };
+
+    let channel = if args.is_empty() {
+        None
+    } else {
+        let channel = match args.single::<ChannelId>() {
+            Ok(channel_id) => channel_id.to_channel(&ctx).await.ok(),
+            Err(_) => None,
+        };
+        match channel {
+            Some(Channel::Guild(channel)) if channel.guild_id == guild_id => Some((channel.name, channel.permission_overwrites)),
+            Some(Channel::Category(category)) if category.guild_id == guild_id => {
+                Some((category.name, category.permission_overwrites))
+            }
+            _ => {
+                msg.reply(&ctx.http, "Invalid channel provided.").await?;
+                return Ok(());
+            }
+        }
+    };
+
+    let explanations = perms::explain(&guild, &member, channel.as_ref().map(|(_, overwrites)| overwrites.as_slice()));
+    let line = |e: &Explanation| format!("{} **{}**: {}", if e.allowed { "✅" } else { "❌" }, e.name(), e.reason);
+
+    let description = match explanations.first() {
+        // The owner and administrators get everything for the same reason, so say it once
+        Some(first) if explanations.iter().all(|e| e.allowed && e.reason == first.reason) => {
+            format!("✅ Every permission ({})", first.reason)
+        }
+        _ => {
+            let allowed = explanations.iter().filter(|e| e.allowed).map(line).collect::<Vec<_>>();
+            let denied = explanations
+                .iter()
+                .filter(|e| !e.allowed && e.reason != perms::NOT_GRANTED)
+                .map(line)
+                .collect::<Vec<_>>();
+            let missing = explanations
+                .iter()
+                .filter(|e| !e.allowed && e.reason == perms::NOT_GRANTED)
+                .map(Explanation::name)
+                .collect::<Vec<_>>();
+
+            let mut sections = vec![format!("**Allowed**\n{}", if allowed.is_empty() { "None".to_string() } else { allowed.join("\n") })];
+            if !denied.is_empty() {
+                sections.push(format!("**Denied**\n{}", denied.join("\n")));
+            }
+            if !missing.is_empty() {
+                sections.push(format!("**Not granted by any role**\n{}", missing.join(", ")));
+            }
+            sections.join("\n\n")
+        }
+    };
+
+    let mut embed = CreateEmbed::default();
+    embed.title(match &channel {
+        Some((name, _)) => format!("Permissions for {} in #{}", member.user.tag(), name),
+        None => format!("Server Permissions for {}", member.user.tag()),
+    });
+    embed.description(truncate(&description, 4000));
+    embed.color(EMBED_COLOR);
+    embed.footer(|f| {
+        f.text(format!("Requested by {}", msg.author.name));
+        f.icon_url(msg.author.face());
+        f
+    });
+
+    msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Reports the permissions the bot is missing in this server and the commands and features they break")]
+#[usage("rcheckperms")]
+#[only_in(guilds)]
+async fn checkperms(ctx: &Context, msg: &Message) -> CommandResult {
+    let member = msg.guild_id.unwrap().member(&ctx, ctx.cache.current_user_id()).await?;
+    let granted = member.permissions(&ctx.cache)?;
+    // The group is called `Permissions`, so the type is spelled out here
+    let missing = |needed: serenity::model::Permissions| {
+        if granted.administrator() {
+            serenity::model::Permissions::empty()
+        } else {
+            needed - granted
+        }
+    };
+
+    let requirements = perms::command_requirements();
+    let commands = requirements
+        .iter()
+        .filter(|requirement| !missing(requirement.permissions).is_empty())
+        .map(|requirement| format!("`r{}`: {}", requirement.name, perms::names(missing(requirement.permissions))))
+        .collect::<Vec<_>>();
+    let features = perms::FEATURE_PERMISSIONS
+        .iter()
+        .filter(|(_, needed)| !missing(*needed).is_empty())
+        .map(|(name, needed)| format!("{}: {}", name, perms::names(missing(*needed))))
+        .collect::<Vec<_>>();
+    let (_, full) = perms::invite_permissions();
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Bot Permission Check");
+    if commands.is_empty() && features.is_empty() {
+        embed.description("✅ The bot has every permission its commands and features use.");
+    } else {
+        embed.description(format!(
+            "❌ Missing: {}\n\nChannel overwrites can still take permissions away in specific channels. Check one with `rperms <bot> <channel>`.",
+            perms::names(missing(full))
+        ));
+    }
+    if !commands.is_empty() {
+        embed.field("Affected Commands", truncate(&commands.join("\n"), 1000), false);
+    }
+    if !features.is_empty() {
+        embed.field("Affected Features", truncate(&features.join("\n"), 1000), false);
+    }
+    embed.color(EMBED_COLOR);
+    embed.footer(|f| {
+        f.text(format!("Requested by {}", msg.author.name));
+        f.icon_url(msg.author.face());
+        f
+    });
+
+    msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Audits the server's configuration")]
+#[usage("raudit perms")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+#[sub_commands(audit_perms)]
+async fn audit(ctx: &Context, msg: &Message) -> CommandResult {
+    msg.reply(&ctx.http, "Usage: `raudit perms`").await?;
+    Ok(())
+}
+
+#[command("perms")]
+#[description("Exports a report of roles, members and @everyone overwrites holding dangerous permissions")]
+#[usage("raudit perms")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+async fn audit_perms(ctx: &Context, msg: &Message) -> CommandResult {
+    let Some(guild) = msg.guild_id.unwrap().to_guild_cached(&ctx.cache) else {
+        msg.reply(&ctx.http, "Failed to load this server.").await?;
+        return Ok(());
+    };
+
+    let (report, summary) = perms::audit(&guild);
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Permission Audit");
+    embed.description(format!("Dangerous permissions checked: {}", perms::names(perms::DANGEROUS_PERMISSIONS)));
+    embed.field("Roles", summary.roles, true);
+    embed.field("Members", summary.members, true);
+    embed.field("@everyone Overwrites", summary.overwrites, true);
+    embed.color(EMBED_COLOR);
+    embed.footer(|f| {
+        f.text(format!("Requested by {}", msg.author.name));
+        f.icon_url(msg.author.face());
+        f
+    });
+
+    let file = AttachmentType::Bytes {
+        data: Cow::Owned(report.into_bytes()),
+        filename: format!("permission-audit-{}.txt", guild.id),
+    };
+    msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed).add_file(file)).await?;
+
+    Ok(())
+}
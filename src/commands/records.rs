@@ -0,0 +1,250 @@
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::framework::standard::{
+    macros::{command, group},
+    Args, CommandResult,
+};
+use serenity::model::channel::Message;
+use serenity::model::prelude::UserId;
+use serenity::model::Timestamp;
+use crate::cases::{self, Case, CaseKind, NameChange, NameKind, Note};
+use crate::settings;
+use crate::utils::truncate;
+
+/// Entries listed on a single dossier page.
+const ENTRIES_PER_PAGE: usize = 8;
+
+#[group]
+#[commands(note, notes, dossier)]
+struct Records;
+
+/// Replies and returns `false` unless the command was used in a configured staff channel.
+async fn in_staff_channel(ctx: &Context, msg: &Message) -> serenity::Result<bool> {
+    let settings = settings::get(ctx, msg.guild_id.unwrap()).await;
+    if settings.staff_channels.contains(&msg.channel_id) {
+        return Ok(true);
+    }
+
+    let reply = if settings.staff_channels.is_empty() {
+        "Moderation records can only be viewed in staff channels. Set one up with `rconfig staff add <channel>`."
+    } else {
+        "Moderation records can only be viewed in staff channels."
+    };
+    msg.reply(&ctx.http, reply).await?;
+    Ok(false)
+}
+
+fn relative_time(timestamp: &Timestamp) -> String {
+    format!("<t:{}:R>", timestamp.unix_timestamp())
+}
+
+fn days_since(timestamp: &Timestamp) -> String {
+    let days = (Timestamp::now().unix_timestamp() - timestamp.unix_timestamp()).max(0) / (24 * 60 * 60);
+    format!("{} days", days)
+}
+
+fn case_line(case: &Case) -> String {
+    let moderator = case.moderator.map_or_else(|| "automatic".to_string(), |id| format!("<@{}>", id));
+    let reason = if case.reason.is_empty() { "No reason given" } else { &case.reason };
+    format!("**#{} {}** {} by {}\n{}", case.id, case.kind, relative_time(&case.created_at), moderator, truncate(reason, 200))
+}
+
+fn note_line(note: &Note) -> String {
+    format!("**#{}** {} by <@{}>\n{}", note.id, relative_time(&note.created_at), note.author, truncate(&note.text, 300))
+}
+
+fn name_line(change: &NameChange) -> String {
+    let kind = match change.kind {
+        NameKind::Username => "Username",
+        NameKind::Nickname => "Nickname",
+    };
+    format!(
+        "{} {}: `{}` → `{}`",
+        relative_time(&change.changed_at),
+        kind,
+        change.old.as_deref().unwrap_or("none"),
+        change.new.as_deref().unwrap_or("none"),
+    )
+}
+
+/// Splits `lines` into embeds of at most [`ENTRIES_PER_PAGE`] entries, newest first.
+fn list_pages(title: &str, lines: Vec<String>, empty: &str) -> Vec<CreateEmbed> {
+    if lines.is_empty() {
+        let mut embed = CreateEmbed::default();
+        embed.title(title).description(empty);
+        return vec![embed];
+    }
+
+    let lines = lines.into_iter().rev().collect::<Vec<_>>();
+    let total = lines.len().div_ceil(ENTRIES_PER_PAGE);
+    lines
+        .chunks(ENTRIES_PER_PAGE)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let mut embed = CreateEmbed::default();
+            if total > 1 {
+                embed.title(format!("{} ({}/{})", title, index + 1, total));
+            } else {
+                embed.title(title);
+            }
+            embed.description(chunk.join("\n\n"));
+            embed
+        })
+        .collect()
+}
+
+#[command]
+#[description("Adds a private moderator note to a member, or removes one with `rnote remove <id>`")]
+#[usage("rnote <user mention or ID> <text>")]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[min_args(2)]
+#[sub_commands(remove)]
+async fn note(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let user_id = match args.single::<UserId>() {
+        Ok(user_id) => user_id,
+        Err(_) => {
+            msg.reply(&ctx.http, "Invalid user provided.").await?;
+            return Ok(());
+        }
+    };
+    let text = args.rest();
+
+    let id = cases::add_note(ctx, guild_id, user_id, msg.author.id, text).await?;
+    info!("Note #{} added for {} by {}", id, user_id, msg.author.id);
+
+    // The note itself may be sensitive, so it isn't echoed back into a possibly public channel,
+    // and the member it's about isn't pinged.
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.reference_message(msg)
+                .content(format!("Added note #{} for <@{}>.", id, user_id))
+                .allowed_mentions(|a| a.empty_parse())
+        })
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Removes a moderator note")]
+#[usage("rnote remove <note id>")]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[num_args(1)]
+async fn remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = match args.single::<u32>() {
+        Ok(id) => id,
+        Err(_) => {
+            msg.reply(&ctx.http, "Invalid note ID provided.").await?;
+            return Ok(());
+        }
+    };
+
+    let reply = if cases::remove_note(ctx, msg.guild_id.unwrap(), id).await? {
+        format!("Removed note #{}.", id)
+    } else {
+        format!("There is no note #{}.", id)
+    };
+    msg.reply(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Lists the moderator notes for a member. Only usable in staff channels")]
+#[usage("rnotes <user mention or ID>")]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[num_args(1)]
+async fn notes(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    if !in_staff_channel(ctx, msg).await? {
+        return Ok(());
+    }
+    let user_id = match args.single::<UserId>() {
+        Ok(user_id) => user_id,
+        Err(_) => {
+            msg.reply(&ctx.http, "Invalid user provided.").await?;
+            return Ok(());
+        }
+    };
+    let user = user_id.to_user(&ctx.http).await?;
+
+    let records = cases::for_user(ctx, msg.guild_id.unwrap(), user_id).await;
+    let lines = records.notes.iter().map(note_line).collect();
+    let pages = list_pages(&format!("Notes for {}", user.tag()), lines, "No notes recorded.");
+
+    super::show_pages(ctx, msg, pages).await
+}
+
+#[command]
+#[description("Shows everything on record for a member: profile, moderation cases, warnings, notes and name changes. Only usable in staff channels")]
+#[usage("rdossier <user mention or ID>")]
+#[only_in(guilds)]
+#[required_permissions(MODERATE_MEMBERS)]
+#[num_args(1)]
+async fn dossier(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    if !in_staff_channel(ctx, msg).await? {
+        return Ok(());
+    }
+    let guild_id = msg.guild_id.unwrap();
+    let user_id = match args.single::<UserId>() {
+        Ok(user_id) => user_id,
+        Err(_) => {
+            msg.reply(&ctx.http, "Invalid user provided.").await?;
+            return Ok(());
+        }
+    };
+    let user = match user_id.to_user(&ctx.http).await {
+        Ok(user) => user,
+        Err(_) => {
+            msg.reply(&ctx.http, "Failed to fetch user information.").await?;
+            return Ok(());
+        }
+    };
+    let member = guild_id.member(&ctx.http, user_id).await.ok();
+    let records = cases::for_user(ctx, guild_id, user_id).await;
+    let (warnings, cases): (Vec<&Case>, Vec<&Case>) = records.cases.iter().partition(|case| case.kind == CaseKind::Warn);
+
+    let mut profile = CreateEmbed::default();
+    profile.title(format!("Dossier for {}", user.tag()));
+    profile.thumbnail(user.face());
+    profile.field("ID", user.id, true);
+    profile.field("Bot", user.bot, true);
+    profile.field("Account Created", format!("{} ({} old)", user.created_at(), days_since(&user.created_at())), false);
+    match &member {
+        Some(member) => {
+            let joined = member.joined_at.map_or_else(
+                || "N/A".to_string(),
+                |joined_at| format!("{} ({} ago)", joined_at, days_since(&joined_at)),
+            );
+            profile.field("Server Joined", joined, false);
+            profile.field("Nickname", member.display_name(), true);
+            let roles = member.roles.iter().map(|role| format!("<@&{}>", role)).collect::<Vec<_>>().join(", ");
+            profile.field("Roles", if roles.is_empty() { "None".to_string() } else { truncate(&roles, 1000) }, false);
+        }
+        None => {
+            profile.field("Server Joined", "Not a member of this server", false);
+        }
+    }
+    profile.field(
+        "On Record",
+        format!(
+            "Cases: {}\nWarnings: {}\nNotes: {}\nName changes: {}",
+            cases.len(),
+            warnings.len(),
+            records.notes.len(),
+            records.names.len()
+        ),
+        false,
+    );
+
+    let mut pages = vec![profile];
+    pages.extend(list_pages("Moderation Cases", cases.into_iter().map(case_line).collect(), "No moderation cases."));
+    pages.extend(list_pages("Warnings", warnings.into_iter().map(case_line).collect(), "No warnings."));
+    pages.extend(list_pages("Notes", records.notes.iter().map(note_line).collect(), "No notes recorded."));
+    pages.extend(list_pages("Recent Name Changes", records.names.iter().map(name_line).collect(), "No name changes seen."));
+
+    super::show_pages(ctx, msg, pages).await
+}
@@ -0,0 +1,291 @@
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::framework::standard::{
+    macros::{command, group},
+    Args, CommandResult,
+};
+use serenity::model::channel::Message;
+use crate::config::EMBED_COLOR;
+use crate::reports::{self, Report, ReportStatus, Severity};
+use crate::utils::truncate;
+
+/// Reports shown by a single `rreports list`.
+const LIST_LIMIT: usize = 15;
+
+#[group]
+#[commands(reports)]
+struct Reports;
+
+/// Filters for `rreports list`.
+#[derive(Default)]
+struct ReportFilter {
+    open: bool,
+    status: Option<ReportStatus>,
+    severity: Option<Severity>,
+    label: Option<String>,
+    query: String,
+}
+
+impl ReportFilter {
+    fn parse(args: &mut Args) -> Result<Self, String> {
+        let mut filter = ReportFilter::default();
+        let mut query = Vec::new();
+        while !args.is_empty() {
+            let arg = args.single::<String>().map_err(|_| "Invalid arguments.".to_string())?;
+            match arg.as_str() {
+                "--open" => filter.open = true,
+                "--status" => {
+                    let value = args.single::<String>().unwrap_or_default();
+                    filter.status = Some(ReportStatus::parse(&value).ok_or("Unknown status.")?);
+                }
+                "--severity" => {
+                    let value = args.single::<String>().unwrap_or_default();
+                    filter.severity = Some(Severity::parse(&value).ok_or("Unknown severity.")?);
+                }
+                "--label" => {
+                    filter.label = Some(args.single::<String>().map_err(|_| "`--label` needs a value.")?.to_lowercase());
+                }
+                flag if flag.starts_with("--") => return Err(format!("Unknown option `{}`.", flag)),
+                word => query.push(word.to_string()),
+            }
+        }
+        filter.query = query.join(" ");
+        Ok(filter)
+    }
+
+    fn matches(&self, report: &Report) -> bool {
+        (!self.open || report.status.is_open())
+            && self.status.is_none_or(|status| report.status == status)
+            && self.severity.is_none_or(|severity| report.severity == severity)
+            && self.label.as_ref().is_none_or(|label| report.labels.contains(label))
+            && (self.query.is_empty() || report.matches(&self.query))
+    }
+}
+
+fn parse_id(args: &mut Args) -> Option<u32> {
+    args.single::<String>().ok()?.trim_start_matches('#').parse().ok()
+}
+
+#[command]
+#[description("Lists and manages bug reports")]
+#[usage("rreports <list/show/status/severity/label/duplicate>")]
+#[only_in(guilds)]
+#[sub_commands(list, show, status, severity, label, duplicate)]
+async fn reports(ctx: &Context, msg: &Message) -> CommandResult {
+    msg.reply(
+        &ctx.http,
+        "Usage: `rreports list [--open] [--status <status>] [--severity <level>] [--label <label>] [search]`, `rreports show <id>`, \
+         `rreports status <id> <open/confirmed/in-progress/resolved/wontfix> [note]`, `rreports severity <id> <level>`, \
+         `rreports label <id> <add/remove> <label>`, `rreports duplicate <id> <original id>`",
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Lists bug reports, optionally filtered and searched")]
+#[usage("rreports list [--open] [--status <status>] [--severity <level>] [--label <label>] [search terms]")]
+#[only_in(guilds)]
+async fn list(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let filter = match ReportFilter::parse(&mut args) {
+        Ok(filter) => filter,
+        Err(err) => {
+            msg.reply(&ctx.http, err).await?;
+            return Ok(());
+        }
+    };
+
+    let matching = reports::list(ctx, msg.guild_id.unwrap())
+        .await
+        .into_iter()
+        .rev()
+        .filter(|report| filter.matches(report))
+        .collect::<Vec<_>>();
+
+    let mut description = matching
+        .iter()
+        .take(LIST_LIMIT)
+        .map(|report| {
+            format!(
+                "**#{}** [{}] [{}] {}",
+                report.id,
+                report.status_text(),
+                report.severity,
+                truncate(report.description.lines().next().unwrap_or_default(), 80)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if matching.is_empty() {
+        description = "No matching reports.".to_string();
+    } else if matching.len() > LIST_LIMIT {
+        description.push_str(&format!("\n…and {} more. Narrow the search to see them.", matching.len() - LIST_LIMIT));
+    }
+
+    let mut embed = CreateEmbed::default();
+    embed.title(format!("Reports ({})", matching.len()));
+    embed.description(description);
+    embed.color(EMBED_COLOR);
+    embed.footer(|f| {
+        f.text(format!("Requested by {}", msg.author.name));
+        f.icon_url(msg.author.face());
+        f
+    });
+    msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Shows a single bug report")]
+#[usage("rreports show <id>")]
+#[only_in(guilds)]
+#[num_args(1)]
+async fn show(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(id) = parse_id(&mut args) else {
+        msg.reply(&ctx.http, "Invalid report ID provided.").await?;
+        return Ok(());
+    };
+
+    match reports::get(ctx, msg.guild_id.unwrap(), id).await {
+        Some(report) => {
+            let link = format!(
+                "https://discord.com/channels/{}/{}/{}",
+                msg.guild_id.unwrap(),
+                report.channel_id,
+                report.message_id
+            );
+            let mut embed = report.embed();
+            embed.url(link);
+            msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+        }
+        None => {
+            msg.reply(&ctx.http, format!("There is no report #{}.", id)).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn reply_updated(ctx: &Context, msg: &Message, id: u32, report: Option<Report>) -> CommandResult {
+    let reply = match report {
+        Some(report) => format!("Report #{} updated: {} / {}.", id, report.status_text(), report.severity),
+        None => format!("There is no report #{}.", id),
+    };
+    msg.reply(&ctx.http, reply).await?;
+    Ok(())
+}
+
+#[command]
+#[description("Changes a report's status and notifies the reporter")]
+#[usage("rreports status <id> <open/confirmed/in-progress/resolved/wontfix> [note]")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[min_args(2)]
+async fn status(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(id) = parse_id(&mut args) else {
+        msg.reply(&ctx.http, "Invalid report ID provided.").await?;
+        return Ok(());
+    };
+    let status = match args.single::<String>().ok().and_then(|value| ReportStatus::parse(&value)) {
+        Some(ReportStatus::Duplicate) => {
+            msg.reply(&ctx.http, "Use `rreports duplicate <id> <original id>` to mark duplicates.").await?;
+            return Ok(());
+        }
+        Some(status) => status,
+        None => {
+            msg.reply(&ctx.http, "Status must be open, confirmed, in-progress, resolved or wontfix.").await?;
+            return Ok(());
+        }
+    };
+    let note = Some(args.rest()).filter(|note| !note.is_empty()).map(str::to_string);
+
+    let change = format!("status is now **{}**", status);
+    let report = reports::update(ctx, msg.guild_id.unwrap(), id, &change, note.as_deref(), |report| {
+        report.status = status;
+        report.duplicate_of = None;
+        report.note = note.clone();
+    })
+    .await?;
+    reply_updated(ctx, msg, id, report).await
+}
+
+#[command]
+#[description("Changes a report's severity and notifies the reporter")]
+#[usage("rreports severity <id> <low/medium/high/critical>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[num_args(2)]
+async fn severity(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(id) = parse_id(&mut args) else {
+        msg.reply(&ctx.http, "Invalid report ID provided.").await?;
+        return Ok(());
+    };
+    let Some(severity) = args.single::<String>().ok().and_then(|value| Severity::parse(&value)) else {
+        msg.reply(&ctx.http, "Severity must be low, medium, high or critical.").await?;
+        return Ok(());
+    };
+
+    let change = format!("severity is now **{}**", severity);
+    let report = reports::update(ctx, msg.guild_id.unwrap(), id, &change, None, |report| report.severity = severity).await?;
+    reply_updated(ctx, msg, id, report).await
+}
+
+#[command]
+#[description("Adds or removes a label on a report")]
+#[usage("rreports label <id> <add/remove> <label>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[num_args(3)]
+async fn label(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(id) = parse_id(&mut args) else {
+        msg.reply(&ctx.http, "Invalid report ID provided.").await?;
+        return Ok(());
+    };
+    let operation = args.single::<String>()?;
+    let label = args.single::<String>()?.to_lowercase();
+
+    let change = match operation.as_str() {
+        "add" => format!("labelled **{}**", label),
+        "remove" => format!("label **{}** removed", label),
+        _ => {
+            msg.reply(&ctx.http, "Invalid operation. Use `add` or `remove`").await?;
+            return Ok(());
+        }
+    };
+    let report = reports::update(ctx, msg.guild_id.unwrap(), id, &change, None, |report| {
+        report.labels.retain(|existing| *existing != label);
+        if operation == "add" {
+            report.labels.push(label);
+        }
+    })
+    .await?;
+    reply_updated(ctx, msg, id, report).await
+}
+
+#[command]
+#[description("Marks a report as a duplicate of an earlier one")]
+#[usage("rreports duplicate <id> <original id>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[num_args(2)]
+async fn duplicate(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let (Some(id), Some(original)) = (parse_id(&mut args), parse_id(&mut args)) else {
+        msg.reply(&ctx.http, "Invalid report ID provided.").await?;
+        return Ok(());
+    };
+    if id == original || reports::get(ctx, guild_id, original).await.is_none() {
+        msg.reply(&ctx.http, format!("Report #{} can't be the original.", original)).await?;
+        return Ok(());
+    }
+
+    let change = format!("marked as a duplicate of report #{}", original);
+    let report = reports::update(ctx, guild_id, id, &change, None, |report| {
+        report.status = ReportStatus::Duplicate;
+        report.duplicate_of = Some(original);
+    })
+    .await?;
+    reply_updated(ctx, msg, id, report).await
+}
@@ -0,0 +1,307 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serenity::client::Context;
+use serenity::framework::standard::{
+    macros::{command, group},
+    Args, CommandResult,
+};
+use serenity::futures::StreamExt;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::channel::Message;
+use serenity::model::guild::{Guild, Member};
+use serenity::model::prelude::RoleId;
+use crate::confirm::confirm;
+use crate::resolve;
+
+#[group]
+#[commands(role)]
+struct Roles;
+
+/// How often bulk operations update their status message.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+/// How long a bulk operation's Cancel button stays active.
+const BULK_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Highest position among a set of roles, or 0 for members with only @everyone.
+fn highest_position(guild: &Guild, roles: &[RoleId]) -> i64 {
+    roles
+        .iter()
+        .filter_map(|role_id| guild.roles.get(role_id))
+        .map(|role| role.position)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Checks that both the invoker and the bot rank above `role_id`, so the command can't be
+/// used to hand out roles the invoker couldn't assign themselves.
+async fn check_hierarchy(ctx: &Context, msg: &Message, guild: &Guild, role_id: RoleId) -> Result<(), String> {
+    let Some(role) = guild.roles.get(&role_id) else {
+        return Err("That role doesn't exist.".to_string());
+    };
+    if role.managed || role_id.0 == guild.id.0 {
+        return Err(format!("`{}` is managed by Discord or an integration and can't be assigned.", role.name));
+    }
+
+    if msg.author.id != guild.owner_id {
+        let invoker = guild.id.member(ctx, msg.author.id).await.map_err(|err| err.to_string())?;
+        if highest_position(guild, &invoker.roles) <= role.position {
+            return Err(format!("`{}` is not below your highest role.", role.name));
+        }
+    }
+
+    let bot = guild.id.member(ctx, ctx.cache.current_user_id()).await.map_err(|err| err.to_string())?;
+    if highest_position(guild, &bot.roles) <= role.position {
+        return Err(format!("`{}` is not below my highest role.", role.name));
+    }
+
+    Ok(())
+}
+
+/// Resolves a role argument and checks the hierarchy, replying with the problem if either fails.
+async fn target_role(ctx: &Context, msg: &Message, input: &str) -> Result<Option<(Guild, RoleId)>, serenity::Error> {
+    let Some(guild) = msg.guild_id.and_then(|guild_id| guild_id.to_guild_cached(&ctx.cache)) else {
+        msg.reply(&ctx.http, "Failed to load this server.").await?;
+        return Ok(None);
+    };
+
+    let checked = match resolve::role(&guild, input) {
+        Ok(role_id) => check_hierarchy(ctx, msg, &guild, role_id).await.map(|()| role_id),
+        Err(err) => Err(err),
+    };
+    match checked {
+        Ok(role_id) => Ok(Some((guild, role_id))),
+        Err(err) => {
+            msg.reply(&ctx.http, err).await?;
+            Ok(None)
+        }
+    }
+}
+
+#[command]
+#[description("Adds or removes roles from one member or many. Roles can be given as a mention, an ID or a name.")]
+#[usage("rrole <add/remove/all/humans/bots/in>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[sub_commands(add, remove, all, humans, bots, in_role)]
+async fn role(ctx: &Context, msg: &Message) -> CommandResult {
+    msg.reply(&ctx.http, "Usage: `rrole <add/remove/all/humans/bots/in>`. See `rhelp role` for details.").await?;
+    Ok(())
+}
+
+#[command]
+#[description("Gives a role to a member")]
+#[usage("rrole add <user mention, ID or name> <role>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[min_args(2)]
+async fn add(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    change_member_role(ctx, msg, args, true).await
+}
+
+#[command]
+#[description("Takes a role away from a member")]
+#[usage("rrole remove <user mention, ID or name> <role>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[min_args(2)]
+async fn remove(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    change_member_role(ctx, msg, args, false).await
+}
+
+async fn change_member_role(ctx: &Context, msg: &Message, mut args: Args, grant: bool) -> CommandResult {
+    let Some(user_id) = resolve::member(ctx, msg, &args.single_quoted::<String>()?).await? else {
+        return Ok(());
+    };
+    let Some((guild, role_id)) = target_role(ctx, msg, args.rest()).await? else {
+        return Ok(());
+    };
+
+    let reason = format!("Requested by {}", msg.author.tag());
+    let result = if grant {
+        ctx.http.add_member_role(guild.id.0, user_id.0, role_id.0, Some(&reason)).await
+    } else {
+        ctx.http.remove_member_role(guild.id.0, user_id.0, role_id.0, Some(&reason)).await
+    };
+
+    let reply = match result {
+        Ok(()) if grant => format!("Gave <@&{}> to <@{}>.", role_id, user_id),
+        Ok(()) => format!("Removed <@&{}> from <@{}>.", role_id, user_id),
+        Err(err) => format!("Failed to update roles: {}", err),
+    };
+    msg.channel_id
+        .send_message(&ctx.http, |m| m.content(reply).allowed_mentions(|a| a.empty_parse()))
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Gives a role to every member")]
+#[usage("rrole all <role>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[min_args(1)]
+async fn all(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some((guild, role_id)) = target_role(ctx, msg, args.rest()).await? else {
+        return Ok(());
+    };
+    bulk_grant(ctx, msg, &guild, role_id, "members", |_| true).await
+}
+
+#[command]
+#[description("Gives a role to every member who isn't a bot")]
+#[usage("rrole humans <role>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[min_args(1)]
+async fn humans(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some((guild, role_id)) = target_role(ctx, msg, args.rest()).await? else {
+        return Ok(());
+    };
+    bulk_grant(ctx, msg, &guild, role_id, "humans", |member| !member.user.bot).await
+}
+
+#[command]
+#[description("Gives a role to every bot")]
+#[usage("rrole bots <role>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[min_args(1)]
+async fn bots(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some((guild, role_id)) = target_role(ctx, msg, args.rest()).await? else {
+        return Ok(());
+    };
+    bulk_grant(ctx, msg, &guild, role_id, "bots", |member| member.user.bot).await
+}
+
+#[command("in")]
+#[description("Gives a role to every member who has another role. Quote role names containing spaces.")]
+#[usage("rrole in <role members must have> <role to give>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_ROLES)]
+#[min_args(2)]
+async fn in_role(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    args.quoted();
+    let source = args.single_quoted::<String>()?;
+    let Some(guild) = msg.guild_id.and_then(|guild_id| guild_id.to_guild_cached(&ctx.cache)) else {
+        msg.reply(&ctx.http, "Failed to load this server.").await?;
+        return Ok(());
+    };
+    let source_id = match resolve::role(&guild, &source) {
+        Ok(role_id) => role_id,
+        Err(err) => {
+            msg.reply(&ctx.http, err).await?;
+            return Ok(());
+        }
+    };
+
+    let target = args.rest().trim_matches('"');
+    let Some((guild, role_id)) = target_role(ctx, msg, target).await? else {
+        return Ok(());
+    };
+    let label = format!("members with <@&{}>", source_id);
+    bulk_grant(ctx, msg, &guild, role_id, &label, |member| member.roles.contains(&source_id)).await
+}
+
+/// Gives `role_id` to every member matching `filter`, editing a status message with progress
+/// until done or cancelled.
+async fn bulk_grant(
+    ctx: &Context,
+    msg: &Message,
+    guild: &Guild,
+    role_id: RoleId,
+    label: &str,
+    filter: impl Fn(&Member) -> bool,
+) -> CommandResult {
+    let mut targets = Vec::new();
+    let mut members = guild.id.members_iter(&ctx.http).boxed();
+    while let Some(member) = members.next().await {
+        let member = member?;
+        if !member.roles.contains(&role_id) && filter(&member) {
+            targets.push(member.user.id);
+        }
+    }
+
+    if targets.is_empty() {
+        msg.reply(&ctx.http, "Every matching member already has that role.").await?;
+        return Ok(());
+    }
+
+    let summary = format!("Give <@&{}> to {} {}?", role_id, targets.len(), label);
+    if !confirm(ctx, msg, "Confirm Bulk Role Change", &summary).await? {
+        return Ok(());
+    }
+
+    let mut status = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.content(format!("Giving <@&{}> to {} {}... 0/{}", role_id, targets.len(), label, targets.len()))
+                .allowed_mentions(|a| a.empty_parse())
+                .components(|c| {
+                    c.create_action_row(|row| {
+                        row.create_button(|b| b.custom_id("cancel").label("Cancel").style(ButtonStyle::Danger))
+                    })
+                })
+        })
+        .await?;
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let watcher = {
+        let ctx = ctx.clone();
+        let status = status.clone();
+        let cancelled = cancelled.clone();
+        let author_id = msg.author.id;
+        tokio::spawn(async move {
+            let interaction = status
+                .await_component_interaction(&ctx)
+                .author_id(author_id)
+                .timeout(BULK_TIMEOUT)
+                .await;
+            if let Some(interaction) = interaction {
+                cancelled.store(true, Ordering::Relaxed);
+                let _ = interaction
+                    .create_interaction_response(&ctx.http, |r| r.kind(InteractionResponseType::DeferredUpdateMessage))
+                    .await;
+            }
+        })
+    };
+
+    // Requests go out one at a time, so serenity's ratelimiter paces the run
+    let reason = format!("Bulk role change requested by {}", msg.author.tag());
+    let mut succeeded = 0;
+    let mut failed = 0;
+    let mut last_update = Instant::now();
+    for (done, user_id) in targets.iter().enumerate() {
+        if cancelled.load(Ordering::Relaxed) {
+            break;
+        }
+
+        match ctx.http.add_member_role(guild.id.0, user_id.0, role_id.0, Some(&reason)).await {
+            Ok(()) => succeeded += 1,
+            Err(err) => {
+                debug!("Failed to give role {} to {}: {}", role_id, user_id, err);
+                failed += 1;
+            }
+        }
+
+        if last_update.elapsed() >= PROGRESS_INTERVAL {
+            last_update = Instant::now();
+            let progress = format!("Giving <@&{}> to {}... {}/{}", role_id, label, done + 1, targets.len());
+            let _ = status.edit(&ctx.http, |m| m.content(progress)).await;
+        }
+    }
+    watcher.abort();
+
+    let outcome = if cancelled.load(Ordering::Relaxed) { "Cancelled" } else { "Finished" };
+    let mut report = format!("{}: gave <@&{}> to {} of {} {}.", outcome, role_id, succeeded, targets.len(), label);
+    if failed > 0 {
+        report.push_str(&format!(" {} failed.", failed));
+    }
+    status.edit(&ctx.http, |m| m.content(report).components(|c| c)).await?;
+    info!("Bulk role change finished: {} succeeded, {} failed", succeeded, failed);
+
+    Ok(())
+}
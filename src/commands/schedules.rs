@@ -0,0 +1,287 @@
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::framework::standard::{
+    macros::{command, group},
+    Args, CommandResult,
+};
+use serenity::model::channel::Message;
+use serenity::model::prelude::ChannelId;
+use serenity::model::Timestamp;
+use crate::announcements::EmbedTemplates;
+use crate::config::EMBED_COLOR;
+use crate::schedules::{Content, MissedRuns, Schedule, Schedules, When};
+use crate::store;
+use crate::utils::truncate;
+
+#[group]
+#[commands(schedule)]
+struct Scheduling;
+
+#[command]
+#[description("Posts messages or embed templates at a set time or on a recurring schedule")]
+#[usage("rschedule <create/list/pause/resume/delete>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[sub_commands(create, list, pause, resume, delete)]
+async fn schedule(ctx: &Context, msg: &Message) -> CommandResult {
+    msg.reply(
+        &ctx.http,
+        "Usage: `rschedule create [--skip-missed] [--ping] <channel> <when> <message or template:name>`, \
+         `rschedule list`, `rschedule pause <id>`, `rschedule resume <id>`, `rschedule delete <id>`. \
+         `when` is a duration like `2h30m`, a date like `\"2026-11-01 18:00 Europe/London\"` \
+         or a cron expression like `\"cron 0 18 * * FRI Europe/London\"`.",
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command]
+#[description(
+    "Schedules a message or embed template. Runs missed while the bot was offline are posted once when it's back, \
+     unless `--skip-missed` is given"
+)]
+#[usage("rschedule create [--skip-missed] [--ping] <channel mention or ID> <when> <message or template:name>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[min_args(3)]
+async fn create(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    args.trimmed().quoted();
+
+    let mut missed = MissedRuns::CatchUp;
+    let mut ping = false;
+    while let Some(flag) = args.current().filter(|arg| arg.starts_with("--")) {
+        match flag {
+            "--skip-missed" => missed = MissedRuns::Skip,
+            "--catch-up" => missed = MissedRuns::CatchUp,
+            "--ping" => ping = true,
+            _ => {
+                msg.reply(&ctx.http, format!("Unknown option `{}`.", flag)).await?;
+                return Ok(());
+            }
+        }
+        args.advance();
+    }
+
+    if ping {
+        let allowed = match msg.member(ctx).await {
+            Ok(member) => member.permissions(&ctx.cache).is_ok_and(|permissions| permissions.mention_everyone()),
+            Err(_) => false,
+        };
+        if !allowed {
+            msg.reply(&ctx.http, "You need the Mention Everyone permission to use `--ping`.").await?;
+            return Ok(());
+        }
+    }
+
+    let channel_id = match args.single::<ChannelId>() {
+        Ok(channel_id) if ctx.cache.guild_channel(channel_id).is_some_and(|channel| channel.guild_id == guild_id) => channel_id,
+        _ => {
+            msg.reply(&ctx.http, "Invalid channel provided.").await?;
+            return Ok(());
+        }
+    };
+
+    let now = Timestamp::now().unix_timestamp();
+    let when = match args.single_quoted::<String>().map(|input| When::parse(&input, now)) {
+        Ok(Ok(when)) => when,
+        Ok(Err(err)) => {
+            msg.reply(&ctx.http, err).await?;
+            return Ok(());
+        }
+        Err(_) => {
+            msg.reply(&ctx.http, "Say when the message should be posted.").await?;
+            return Ok(());
+        }
+    };
+    let Some(next_run) = when.next_after(now) else {
+        msg.reply(&ctx.http, "That schedule would never run.").await?;
+        return Ok(());
+    };
+
+    let content = match args.rest().trim() {
+        "" => {
+            msg.reply(&ctx.http, "Give the message or `template:<name>` to post.").await?;
+            return Ok(());
+        }
+        rest => match rest.strip_prefix("template:") {
+            Some(name) => {
+                let name = name.trim().to_lowercase();
+                let templates = store::get::<EmbedTemplates>(ctx).await;
+                let exists = templates.read().await.get(&guild_id).is_some_and(|all| all.contains_key(&name));
+                if !exists {
+                    msg.reply(&ctx.http, format!("There is no template called `{}`.", name)).await?;
+                    return Ok(());
+                }
+                Content::Template(name)
+            }
+            None if rest.chars().count() > 2000 => {
+                msg.reply(&ctx.http, "Scheduled messages can be at most 2000 characters.").await?;
+                return Ok(());
+            }
+            None => Content::Text(rest.to_string()),
+        },
+    };
+
+    let schedules = store::get::<Schedules>(ctx).await;
+    let id = schedules
+        .write(|all| {
+            let guild = all.entry(guild_id).or_default();
+            guild.next_id += 1;
+            let id = guild.next_id;
+            guild.schedules.push(Schedule {
+                id,
+                channel_id,
+                author: msg.author.id,
+                when: when.clone(),
+                content,
+                missed,
+                ping,
+                paused: false,
+                next_run,
+            });
+            id
+        })
+        .await?;
+
+    msg.reply(
+        &ctx.http,
+        format!("Schedule #{} created: {} in <#{}>, next run <t:{}:R>.", id, when.describe(), channel_id, next_run),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Lists this server's schedules")]
+#[usage("rschedule list")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+async fn list(ctx: &Context, msg: &Message) -> CommandResult {
+    let schedules = store::get::<Schedules>(ctx).await;
+    let lines = schedules
+        .read()
+        .await
+        .get(&msg.guild_id.unwrap())
+        .map(|guild| {
+            guild
+                .schedules
+                .iter()
+                .map(|schedule| {
+                    let status = if schedule.paused { "paused".to_string() } else { format!("next <t:{}:R>", schedule.next_run) };
+                    let missed = match schedule.missed {
+                        MissedRuns::CatchUp => "catches up",
+                        MissedRuns::Skip => "skips missed runs",
+                    };
+                    let content = match &schedule.content {
+                        Content::Text(text) => truncate(text, 80),
+                        Content::Template(name) => format!("template `{}`", name),
+                    };
+                    format!(
+                        "**#{}** {} in <#{}>, {}, {}\n{}",
+                        schedule.id,
+                        schedule.when.describe(),
+                        schedule.channel_id,
+                        status,
+                        missed,
+                        content
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Schedules");
+    embed.description(if lines.is_empty() { "No schedules yet.".to_string() } else { truncate(&lines.join("\n\n"), 4000) });
+    embed.color(EMBED_COLOR);
+    embed.footer(|f| {
+        f.text(format!("Requested by {}", msg.author.name));
+        f.icon_url(msg.author.face());
+        f
+    });
+
+    msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Applies `f` to the schedule with the given ID, returning whether it exists.
+async fn update(ctx: &Context, msg: &Message, id: u32, f: impl FnOnce(&mut Schedule)) -> anyhow::Result<bool> {
+    let schedules = store::get::<Schedules>(ctx).await;
+    schedules
+        .write(|all| {
+            let schedule = all
+                .get_mut(&msg.guild_id.unwrap())
+                .and_then(|guild| guild.schedules.iter_mut().find(|schedule| schedule.id == id));
+            schedule.map(f).is_some()
+        })
+        .await
+}
+
+#[command]
+#[description("Pauses a schedule")]
+#[usage("rschedule pause <id>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[num_args(1)]
+async fn pause(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = args.single::<String>()?.trim_start_matches('#').parse::<u32>().unwrap_or_default();
+    let reply = if update(ctx, msg, id, |schedule| schedule.paused = true).await? {
+        format!("Schedule #{} paused.", id)
+    } else {
+        format!("There is no schedule #{}.", id)
+    };
+    msg.reply(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Resumes a paused schedule. Recurring schedules pick up from their next run rather than catching up")]
+#[usage("rschedule resume <id>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[num_args(1)]
+async fn resume(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = args.single::<String>()?.trim_start_matches('#').parse::<u32>().unwrap_or_default();
+    let now = Timestamp::now().unix_timestamp();
+    let resumed = update(ctx, msg, id, |schedule| {
+        schedule.paused = false;
+        if let When::Cron { .. } = schedule.when {
+            schedule.next_run = schedule.when.next_after(now).unwrap_or(schedule.next_run);
+        }
+    })
+    .await?;
+    let reply = if resumed { format!("Schedule #{} resumed.", id) } else { format!("There is no schedule #{}.", id) };
+    msg.reply(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Deletes a schedule")]
+#[usage("rschedule delete <id>")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[num_args(1)]
+async fn delete(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = args.single::<String>()?.trim_start_matches('#').parse::<u32>().unwrap_or_default();
+    let schedules = store::get::<Schedules>(ctx).await;
+    let removed = schedules
+        .write(|all| {
+            let guild = all.get_mut(&msg.guild_id.unwrap())?;
+            let index = guild.schedules.iter().position(|schedule| schedule.id == id)?;
+            Some(guild.schedules.remove(index))
+        })
+        .await?;
+    let reply = match removed {
+        Some(_) => format!("Schedule #{} deleted.", id),
+        None => format!("There is no schedule #{}.", id),
+    };
+    msg.reply(&ctx.http, reply).await?;
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use serenity::client::Context;
+use serenity::framework::standard::{
+    macros::{command, group},
+    Args, CommandResult,
+};
+use serenity::model::channel::Message;
+use crate::suggestions::{self, Status};
+
+#[group]
+#[commands(approve, deny, consider, implement)]
+struct Suggestions;
+
+async fn set_status(ctx: &Context, msg: &Message, mut args: Args, status: Status) -> CommandResult {
+    let id = match args.single::<u32>() {
+        Ok(id) => id,
+        Err(_) => {
+            msg.reply(&ctx.http, "Invalid suggestion ID provided.").await?;
+            return Ok(());
+        }
+    };
+    let reason = Some(args.rest()).filter(|reason| !reason.is_empty());
+
+    let reply = match suggestions::review(ctx, msg.guild_id.unwrap(), id, status, msg.author.id, reason).await? {
+        Some(_) => format!("Suggestion #{} is now **{}**.", id, status),
+        None => format!("There is no suggestion #{}.", id),
+    };
+    msg.reply(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Marks a suggestion as approved and lets its author know")]
+#[usage("rapprove <id> [reason]")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[min_args(1)]
+async fn approve(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    set_status(ctx, msg, args, Status::Approved).await
+}
+
+#[command]
+#[description("Marks a suggestion as denied and lets its author know")]
+#[usage("rdeny <id> [reason]")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[min_args(1)]
+async fn deny(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    set_status(ctx, msg, args, Status::Denied).await
+}
+
+#[command]
+#[description("Marks a suggestion as under consideration and lets its author know")]
+#[usage("rconsider <id> [reason]")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[min_args(1)]
+async fn consider(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    set_status(ctx, msg, args, Status::Considered).await
+}
+
+#[command]
+#[description("Marks a suggestion as implemented and lets its author know")]
+#[usage("rimplement <id> [reason]")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[min_args(1)]
+async fn implement(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    set_status(ctx, msg, args, Status::Implemented).await
+}
@@ -0,0 +1,215 @@
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::framework::standard::{
+    macros::{command, group},
+    Args, CommandResult,
+};
+use serenity::model::channel::Message;
+use serenity::model::prelude::{ChannelId, UserId};
+use crate::config::EMBED_COLOR;
+use crate::settings;
+use crate::tickets::{self, Ticket};
+use crate::utils::truncate;
+
+#[group]
+#[commands(ticket)]
+struct Tickets;
+
+/// Looks up the ticket for the current channel, replying if there isn't one.
+async fn current_ticket(ctx: &Context, msg: &Message) -> serenity::Result<Option<Ticket>> {
+    let ticket = tickets::ticket_for_channel(ctx, msg.guild_id.unwrap(), msg.channel_id).await;
+    if ticket.is_none() {
+        msg.reply(&ctx.http, "This isn't an open ticket.").await?;
+    }
+    Ok(ticket)
+}
+
+/// Whether the author is support staff: a support role or Manage Channels.
+async fn is_staff(ctx: &Context, msg: &Message) -> bool {
+    let config = settings::get(ctx, msg.guild_id.unwrap()).await.tickets;
+    let Ok(member) = msg.member(&ctx).await else { return false };
+    let permissions = member.permissions(&ctx.cache).unwrap_or_default();
+    config.is_staff(&member.roles, permissions)
+}
+
+/// Finds the ticket for the current channel if the author is support staff, replying otherwise.
+async fn staff_ticket(ctx: &Context, msg: &Message) -> serenity::Result<Option<Ticket>> {
+    if !is_staff(ctx, msg).await {
+        msg.reply(&ctx.http, "Only support staff can do that.").await?;
+        return Ok(None);
+    }
+    current_ticket(ctx, msg).await
+}
+
+#[command]
+#[description("Manages support tickets")]
+#[usage("rticket <panel/claim/unclaim/add/remove/close>")]
+#[only_in(guilds)]
+#[sub_commands(panel, claim, unclaim, add, remove, close)]
+async fn ticket(ctx: &Context, msg: &Message) -> CommandResult {
+    msg.reply(
+        &ctx.http,
+        "Usage: `rticket panel [channel] [message]`, `rticket claim`, `rticket unclaim`, \
+         `rticket add <user>`, `rticket remove <user>`, `rticket close [reason]`",
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command]
+#[description("Posts the ticket panel with an open button for each category")]
+#[usage("rticket panel [channel mention or ID] [message]")]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+async fn panel(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = settings::get(ctx, msg.guild_id.unwrap()).await.tickets;
+    if config.categories.is_empty() {
+        msg.reply(&ctx.http, "Add a ticket category first with `rconfig tickets category add <name> [description]`.").await?;
+        return Ok(());
+    }
+    if config.categories.len() > tickets::MAX_CATEGORIES {
+        let reply = format!(
+            "A panel can offer at most {} categories. Remove some with `rconfig tickets category remove <name>`.",
+            tickets::MAX_CATEGORIES
+        );
+        msg.reply(&ctx.http, reply).await?;
+        return Ok(());
+    }
+
+    let channel_id = if args.current().is_some_and(|arg| arg.parse::<ChannelId>().is_ok()) {
+        match super::config::guild_channel(ctx, msg.guild_id.unwrap(), &mut args) {
+            Some(channel) => channel.id,
+            None => {
+                msg.reply(&ctx.http, "Invalid channel provided.").await?;
+                return Ok(());
+            }
+        }
+    } else {
+        msg.channel_id
+    };
+    let text = match args.rest() {
+        "" => "Need help? Press a button below to open a private ticket with our support team.",
+        text => text,
+    };
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Support Tickets");
+    embed.description(truncate(text, 1000));
+    for category in &config.categories {
+        let description = category.description.as_deref().map_or("\u{200b}".to_string(), |d| truncate(d, tickets::MAX_DESCRIPTION));
+        embed.field(&category.name, description, false);
+    }
+    embed.color(EMBED_COLOR);
+
+    channel_id
+        .send_message(&ctx.http, |m| m.set_embed(embed).set_components(tickets::panel_buttons(&config)))
+        .await?;
+    if channel_id != msg.channel_id {
+        msg.reply(&ctx.http, format!("Ticket panel posted in <#{}>.", channel_id)).await?;
+    }
+
+    Ok(())
+}
+
+#[command]
+#[description("Claims the current ticket so other staff know you're handling it")]
+#[usage("rticket claim")]
+#[only_in(guilds)]
+async fn claim(ctx: &Context, msg: &Message) -> CommandResult {
+    let Some(ticket) = staff_ticket(ctx, msg).await? else {
+        return Ok(());
+    };
+    if let Some(claimer) = ticket.claimed_by.filter(|claimer| *claimer != msg.author.id) {
+        msg.reply(&ctx.http, format!("This ticket is already claimed by <@{}>.", claimer)).await?;
+        return Ok(());
+    }
+
+    tickets::set_claim(ctx, msg.guild_id.unwrap(), &ticket, Some(msg.author.id)).await?;
+    Ok(())
+}
+
+#[command]
+#[description("Releases your claim on the current ticket")]
+#[usage("rticket unclaim")]
+#[only_in(guilds)]
+async fn unclaim(ctx: &Context, msg: &Message) -> CommandResult {
+    let Some(ticket) = staff_ticket(ctx, msg).await? else {
+        return Ok(());
+    };
+    if ticket.claimed_by.is_none() {
+        msg.reply(&ctx.http, "This ticket isn't claimed.").await?;
+        return Ok(());
+    }
+
+    tickets::set_claim(ctx, msg.guild_id.unwrap(), &ticket, None).await?;
+    Ok(())
+}
+
+#[command]
+#[description("Adds a member to the current ticket")]
+#[usage("rticket add <user mention or ID>")]
+#[only_in(guilds)]
+#[num_args(1)]
+async fn add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(ticket) = staff_ticket(ctx, msg).await? else {
+        return Ok(());
+    };
+    let Ok(user_id) = args.single::<UserId>() else {
+        msg.reply(&ctx.http, "Invalid user provided.").await?;
+        return Ok(());
+    };
+
+    if let Err(err) = tickets::add_participant(ctx, msg.guild_id.unwrap(), &ticket, user_id).await {
+        error!("Failed to add {} to ticket #{}: {:?}", user_id, ticket.id, err);
+        msg.reply(&ctx.http, "Failed to add that user to the ticket.").await?;
+    }
+
+    Ok(())
+}
+
+#[command]
+#[description("Removes a member from the current ticket")]
+#[usage("rticket remove <user mention or ID>")]
+#[only_in(guilds)]
+#[num_args(1)]
+async fn remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Some(ticket) = staff_ticket(ctx, msg).await? else {
+        return Ok(());
+    };
+    let Ok(user_id) = args.single::<UserId>() else {
+        msg.reply(&ctx.http, "Invalid user provided.").await?;
+        return Ok(());
+    };
+
+    if let Err(err) = tickets::remove_participant(ctx, msg.guild_id.unwrap(), &ticket, user_id).await {
+        msg.reply(&ctx.http, format!("Failed to remove that user: {}.", err)).await?;
+    }
+
+    Ok(())
+}
+
+#[command]
+#[description("Closes the current ticket, uploading its transcript and DMing it to the opener")]
+#[usage("rticket close [reason]")]
+#[only_in(guilds)]
+async fn close(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some(ticket) = current_ticket(ctx, msg).await? else {
+        return Ok(());
+    };
+    if ticket.opener != msg.author.id && !is_staff(ctx, msg).await {
+        msg.reply(&ctx.http, "Only support staff or the ticket opener can close this ticket.").await?;
+        return Ok(());
+    }
+    let reason = match args.rest() {
+        "" => "No reason given",
+        reason => reason,
+    };
+
+    if let Err(err) = tickets::close(ctx, msg.guild_id.unwrap(), &ticket, &msg.author, reason).await {
+        error!("Failed to close ticket #{}: {:?}", ticket.id, err);
+        msg.reply(&ctx.http, "Failed to close this ticket.").await?;
+    }
+
+    Ok(())
+}
@@ -1,5 +1,40 @@
+use std::time::Duration;
+
 use serenity::utils::Color;
 
 // Change this to anything 
-pub const EMBED_COLOR: Color = Color::from_rgb(255, 165, 0); 
+pub const EMBED_COLOR: Color = Color::from_rgb(255, 165, 0);
+
+// File in the data directory listing known phishing domains, one per line
+pub const PHISHING_BLOCKLIST_FILE: &str = "phishing_domains.txt";
+// How often the phishing blocklist is checked for changes
+pub const BLOCKLIST_RELOAD_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+// Link shorteners that are followed to find where a link really goes
+pub const LINK_SHORTENERS: &[&str] = &[
+    "bit.ly", "tinyurl.com", "t.co", "goo.gl", "is.gd", "cutt.ly", "rb.gy",
+    "shorturl.at", "tiny.cc", "ow.ly", "rebrand.ly", "t.ly", "v.gd",
+];
+
+// Names that scam domains like to imitate
+pub const LOOKALIKE_BRANDS: &[&str] = &["discord", "steam"];
+
+// Legitimate domains (and their subdomains) that are never treated as lookalikes
+pub const TRUSTED_DOMAINS: &[&str] = &[
+    "discord.com", "discord.gg", "discordapp.com", "discordapp.net", "discord.media",
+    "discord.gift", "discord.new", "discord.dev", "discordstatus.com", "discord.js.org",
+    "discordjs.guide", "discord.co", "steampowered.com", "steamcommunity.com",
+    "steamstatic.com", "steamgames.com", "steamchina.com", "steamdeck.com",
+];
+
+// Well-known sites named after a brand but not run by it, which are never treated as lookalikes
+pub const LOOKALIKE_ALLOWLIST: &[&str] = &[
+    "steamdb.info", "steamcharts.com", "steamladder.com", "steamgifts.com", "steamid.io",
+    "discordbotlist.com", "discord.me", "discords.com", "discordservers.com", "discordlist.gg",
+];
+
+// Approximate memory, in bytes, the message log may spend remembering recent messages
+pub const MESSAGE_CACHE_BUDGET: usize = 16 * 1024 * 1024;
 
+// Roles whose names contain any of these are treated as punishment roles by sticky roles
+pub const PUNISHMENT_ROLE_NAMES: &[&str] = &["mute", "jail", "quarantine", "punish", "restrict"];
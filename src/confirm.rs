@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::futures::StreamExt;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::prelude::Message;
+
+use crate::config::EMBED_COLOR;
+use crate::settings;
+
+/// How long a confirmation prompt accepts button presses.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+
+const CONFIRM_ID: &str = "confirm";
+const CANCEL_ID: &str = "cancel";
+
+/// Asks the author of `msg` to confirm an action with Confirm/Cancel buttons.
+///
+/// Returns `true` once the author presses Confirm, or straight away if they hold one
+/// of the guild's trusted roles. Cancelling or letting the prompt expire returns `false`.
+pub async fn confirm(ctx: &Context, msg: &Message, title: &str, summary: &str) -> Result<bool> {
+    if let (Some(guild_id), Some(member)) = (msg.guild_id, &msg.member) {
+        let trusted_roles = settings::get(ctx, guild_id).await.trusted_roles;
+        if member.roles.iter().any(|role| trusted_roles.contains(role)) {
+            return Ok(true);
+        }
+    }
+    confirm_always(ctx, msg, title, summary).await
+}
+
+/// Like [`confirm`], but prompts even authors holding a trusted role.
+pub async fn confirm_always(ctx: &Context, msg: &Message, title: &str, summary: &str) -> Result<bool> {
+    let mut embed = CreateEmbed::default();
+    embed.title(title);
+    embed.description(summary);
+    embed.color(EMBED_COLOR);
+    embed.footer(|f| {
+        f.text(format!("Only {} can respond. Expires in {} seconds.", msg.author.name, CONFIRM_TIMEOUT.as_secs()));
+        f.icon_url(msg.author.face());
+        f
+    });
+
+    let mut prompt = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.set_embed(embed).components(|c| {
+                c.create_action_row(|row| {
+                    row.create_button(|b| b.custom_id(CONFIRM_ID).label("Confirm").style(ButtonStyle::Danger));
+                    row.create_button(|b| b.custom_id(CANCEL_ID).label("Cancel").style(ButtonStyle::Secondary))
+                })
+            })
+        })
+        .await?;
+
+    let mut interactions = prompt
+        .await_component_interactions(ctx)
+        .timeout(CONFIRM_TIMEOUT)
+        .build();
+
+    while let Some(interaction) = interactions.next().await {
+        if interaction.user.id != msg.author.id {
+            // A failed reply to a bystander shouldn't cancel the prompt for the author
+            let result = interaction
+                .create_interaction_response(&ctx.http, |r| {
+                    r.kind(InteractionResponseType::ChannelMessageWithSource)
+                        .interaction_response_data(|d| {
+                            d.content("Only the person who ran this command can respond.").ephemeral(true)
+                        })
+                })
+                .await;
+            if let Err(err) = result {
+                warn!("Failed to turn away {} from a confirmation prompt: {}", interaction.user.id, err);
+            }
+            continue;
+        }
+
+        let confirmed = interaction.data.custom_id == CONFIRM_ID;
+        interaction
+            .create_interaction_response(&ctx.http, |r| {
+                r.kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|d| {
+                        d.content(if confirmed { "Confirmed." } else { "Cancelled." })
+                            .components(|c| c)
+                    })
+            })
+            .await?;
+        return Ok(confirmed);
+    }
+
+    prompt
+        .edit(&ctx.http, |m| m.content("Confirmation expired.").components(|c| c))
+        .await?;
+
+    Ok(false)
+}
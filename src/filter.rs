@@ -0,0 +1,330 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{LazyLock, Mutex};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serenity::client::Context;
+use serenity::model::event::MessageUpdateEvent;
+use serenity::model::prelude::{ChannelId, Message, RoleId};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::automod::{self, AutomodAction, Offence};
+use crate::settings;
+
+static INVITE_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?:discord(?:app)?\.com/invite|discord\.gg)/([a-z0-9-]+)")
+        .expect("invite pattern should be valid")
+});
+
+/// Compiled rule patterns, keyed by their regex source.
+static COMPILED: LazyLock<Mutex<HashMap<String, Regex>>> = LazyLock::new(Default::default);
+const MAX_COMPILED: usize = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleKind {
+    /// A whole word, matched after normalization.
+    Word,
+    /// A pattern where `*` matches any run of characters and `?` a single character.
+    Wildcard,
+    /// A case-insensitive regular expression, matched against both the raw and normalized text.
+    Regex,
+    /// Discord invites to any server except the comma-separated invite codes in the pattern.
+    Invite,
+}
+
+impl RuleKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "word" => Some(Self::Word),
+            "wildcard" => Some(Self::Wildcard),
+            "regex" => Some(Self::Regex),
+            "invite" | "invites" => Some(Self::Invite),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RuleKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Word => "word",
+            Self::Wildcard => "wildcard",
+            Self::Regex => "regex",
+            Self::Invite => "invite",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FilterRule {
+    pub id: u32,
+    pub kind: RuleKind,
+    pub pattern: String,
+    pub action: AutomodAction,
+    #[serde(default)]
+    pub exempt_channels: Vec<ChannelId>,
+    #[serde(default)]
+    pub exempt_roles: Vec<RoleId>,
+}
+
+impl FilterRule {
+    /// Builds the regex source this rule is matched with, validating user-supplied regexes.
+    pub fn regex_source(kind: RuleKind, pattern: &str) -> Result<String, regex::Error> {
+        let source = match kind {
+            RuleKind::Word => format!(r"(?:^|\W){}(?:\W|$)", regex::escape(&normalize(pattern))),
+            RuleKind::Wildcard => {
+                let body = normalize(pattern)
+                    .split('*')
+                    .map(|part| part.split('?').map(regex::escape).collect::<Vec<_>>().join("."))
+                    .collect::<Vec<_>>()
+                    .join(".*");
+                format!(r"(?:^|\W){}(?:\W|$)", body)
+            }
+            RuleKind::Regex => format!("(?i){}", pattern),
+            RuleKind::Invite => return Ok(String::new()),
+        };
+        Regex::new(&source)?;
+        Ok(source)
+    }
+
+    /// Whether this rule matches a message, given its raw and [`normalize`]d content.
+    pub fn matches(&self, raw: &str, normalized: &str) -> bool {
+        if self.kind == RuleKind::Invite {
+            let allowed = self.pattern.split(',').map(str::trim).collect::<Vec<_>>();
+            return INVITE_LINK
+                .captures_iter(raw)
+                .any(|caps| !allowed.iter().any(|code| code.eq_ignore_ascii_case(&caps[1])));
+        }
+
+        let Ok(source) = Self::regex_source(self.kind, &self.pattern) else {
+            return false;
+        };
+        let mut compiled = COMPILED.lock().expect("filter cache lock shouldn't be poisoned");
+        if compiled.len() >= MAX_COMPILED {
+            compiled.clear();
+        }
+        let regex = compiled
+            .entry(source)
+            .or_insert_with_key(|source| Regex::new(source).expect("rule pattern was validated"));
+
+        match self.kind {
+            RuleKind::Regex => regex.is_match(raw) || regex.is_match(normalized),
+            _ => regex.is_match(normalized),
+        }
+    }
+
+    fn exempts(&self, channel_id: ChannelId, roles: &[RoleId]) -> bool {
+        self.exempt_channels.contains(&channel_id) || roles.iter().any(|role| self.exempt_roles.contains(role))
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterSettings {
+    pub next_id: u32,
+    pub rules: Vec<FilterRule>,
+}
+
+impl FilterSettings {
+    /// Finds the most severe rule that matches `content` and doesn't exempt its channel or author.
+    /// Rules whose action is `off` are skipped.
+    pub fn find_match(&self, content: &str, channel_id: ChannelId, roles: &[RoleId]) -> Option<&FilterRule> {
+        let normalized = normalize(content);
+        self.rules
+            .iter()
+            .filter(|rule| rule.action != AutomodAction::Off && !rule.exempts(channel_id, roles))
+            .filter(|rule| rule.matches(content, &normalized))
+            .max_by_key(|rule| rule.action)
+    }
+}
+
+/// Folds text to a canonical form so filters can't be dodged with accents, homoglyphs,
+/// fullwidth letters, zero-width characters or leetspeak.
+pub fn normalize(text: &str) -> String {
+    let chars = text
+        .nfkd()
+        .filter(|c| !is_combining_mark(*c) && !matches!(c, '\u{200B}'..='\u{200F}' | '\u{2060}' | '\u{FEFF}' | '\u{00AD}'))
+        .flat_map(char::to_lowercase)
+        .collect::<Vec<_>>();
+
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| confusable(c, chars.get(i + 1).is_some_and(|next| next.is_alphanumeric())))
+        .collect()
+}
+
+/// Maps a lowercase character to the ASCII letter it imitates. Symbols are only treated
+/// as letters when followed by one, so trailing punctuation like `!` is left alone.
+fn confusable(c: char, before_alnum: bool) -> char {
+    match c {
+        '!' | '|' if before_alnum => 'i',
+        '@' if before_alnum => 'a',
+        '$' if before_alnum => 's',
+        '+' if before_alnum => 't',
+        '0' | 'о' | 'ο' | 'σ' => 'o',
+        '1' | 'і' | 'ι' | 'ı' => 'i',
+        '3' | 'е' | 'є' | 'ε' => 'e',
+        '4' | 'а' | 'α' => 'a',
+        '5' | 'ѕ' => 's',
+        '7' | 'т' | 'τ' => 't',
+        '8' | 'в' | 'β' => 'b',
+        '9' => 'g',
+        'с' | 'ϲ' => 'c',
+        'р' | 'ρ' => 'p',
+        'х' | 'χ' => 'x',
+        'у' | 'γ' => 'y',
+        'к' | 'κ' => 'k',
+        'м' => 'm',
+        'н' | 'η' => 'n',
+        'ј' => 'j',
+        'ԁ' => 'd',
+        'ɡ' => 'g',
+        'ν' => 'v',
+        'ԝ' | 'ω' => 'w',
+        _ => c,
+    }
+}
+
+/// Checks a new message against the guild's filter. Returns `true` if a rule matched.
+pub async fn check_message(ctx: &Context, msg: &Message) -> bool {
+    let Some(guild_id) = msg.guild_id else { return false };
+    if msg.author.bot {
+        return false;
+    }
+
+    let roles = msg.member.as_ref().map(|member| member.roles.clone()).unwrap_or_default();
+    let offence = Offence {
+        guild_id,
+        channel_id: msg.channel_id,
+        message_id: msg.id,
+        author: &msg.author,
+        content: &msg.content,
+    };
+    check(ctx, &offence, &roles).await
+}
+
+/// Checks an edited message against the guild's filter.
+pub async fn check_update(ctx: &Context, event: &MessageUpdateEvent) {
+    let (Some(guild_id), Some(author), Some(content)) = (event.guild_id, &event.author, &event.content) else {
+        return;
+    };
+    if author.bot {
+        return;
+    }
+
+    if settings::get(ctx, guild_id).await.filter.rules.is_empty() {
+        return;
+    }
+
+    let roles = match ctx.cache.member_field(guild_id, author.id, |member| member.roles.clone()) {
+        Some(roles) => roles,
+        None => match guild_id.member(ctx, author.id).await {
+            Ok(member) => member.roles,
+            Err(_) => Vec::new(),
+        },
+    };
+    let offence = Offence {
+        guild_id,
+        channel_id: event.channel_id,
+        message_id: event.id,
+        author,
+        content,
+    };
+    check(ctx, &offence, &roles).await;
+}
+
+async fn check(ctx: &Context, offence: &Offence<'_>, roles: &[RoleId]) -> bool {
+    let filter = settings::get(ctx, offence.guild_id).await.filter;
+    let Some(rule) = filter.find_match(offence.content, offence.channel_id, roles) else {
+        return false;
+    };
+
+    let reason = format!("Matched filter rule #{} ({})", rule.id, rule.kind);
+    automod::punish(ctx, offence, rule.action, &reason, "Filter").await;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(kind: RuleKind, pattern: &str) -> FilterRule {
+        FilterRule {
+            id: 1,
+            kind,
+            pattern: pattern.to_string(),
+            action: AutomodAction::Delete,
+            exempt_channels: Vec::new(),
+            exempt_roles: Vec::new(),
+        }
+    }
+
+    fn matches(rule: &FilterRule, content: &str) -> bool {
+        rule.matches(content, &normalize(content))
+    }
+
+    #[test]
+    fn normalize_folds_disguised_text() {
+        assert_eq!(normalize("Héllo"), "hello");
+        assert_eq!(normalize("ｆｕｌｌｗｉｄｔｈ"), "fullwidth");
+        assert_eq!(normalize("z\u{200B}ero"), "zero");
+        assert_eq!(normalize("h3ll0 w0rld"), "hello world");
+        assert_eq!(normalize("раураl"), "paypal");
+        assert_eq!(normalize("@dm!n $ucks"), "admin sucks");
+        assert_eq!(normalize("wow!"), "wow!");
+    }
+
+    #[test]
+    fn word_rules_match_whole_words_only() {
+        let rule = rule(RuleKind::Word, "spam");
+        assert!(matches(&rule, "spam"));
+        assert!(matches(&rule, "no SPAM, please"));
+        assert!(matches(&rule, "5p4m"));
+        assert!(!matches(&rule, "spammer"));
+        assert!(!matches(&rule, "antispam"));
+    }
+
+    #[test]
+    fn word_rules_match_patterns_with_symbols_at_the_edges() {
+        let cpp = rule(RuleKind::Word, "c++");
+        assert!(matches(&cpp, "I love c++"));
+        assert!(matches(&cpp, "c++ is fine"));
+        assert!(!matches(&cpp, "abc++"));
+
+        let smiley = rule(RuleKind::Word, ":)");
+        assert!(matches(&smiley, "hi :)"));
+        assert!(matches(&smiley, ":)"));
+    }
+
+    #[test]
+    fn wildcard_rules() {
+        let rule = rule(RuleKind::Wildcard, "free*nitro");
+        assert!(matches(&rule, "get FREE discord nitro"));
+        assert!(!matches(&rule, "nitro is free"));
+    }
+
+    #[test]
+    fn invite_rules_allow_listed_codes() {
+        let rule = rule(RuleKind::Invite, "rusty, Friends");
+        assert!(!matches(&rule, "join discord.gg/rusty"));
+        assert!(!matches(&rule, "discord.com/invite/friends"));
+        assert!(matches(&rule, "discord.gg/elsewhere"));
+    }
+
+    #[test]
+    fn find_match_skips_disabled_and_exempt_rules() {
+        let mut off = rule(RuleKind::Word, "spam");
+        off.action = AutomodAction::Off;
+        let mut exempt = rule(RuleKind::Word, "spam");
+        exempt.exempt_channels.push(ChannelId(5));
+        let settings = FilterSettings { next_id: 2, rules: vec![off, exempt] };
+
+        assert!(settings.find_match("spam", ChannelId(5), &[]).is_none());
+        assert!(settings.find_match("spam", ChannelId(6), &[]).is_some_and(|rule| rule.action == AutomodAction::Delete));
+    }
+}
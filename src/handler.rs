@@ -1,15 +1,153 @@
-use serenity::{async_trait, model::prelude::Ready, prelude::*};
+use serenity::{
+    async_trait,
+    model::application::interaction::Interaction,
+    model::event::{InviteCreateEvent, MessageUpdateEvent},
+    model::prelude::{ChannelId, Guild, GuildChannel, GuildId, Member, PartialGuildChannel, Message, MessageId, Ready, User},
+    prelude::*,
+};
+
+use crate::{antiraid, automod, cases, filter, invites, linkscan, memberlog, modmail, msglog, screening, stickyroles, suggestions, tickets};
 
 pub struct Handler;
 
 #[async_trait]
 impl EventHandler for Handler {
     #[instrument(level = "error", skip_all)]
-    async fn ready(&self, _ctx: Context, bot: Ready) {
+    async fn ready(&self, ctx: Context, bot: Ready) {
         info!(
             id = u64::from(bot.user.id),
             "Logged in as {}",
             bot.user.tag()
         );
+        antiraid::resume(&ctx).await;
+    }
+
+    #[instrument(level = "error", skip_all, fields(guild_id = u64::from(guild.id)))]
+    async fn guild_create(&self, ctx: Context, guild: Guild, _is_new: bool) {
+        invites::refresh(&ctx, guild.id).await;
+    }
+
+    #[instrument(level = "error", skip_all)]
+    async fn invite_create(&self, ctx: Context, data: InviteCreateEvent) {
+        invites::on_invite_create(&ctx, &data).await;
+    }
+
+    #[instrument(level = "error", skip_all, fields(msg_id = u64::from(msg.id)))]
+    async fn message(&self, ctx: Context, msg: Message) {
+        // Direct messages are relayed to modmail by the framework, unless they ran a command
+        if msg.guild_id.is_none() {
+            return;
+        }
+        msglog::record(&ctx, &msg).await;
+        if linkscan::check_message(&ctx, &msg).await || filter::check_message(&ctx, &msg).await {
+            return;
+        }
+        automod::check_message(&ctx, &msg).await;
+    }
+
+    #[instrument(level = "error", skip_all, fields(msg_id = u64::from(event.id)))]
+    async fn message_update(
+        &self,
+        ctx: Context,
+        _old_if_available: Option<Message>,
+        _new: Option<Message>,
+        event: MessageUpdateEvent,
+    ) {
+        msglog::on_update(&ctx, &event).await;
+        filter::check_update(&ctx, &event).await;
+    }
+
+    #[instrument(level = "error", skip_all, fields(msg_id = u64::from(deleted_message_id)))]
+    async fn message_delete(
+        &self,
+        ctx: Context,
+        channel_id: ChannelId,
+        deleted_message_id: MessageId,
+        guild_id: Option<GuildId>,
+    ) {
+        msglog::on_delete(&ctx, guild_id, channel_id, deleted_message_id).await;
+    }
+
+    #[instrument(level = "error", skip_all, fields(channel_id = u64::from(channel_id)))]
+    async fn message_delete_bulk(
+        &self,
+        ctx: Context,
+        channel_id: ChannelId,
+        multiple_deleted_messages_ids: Vec<MessageId>,
+        guild_id: Option<GuildId>,
+    ) {
+        msglog::on_bulk_delete(&ctx, guild_id, channel_id, &multiple_deleted_messages_ids).await;
+    }
+
+    #[instrument(level = "error", skip_all, fields(user_id = u64::from(new_member.user.id)))]
+    async fn guild_member_addition(&self, ctx: Context, new_member: Member) {
+        // Raid mode is enforced first so kicks aren't held up by fetching invites
+        let kicked = antiraid::on_member_join(&ctx, &new_member).await;
+        let invite = invites::on_member_join(&ctx, &new_member).await;
+        memberlog::on_join(&ctx, &new_member, invite.as_ref()).await;
+        if kicked {
+            return;
+        }
+        stickyroles::on_member_join(&ctx, &new_member).await;
+        screening::on_member_join(&ctx, &new_member).await;
+    }
+
+    #[instrument(level = "error", skip_all, fields(user_id = u64::from(new.user.id)))]
+    async fn guild_member_update(&self, ctx: Context, old_if_available: Option<Member>, new: Member) {
+        if let Some(old) = old_if_available {
+            cases::on_member_update(&ctx, &old, &new).await;
+            memberlog::on_update(&ctx, &old, &new).await;
+        }
+        stickyroles::on_member_update(&ctx, &new).await;
+    }
+
+    #[instrument(level = "error", skip_all, fields(user_id = u64::from(user.id)))]
+    async fn guild_member_removal(
+        &self,
+        ctx: Context,
+        guild_id: GuildId,
+        user: User,
+        member_data_if_available: Option<Member>,
+    ) {
+        invites::on_member_leave(&ctx, guild_id, user.id).await;
+        stickyroles::on_member_leave(&ctx, guild_id, user.id, member_data_if_available.as_ref()).await;
+        memberlog::on_leave(&ctx, guild_id, &user, member_data_if_available.as_ref()).await;
+    }
+
+    #[instrument(level = "error", skip_all, fields(user_id = u64::from(banned_user.id)))]
+    async fn guild_ban_addition(&self, ctx: Context, guild_id: GuildId, banned_user: User) {
+        memberlog::on_ban(&ctx, guild_id, &banned_user).await;
+    }
+
+    #[instrument(level = "error", skip_all, fields(user_id = u64::from(unbanned_user.id)))]
+    async fn guild_ban_removal(&self, ctx: Context, guild_id: GuildId, unbanned_user: User) {
+        memberlog::on_unban(&ctx, guild_id, &unbanned_user).await;
+    }
+
+    #[instrument(level = "error", skip_all, fields(channel_id = u64::from(channel.id)))]
+    async fn channel_delete(&self, ctx: Context, channel: &GuildChannel) {
+        modmail::on_channel_delete(&ctx, channel.id).await;
+        tickets::on_channel_delete(&ctx, channel.guild_id, channel.id).await;
+    }
+
+    #[instrument(level = "error", skip_all, fields(guild_id = u64::from(thread.guild_id)))]
+    async fn thread_delete(&self, ctx: Context, thread: PartialGuildChannel) {
+        tickets::on_channel_delete(&ctx, thread.guild_id, thread.id).await;
+    }
+
+    #[instrument(level = "error", skip_all)]
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        let Interaction::MessageComponent(component) = interaction else {
+            return;
+        };
+
+        let custom_id = component.data.custom_id.as_str();
+        if custom_id.starts_with(screening::BUTTON_PREFIX) {
+            screening::handle_button(&ctx, &component).await;
+        } else if custom_id.starts_with(suggestions::BUTTON_PREFIX) {
+            suggestions::handle_button(&ctx, &component).await;
+        } else if custom_id.starts_with(tickets::BUTTON_PREFIX) {
+            tickets::handle_button(&ctx, &component).await;
+        }
     }
-}
\ No newline at end of file
+}
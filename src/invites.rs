@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serenity::client::Context;
+use serenity::model::event::InviteCreateEvent;
+use serenity::model::invite::RichInvite;
+use serenity::model::prelude::{GuildId, Member, UserId};
+use serenity::model::Timestamp;
+use serenity::prelude::TypeMapKey;
+use tokio::sync::Mutex;
+
+use crate::store::{self, Store};
+
+/// What we last saw of an invite.
+#[derive(Clone)]
+pub struct CachedInvite {
+    uses: u64,
+    max_uses: u64,
+    inviter: Option<UserId>,
+}
+
+/// Invite usage counts per guild, compared against fresh counts when someone joins.
+pub struct InviteCache;
+
+impl TypeMapKey for InviteCache {
+    type Value = Arc<Mutex<HashMap<GuildId, HashMap<String, CachedInvite>>>>;
+}
+
+/// How a member joined, as far as the invite usage counts could tell.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Attribution {
+    pub code: Option<String>,
+    pub inviter: Option<UserId>,
+    #[serde(default)]
+    pub vanity: bool,
+    pub joined_at: Timestamp,
+    #[serde(default)]
+    pub left: bool,
+}
+
+impl Attribution {
+    pub fn describe(&self) -> String {
+        match (&self.code, self.inviter) {
+            (Some(code), _) if self.vanity => format!("Vanity URL (discord.gg/{})", code),
+            (Some(code), Some(inviter)) => format!("discord.gg/{} by <@{}>", code, inviter),
+            (Some(code), None) => format!("discord.gg/{}", code),
+            (None, _) => "Unknown".to_string(),
+        }
+    }
+}
+
+/// Persisted join attributions, keyed by the member who joined.
+pub struct InviteRecords;
+
+impl TypeMapKey for InviteRecords {
+    type Value = Arc<Store<HashMap<GuildId, HashMap<UserId, Attribution>>>>;
+}
+
+/// Per-inviter totals for `rinvites`.
+#[derive(Default)]
+pub struct InviterStats {
+    pub invited: usize,
+    pub stayed: usize,
+}
+
+fn snapshot(invites: Vec<RichInvite>) -> HashMap<String, CachedInvite> {
+    invites
+        .into_iter()
+        .map(|invite| {
+            let cached = CachedInvite {
+                uses: invite.uses,
+                max_uses: invite.max_uses,
+                inviter: invite.inviter.map(|user| user.id),
+            };
+            (invite.code, cached)
+        })
+        .collect()
+}
+
+/// Records the current usage counts for a guild's invites. Needs Manage Server.
+pub async fn refresh(ctx: &Context, guild_id: GuildId) {
+    match guild_id.invites(&ctx.http).await {
+        Ok(invites) => {
+            let cache = store::get::<InviteCache>(ctx).await;
+            cache.lock().await.insert(guild_id, snapshot(invites));
+        }
+        Err(err) => warn!("Failed to fetch invites for guild {}: {}", guild_id, err),
+    }
+}
+
+pub async fn on_invite_create(ctx: &Context, event: &InviteCreateEvent) {
+    let Some(guild_id) = event.guild_id else { return };
+    let cache = store::get::<InviteCache>(ctx).await;
+    let invite = CachedInvite {
+        uses: 0,
+        max_uses: event.max_uses,
+        inviter: event.inviter.as_ref().map(|user| user.id),
+    };
+    cache.lock().await.entry(guild_id).or_default().insert(event.code.clone(), invite);
+}
+
+/// Works out which invite a new member used and persists the result.
+pub async fn on_member_join(ctx: &Context, member: &Member) -> Option<Attribution> {
+    let guild_id = member.guild_id;
+    let fresh = match guild_id.invites(&ctx.http).await {
+        Ok(invites) => snapshot(invites),
+        Err(err) => {
+            warn!("Failed to fetch invites for guild {}: {}", guild_id, err);
+            return None;
+        }
+    };
+
+    // Diffed against whatever is cached now, since simultaneous joins may have stored a newer
+    // snapshot while this one was being fetched. Counts never go backwards, so a fetch that
+    // finishes late can't make the next join look like it used an invite twice.
+    let cache = store::get::<InviteCache>(ctx).await;
+    let mut cache = cache.lock().await;
+    let old = cache.get(&guild_id).cloned().unwrap_or_default();
+    let merged = fresh
+        .iter()
+        .map(|(code, invite)| {
+            let uses = old.get(code).map_or(invite.uses, |old| old.uses.max(invite.uses));
+            (code.clone(), CachedInvite { uses, ..invite.clone() })
+        })
+        .collect();
+    cache.insert(guild_id, merged);
+
+    // Invites created while we were offline aren't cached, so they count from zero
+    let mut used = fresh
+        .iter()
+        .filter(|(code, invite)| invite.uses > old.get(*code).map_or(0, |old| old.uses))
+        .map(|(code, invite)| (code.clone(), invite.inviter))
+        .collect::<Vec<_>>();
+    if used.is_empty() {
+        // A limited invite disappears once its last use is taken
+        used = old
+            .iter()
+            .filter(|(code, invite)| !fresh.contains_key(*code) && invite.max_uses > 0 && invite.uses + 1 >= invite.max_uses)
+            .map(|(code, invite)| (code.clone(), invite.inviter))
+            .collect();
+    }
+    drop(cache);
+
+    let vanity_code = guild_id.to_guild_cached(&ctx.cache).and_then(|guild| guild.vanity_url_code);
+    let (code, inviter, vanity) = match (used.as_slice(), vanity_code) {
+        ([(code, inviter)], _) => (Some(code.clone()), *inviter, false),
+        ([], Some(vanity)) => (Some(vanity), None, true),
+        _ => (None, None, false),
+    };
+    let attribution = Attribution { code, inviter, vanity, joined_at: Timestamp::now(), left: false };
+
+    let records = store::get::<InviteRecords>(ctx).await;
+    let result = records
+        .write(|all| all.entry(guild_id).or_default().insert(member.user.id, attribution.clone()))
+        .await;
+    if let Err(err) = result {
+        error!("Failed to save invite attribution for {}: {:?}", member.user.id, err);
+    }
+
+    Some(attribution)
+}
+
+pub async fn on_member_leave(ctx: &Context, guild_id: GuildId, user_id: UserId) {
+    let records = store::get::<InviteRecords>(ctx).await;
+    let result = records
+        .write(|all| {
+            if let Some(attribution) = all.get_mut(&guild_id).and_then(|joins| joins.get_mut(&user_id)) {
+                attribution.left = true;
+            }
+        })
+        .await;
+    if let Err(err) = result {
+        error!("Failed to update invite attribution for {}: {:?}", user_id, err);
+    }
+}
+
+/// Returns how a member joined, if it was recorded.
+pub async fn attribution(ctx: &Context, guild_id: GuildId, user_id: UserId) -> Option<Attribution> {
+    let records = store::get::<InviteRecords>(ctx).await;
+    let all = records.read().await;
+    all.get(&guild_id)?.get(&user_id).cloned()
+}
+
+/// Totals for every member who has invited someone.
+pub async fn stats(ctx: &Context, guild_id: GuildId) -> HashMap<UserId, InviterStats> {
+    let records = store::get::<InviteRecords>(ctx).await;
+    let all = records.read().await;
+
+    let mut stats = HashMap::<UserId, InviterStats>::new();
+    for attribution in all.get(&guild_id).into_iter().flat_map(|joins| joins.values()) {
+        if let Some(inviter) = attribution.inviter {
+            let entry = stats.entry(inviter).or_default();
+            entry.invited += 1;
+            if !attribution.left {
+                entry.stayed += 1;
+            }
+        }
+    }
+    stats
+}
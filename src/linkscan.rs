@@ -0,0 +1,317 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, SystemTime};
+
+use regex::Regex;
+use reqwest::redirect::Policy;
+use serde::{Deserialize, Serialize};
+use serenity::client::Context;
+use serenity::model::prelude::Message;
+use serenity::prelude::TypeMapKey;
+use tokio::sync::RwLock;
+use url::Url;
+
+use crate::automod::{self, AutomodAction, Offence};
+use crate::config::{
+    BLOCKLIST_RELOAD_INTERVAL, LINK_SHORTENERS, LOOKALIKE_ALLOWLIST, LOOKALIKE_BRANDS, PHISHING_BLOCKLIST_FILE,
+    TRUSTED_DOMAINS,
+};
+use crate::filter::normalize;
+use crate::utils::levenshtein;
+use crate::{settings, store};
+
+static URL_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:https?://|www\.)[^\s<>|]+").expect("URL pattern should be valid")
+});
+
+static REDIRECT_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .redirect(Policy::none())
+        .timeout(REDIRECT_TIMEOUT)
+        .build()
+        .expect("link scanner HTTP client should build")
+});
+
+/// Maximum number of shortener redirects followed for a single link.
+const MAX_REDIRECTS: usize = 5;
+const REDIRECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Link scanning is off until a server turns it on, since lookalike detection can flag
+/// legitimate fan sites.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkScanSettings {
+    pub enabled: bool,
+}
+
+/// Known phishing domains, loaded from a text file with one domain per line.
+#[derive(Default)]
+pub struct Blocklist {
+    path: PathBuf,
+    domains: HashSet<String>,
+    modified: Option<SystemTime>,
+    /// Whether the file couldn't be read last time, so the warning isn't repeated every reload.
+    unreadable: bool,
+}
+
+impl Blocklist {
+    /// Re-reads the file if it changed since the last load. Returns whether it was reloaded.
+    pub async fn reload(&mut self) -> bool {
+        let modified = tokio::fs::metadata(&self.path).await.and_then(|meta| meta.modified()).ok();
+        if modified.is_some() && modified == self.modified {
+            return false;
+        }
+
+        match tokio::fs::read_to_string(&self.path).await {
+            Ok(contents) => {
+                self.domains = contents
+                    .lines()
+                    .map(|line| line.split('#').next().unwrap_or_default().trim().to_lowercase())
+                    .filter(|line| !line.is_empty())
+                    .collect();
+                self.modified = modified;
+                self.unreadable = false;
+                info!("Loaded {} phishing domains from {}", self.domains.len(), self.path.display());
+                true
+            }
+            Err(err) => {
+                if !self.unreadable {
+                    warn!("Failed to read phishing blocklist {}: {}", self.path.display(), err);
+                    self.unreadable = true;
+                }
+                self.modified = modified;
+                false
+            }
+        }
+    }
+
+    /// Whether `host` or any domain it is a subdomain of is blocklisted.
+    pub fn contains(&self, host: &str) -> bool {
+        parent_domains(host).any(|domain| self.domains.contains(domain))
+    }
+}
+
+pub struct PhishingBlocklist;
+
+impl TypeMapKey for PhishingBlocklist {
+    type Value = Arc<RwLock<Blocklist>>;
+}
+
+/// Creates an empty blocklist backed by the file in the data directory. It's filled in by
+/// [`reload_periodically`].
+pub fn load_blocklist() -> Arc<RwLock<Blocklist>> {
+    let path = store::data_dir().join(PHISHING_BLOCKLIST_FILE);
+    Arc::new(RwLock::new(Blocklist { path, ..Default::default() }))
+}
+
+/// Loads the blocklist file, then picks up edits to it without restarting the bot.
+pub async fn reload_periodically(blocklist: Arc<RwLock<Blocklist>>) {
+    let mut interval = tokio::time::interval(BLOCKLIST_RELOAD_INTERVAL);
+    loop {
+        interval.tick().await;
+        blocklist.write().await.reload().await;
+    }
+}
+
+/// Yields `host` followed by each of its parent domains, e.g. `a.b.com`, `b.com`, `com`.
+fn parent_domains(host: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(host), |domain| domain.split_once('.').map(|(_, parent)| parent))
+}
+
+fn is_listed(host: &str, list: &[&str]) -> bool {
+    parent_domains(host).any(|domain| list.contains(&domain))
+}
+
+/// Finds every link in a message, parsed into normalized URLs with punycode hosts.
+pub fn extract_urls(content: &str) -> Vec<Url> {
+    URL_PATTERN
+        .find_iter(content)
+        .filter_map(|found| {
+            let link = found.as_str().trim_end_matches(['>', ')', '.', ',', '!', '?', '"', '\'']);
+            if link.to_lowercase().starts_with("www.") {
+                Url::parse(&format!("https://{}", link)).ok()
+            } else {
+                Url::parse(link).ok()
+            }
+        })
+        .collect()
+}
+
+/// Returns the brand a domain appears to imitate, if it isn't one of the brand's real domains
+/// or a well-known site named after it.
+///
+/// Punycode is decoded and homoglyphs are folded before comparing, so `dіscord.gift` (Cyrillic `і`)
+/// and `disc0rd-nitro.com` are both caught, as are near-misses of the real domain names like
+/// `steamcommunlty.com`. Words that merely contain a brand, like `steamdb`, aren't flagged.
+pub fn lookalike(host: &str) -> Option<&'static str> {
+    if is_listed(host, TRUSTED_DOMAINS) || is_listed(host, LOOKALIKE_ALLOWLIST) {
+        return None;
+    }
+
+    let (unicode, _) = idna::domain_to_unicode(host);
+    let folded = normalize(&unicode);
+    let labels = folded.split('.').collect::<Vec<_>>();
+    // The top-level domain can't be chosen by a scammer, so it's ignored
+    let labels = &labels[..labels.len().saturating_sub(1)];
+
+    labels
+        .iter()
+        .flat_map(|label| std::iter::once(*label).chain(label.split('-')))
+        .find_map(imitated_brand)
+}
+
+/// Checks whether a word is a brand name or a trusted domain name, or a near-miss of one such
+/// as `dlscord`. Short names must match exactly, so `stream` isn't mistaken for `steam`.
+fn imitated_brand(word: &str) -> Option<&'static str> {
+    let trusted_names = TRUSTED_DOMAINS.iter().filter_map(|trusted| {
+        let label = trusted.split('.').next().unwrap_or_default();
+        LOOKALIKE_BRANDS.iter().find(|brand| label.starts_with(*brand)).map(|brand| (label, *brand))
+    });
+
+    LOOKALIKE_BRANDS
+        .iter()
+        .map(|brand| (*brand, *brand))
+        .chain(trusted_names)
+        .find(|(name, _)| {
+            let max_distance = match name.len() {
+                10.. => 2,
+                7.. => 1,
+                _ => 0,
+            };
+            levenshtein(word, name) <= max_distance
+        })
+        .map(|(_, brand)| brand)
+}
+
+/// Follows redirects from link shorteners to find the final destination of a URL.
+async fn resolve_shorteners(url: Url) -> Url {
+    let mut current = url;
+    for _ in 0..MAX_REDIRECTS {
+        let Some(host) = current.host_str() else { break };
+        if !is_listed(host, LINK_SHORTENERS) {
+            break;
+        }
+
+        let response = match REDIRECT_CLIENT.head(current.clone()).send().await {
+            Ok(response) => response,
+            Err(err) => {
+                debug!("Failed to follow shortened link {}: {}", current, err);
+                break;
+            }
+        };
+        let next = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| current.join(location).ok());
+        match next {
+            Some(next) => current = next,
+            None => break,
+        }
+    }
+
+    current
+}
+
+/// Why a link was flagged.
+enum Verdict {
+    Blocklisted(String),
+    Lookalike(String, &'static str),
+}
+
+/// Scans a message for phishing links, deleting it and timing out the author on a match.
+/// Returns `true` if the message was flagged.
+pub async fn check_message(ctx: &Context, msg: &Message) -> bool {
+    let Some(guild_id) = msg.guild_id else { return false };
+    if msg.author.bot {
+        return false;
+    }
+
+    let urls = extract_urls(&msg.content);
+    if urls.is_empty() || !settings::get(ctx, guild_id).await.linkscan.enabled {
+        return false;
+    }
+
+    let blocklist = store::get::<PhishingBlocklist>(ctx).await;
+    let mut verdict = None;
+    for url in urls {
+        let url = resolve_shorteners(url).await;
+        let Some(host) = url.host_str() else { continue };
+        let host = host.trim_end_matches('.').to_lowercase();
+
+        if blocklist.read().await.contains(&host) {
+            verdict = Some(Verdict::Blocklisted(host));
+            break;
+        }
+        if let Some(brand) = lookalike(&host) {
+            verdict = Some(Verdict::Lookalike(host, brand));
+            break;
+        }
+    }
+
+    let reason = match verdict {
+        Some(Verdict::Blocklisted(host)) => format!("Phishing link to blocklisted domain `{}`", host),
+        Some(Verdict::Lookalike(host, brand)) => format!("Suspicious link to `{}`, which imitates {}", host, brand),
+        None => return false,
+    };
+
+    let offence = Offence {
+        guild_id,
+        channel_id: msg.channel_id,
+        message_id: msg.id,
+        author: &msg.author,
+        content: &msg.content,
+    };
+    automod::punish(ctx, &offence, AutomodAction::Timeout, &reason, "Link Scanner").await;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_urls_finds_links_and_trims_punctuation() {
+        let urls = extract_urls("see https://example.com/a?b=c, (www.Example.org/path) and <https://x.io>.");
+        let urls = urls.iter().map(Url::as_str).collect::<Vec<_>>();
+        assert_eq!(urls, ["https://example.com/a?b=c", "https://www.example.org/path", "https://x.io/"]);
+        assert!(extract_urls("no links, just example.com").is_empty());
+    }
+
+    #[test]
+    fn extract_urls_encodes_unicode_hosts_as_punycode() {
+        let urls = extract_urls("https://dіscord.gift/free");
+        assert_eq!(urls.len(), 1);
+        assert!(urls[0].host_str().is_some_and(|host| host.starts_with("xn--")));
+    }
+
+    #[test]
+    fn lookalike_catches_imitations() {
+        assert_eq!(lookalike("disc0rd-nitro.com"), Some("discord"));
+        assert_eq!(lookalike("dlscord.gift"), Some("discord"));
+        assert_eq!(lookalike("steamcommunlty.com"), Some("steam"));
+        assert_eq!(lookalike("discord.com.evil.ru"), Some("discord"));
+
+        let host = extract_urls("https://dіscord.gift")[0].host_str().unwrap().to_string();
+        assert_eq!(lookalike(&host), Some("discord"));
+    }
+
+    #[test]
+    fn lookalike_leaves_real_and_unrelated_sites_alone() {
+        for host in [
+            "discord.com",
+            "cdn.discordapp.com",
+            "steamcommunity.com",
+            "steamdb.info",
+            "steamcharts.com",
+            "discordbotlist.com",
+            "mysteamstats.net",
+            "stream.com",
+            "team-fortress.net",
+            "example.com",
+        ] {
+            assert_eq!(lookalike(host), None, "{} shouldn't be flagged", host);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serenity::client::Context;
+use serenity::model::channel::{PermissionOverwrite, PermissionOverwriteType};
+use serenity::model::prelude::{ChannelId, RoleId};
+use serenity::model::Permissions;
+use serenity::prelude::TypeMapKey;
+
+use crate::store::{self, Store};
+
+/// Permissions removed from @everyone while a channel is locked.
+const LOCKED_PERMISSIONS: Permissions = Permissions::SEND_MESSAGES
+    .union(Permissions::SEND_MESSAGES_IN_THREADS)
+    .union(Permissions::CREATE_PUBLIC_THREADS)
+    .union(Permissions::CREATE_PRIVATE_THREADS);
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LockdownSettings {
+    /// Channels locked by a server-wide lockdown.
+    pub channels: Vec<ChannelId>,
+    /// Notice posted in channels when they are locked.
+    pub notice: Option<String>,
+}
+
+/// The @everyone overwrite a channel had before it was locked.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct SavedOverwrite {
+    pub allow: Permissions,
+    pub deny: Permissions,
+}
+
+pub struct LockedChannels;
+
+impl TypeMapKey for LockedChannels {
+    /// Locked channels mapped to their original @everyone overwrite, if they had one.
+    type Value = Arc<Store<HashMap<ChannelId, Option<SavedOverwrite>>>>;
+}
+
+/// Denies @everyone from sending messages in a channel, remembering the original overwrite.
+/// Returns `false` if the channel was already locked.
+pub async fn lock(ctx: &Context, channel_id: ChannelId) -> Result<bool> {
+    let locked = store::get::<LockedChannels>(ctx).await;
+    if locked.read().await.contains_key(&channel_id) {
+        return Ok(false);
+    }
+
+    let Some(channel) = channel_id.to_channel(ctx).await?.guild() else {
+        bail!("{} is not a server channel", channel_id);
+    };
+    let everyone = RoleId(channel.guild_id.0);
+    let original = channel
+        .permission_overwrites
+        .iter()
+        .find(|overwrite| overwrite.kind == PermissionOverwriteType::Role(everyone))
+        .map(|overwrite| SavedOverwrite { allow: overwrite.allow, deny: overwrite.deny });
+
+    // Remember the original before touching Discord so a crash can't lose it
+    locked.write(|channels| channels.insert(channel_id, original)).await?;
+
+    let (allow, deny) = original.map_or((Permissions::empty(), Permissions::empty()), |o| (o.allow, o.deny));
+    let overwrite = PermissionOverwrite {
+        allow: allow - LOCKED_PERMISSIONS,
+        deny: deny | LOCKED_PERMISSIONS,
+        kind: PermissionOverwriteType::Role(everyone),
+    };
+    if let Err(err) = channel_id.create_permission(&ctx.http, &overwrite).await {
+        locked.write(|channels| channels.remove(&channel_id)).await?;
+        return Err(err.into());
+    }
+
+    Ok(true)
+}
+
+/// Restores the @everyone overwrite a channel had before it was locked.
+/// Returns `false` if the bot hadn't locked the channel.
+pub async fn unlock(ctx: &Context, channel_id: ChannelId) -> Result<bool> {
+    let locked = store::get::<LockedChannels>(ctx).await;
+    let Some(original) = locked.read().await.get(&channel_id).copied() else {
+        return Ok(false);
+    };
+
+    let Some(channel) = channel_id.to_channel(ctx).await?.guild() else {
+        bail!("{} is not a server channel", channel_id);
+    };
+    let kind = PermissionOverwriteType::Role(RoleId(channel.guild_id.0));
+    match original {
+        Some(SavedOverwrite { allow, deny }) => {
+            channel_id
+                .create_permission(&ctx.http, &PermissionOverwrite { allow, deny, kind })
+                .await?
+        }
+        None => channel_id.delete_permission(&ctx.http, kind).await?,
+    }
+
+    locked.write(|channels| channels.remove(&channel_id)).await?;
+
+    Ok(true)
+}
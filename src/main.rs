@@ -2,15 +2,42 @@
 extern crate tracing;
 
 use std::env;
+use std::sync::Arc;
 
 use anyhow::{Context, Result};
 use serenity::prelude::*;
 use tracing_subscriber::util::SubscriberInitExt;
 
+mod announcements;
+mod antiraid;
+mod automod;
+mod cases;
+mod changelog;
 mod config;
 mod commands;
+mod confirm;
+mod filter;
 mod handler;
+mod invites;
+mod linkscan;
+mod lockdown;
+mod memberlog;
 mod log;
+mod modlog;
+mod modmail;
+mod msglog;
+mod perms;
+mod reports;
+mod resolve;
+mod schedules;
+mod screening;
+mod settings;
+mod stickyroles;
+mod store;
+mod suggestions;
+mod tickets;
+mod transcript;
+mod utils;
 
 // note: this value is mirrored in src/commands/help.rs
 pub const EMBED_COLOR: [u8; 3] = [0x58, 0x65, 0xF2];
@@ -18,14 +45,56 @@ pub const EMBED_COLOR: [u8; 3] = [0x58, 0x65, 0xF2];
 async fn client() -> Result<Client> {
     let token =
         env::var("DISCORD_TOKEN").context("failed to load `DISCORD_TOKEN` environment variable")?;
-    let intents = GatewayIntents::non_privileged() | GatewayIntents::MESSAGE_CONTENT;
+    let intents = GatewayIntents::non_privileged()
+        | GatewayIntents::MESSAGE_CONTENT
+        | GatewayIntents::GUILD_MEMBERS;
+
+    let settings = Arc::new(store::Store::open("settings").context("failed to load guild settings")?);
+    let locked_channels = store::Store::open("locked_channels").context("failed to load locked channels")?;
+    let raids = store::Store::open("raid_mode").context("failed to load raid mode")?;
+    let records = store::Store::open("records").context("failed to load moderation records")?;
+    let invite_records = store::Store::open("invites").context("failed to load invite attributions")?;
+    let sticky_roles = store::Store::open("sticky_roles").context("failed to load sticky roles")?;
+    let modmail_threads = store::Store::open("modmail").context("failed to load modmail threads")?;
+    let suggestions = store::Store::open("suggestions").context("failed to load suggestions")?;
+    let reports = store::Store::open("reports").context("failed to load reports")?;
+    let tickets = store::Store::open("tickets").context("failed to load tickets")?;
+    let embed_templates = Arc::new(store::Store::open("embed_templates").context("failed to load embed templates")?);
+    let posted_embeds = store::Store::open("posted_embeds").context("failed to load posted embeds")?;
+    let schedules = Arc::new(store::Store::open("schedules").context("failed to load schedules")?);
+    let announced_version = store::Store::open("changelog").context("failed to load the announced version")?;
+
+    let blocklist = linkscan::load_blocklist();
+    tokio::spawn(linkscan::reload_periodically(blocklist.clone()));
 
     let client = Client::builder(token, intents)
+        .type_map_insert::<settings::Settings>(settings.clone())
+        .type_map_insert::<automod::AutomodTracker>(Default::default())
+        .type_map_insert::<linkscan::PhishingBlocklist>(blocklist)
+        .type_map_insert::<lockdown::LockedChannels>(Arc::new(locked_channels))
+        .type_map_insert::<antiraid::RecentJoins>(Default::default())
+        .type_map_insert::<antiraid::RaidSuspects>(Default::default())
+        .type_map_insert::<antiraid::ActiveRaids>(Arc::new(raids))
+        .type_map_insert::<cases::Records>(Arc::new(records))
+        .type_map_insert::<msglog::MessageLogCache>(Default::default())
+        .type_map_insert::<invites::InviteCache>(Default::default())
+        .type_map_insert::<invites::InviteRecords>(Arc::new(invite_records))
+        .type_map_insert::<stickyroles::StickyRoles>(Arc::new(sticky_roles))
+        .type_map_insert::<modmail::ModmailThreads>(Arc::new(modmail_threads))
+        .type_map_insert::<suggestions::Suggestions>(Arc::new(suggestions))
+        .type_map_insert::<reports::Reports>(Arc::new(reports))
+        .type_map_insert::<tickets::Tickets>(Arc::new(tickets))
+        .type_map_insert::<announcements::EmbedTemplates>(embed_templates.clone())
+        .type_map_insert::<announcements::PostedEmbeds>(Arc::new(posted_embeds))
+        .type_map_insert::<schedules::Schedules>(schedules.clone())
         .event_handler(handler::Handler)
         .framework(commands::framework())
         .await
         .expect("Discord client should build successfully");
 
+    tokio::spawn(schedules::run(client.cache_and_http.clone(), schedules, embed_templates, settings.clone()));
+    tokio::spawn(changelog::announce(client.cache_and_http.clone(), settings, announced_version));
+
     Ok(client)
 }
 
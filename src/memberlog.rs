@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::model::prelude::{ChannelId, GuildId, Member, RoleId, User};
+use serenity::model::Timestamp;
+
+use crate::config::EMBED_COLOR;
+use crate::invites::Attribution;
+use crate::settings;
+use crate::utils::truncate;
+
+/// Member lifecycle events that can each be sent to their own channel.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MemberEvent {
+    Joins,
+    Leaves,
+    Nicknames,
+    Roles,
+    Avatars,
+    Bans,
+}
+
+impl MemberEvent {
+    pub const ALL: [MemberEvent; 6] = [
+        MemberEvent::Joins,
+        MemberEvent::Leaves,
+        MemberEvent::Nicknames,
+        MemberEvent::Roles,
+        MemberEvent::Avatars,
+        MemberEvent::Bans,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MemberEvent::Joins => "joins",
+            MemberEvent::Leaves => "leaves",
+            MemberEvent::Nicknames => "nicknames",
+            MemberEvent::Roles => "roles",
+            MemberEvent::Avatars => "avatars",
+            MemberEvent::Bans => "bans",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|event| event.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Log channel for each member event; events without a channel aren't logged.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemberLogSettings {
+    pub joins: Option<ChannelId>,
+    pub leaves: Option<ChannelId>,
+    pub nicknames: Option<ChannelId>,
+    pub roles: Option<ChannelId>,
+    pub avatars: Option<ChannelId>,
+    pub bans: Option<ChannelId>,
+}
+
+impl MemberLogSettings {
+    pub fn channel_mut(&mut self, event: MemberEvent) -> &mut Option<ChannelId> {
+        match event {
+            MemberEvent::Joins => &mut self.joins,
+            MemberEvent::Leaves => &mut self.leaves,
+            MemberEvent::Nicknames => &mut self.nicknames,
+            MemberEvent::Roles => &mut self.roles,
+            MemberEvent::Avatars => &mut self.avatars,
+            MemberEvent::Bans => &mut self.bans,
+        }
+    }
+
+    pub fn channel(&self, event: MemberEvent) -> Option<ChannelId> {
+        match event {
+            MemberEvent::Joins => self.joins,
+            MemberEvent::Leaves => self.leaves,
+            MemberEvent::Nicknames => self.nicknames,
+            MemberEvent::Roles => self.roles,
+            MemberEvent::Avatars => self.avatars,
+            MemberEvent::Bans => self.bans,
+        }
+    }
+}
+
+async fn post(ctx: &Context, guild_id: GuildId, event: MemberEvent, user: &User, mut embed: CreateEmbed) {
+    let Some(channel_id) = settings::get(ctx, guild_id).await.member_log.channel(event) else {
+        return;
+    };
+
+    embed.author(|a| a.name(user.tag()).icon_url(user.face()));
+    embed.footer(|f| f.text(format!("User ID: {}", user.id)));
+    embed.color(EMBED_COLOR);
+    embed.timestamp(Timestamp::now());
+
+    if let Err(err) = channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await {
+        error!("Failed to post {} log to channel {}: {}", event.name(), channel_id, err);
+    }
+}
+
+fn timestamp_field(timestamp: Timestamp) -> String {
+    format!("<t:{0}:f> (<t:{0}:R>)", timestamp.unix_timestamp())
+}
+
+fn role_mentions(roles: &[RoleId]) -> String {
+    if roles.is_empty() {
+        "None".to_string()
+    } else {
+        truncate(&roles.iter().map(|role| format!("<@&{}>", role)).collect::<Vec<_>>().join(", "), 1000)
+    }
+}
+
+pub async fn on_join(ctx: &Context, member: &Member, invite: Option<&Attribution>) {
+    let mut embed = CreateEmbed::default();
+    embed.title("Member Joined");
+    embed.description(format!("<@{}>", member.user.id));
+    embed.thumbnail(member.user.face());
+    embed.field("Account Created", timestamp_field(member.user.created_at()), false);
+    embed.field("Invite", invite.map_or_else(|| "Unknown".to_string(), Attribution::describe), false);
+    if let Some(count) = member.guild_id.to_guild_cached(&ctx.cache).map(|guild| guild.member_count) {
+        embed.field("Member Count", count, true);
+    }
+    post(ctx, member.guild_id, MemberEvent::Joins, &member.user, embed).await;
+}
+
+/// Logs a member leaving; `member` is the cached copy, when the cache still had one.
+pub async fn on_leave(ctx: &Context, guild_id: GuildId, user: &User, member: Option<&Member>) {
+    let mut embed = CreateEmbed::default();
+    embed.title("Member Left");
+    embed.description(format!("<@{}>", user.id));
+    embed.thumbnail(user.face());
+    match member {
+        Some(member) => {
+            if let Some(joined_at) = member.joined_at {
+                embed.field("Joined", timestamp_field(joined_at), false);
+            }
+            embed.field("Roles", role_mentions(&member.roles), false);
+        }
+        None => {
+            embed.field("Roles", "Unknown (member wasn't cached)", false);
+        }
+    }
+    post(ctx, guild_id, MemberEvent::Leaves, user, embed).await;
+}
+
+pub async fn on_update(ctx: &Context, old: &Member, new: &Member) {
+    if old.nick != new.nick {
+        let mut embed = CreateEmbed::default();
+        embed.title("Nickname Changed");
+        embed.description(format!("<@{}>", new.user.id));
+        embed.field("Before", old.nick.as_deref().unwrap_or("*(none)*"), true);
+        embed.field("After", new.nick.as_deref().unwrap_or("*(none)*"), true);
+        post(ctx, new.guild_id, MemberEvent::Nicknames, &new.user, embed).await;
+    }
+
+    let added = new.roles.iter().filter(|role| !old.roles.contains(role)).copied().collect::<Vec<_>>();
+    let removed = old.roles.iter().filter(|role| !new.roles.contains(role)).copied().collect::<Vec<_>>();
+    if !added.is_empty() || !removed.is_empty() {
+        let mut embed = CreateEmbed::default();
+        embed.title("Roles Changed");
+        embed.description(format!("<@{}>", new.user.id));
+        if !added.is_empty() {
+            embed.field("Added", role_mentions(&added), false);
+        }
+        if !removed.is_empty() {
+            embed.field("Removed", role_mentions(&removed), false);
+        }
+        post(ctx, new.guild_id, MemberEvent::Roles, &new.user, embed).await;
+    }
+
+    let user_avatar_changed = old.user.avatar != new.user.avatar;
+    if user_avatar_changed || old.avatar != new.avatar {
+        let mut embed = CreateEmbed::default();
+        embed.title(if user_avatar_changed { "Avatar Changed" } else { "Server Avatar Changed" });
+        embed.description(format!("<@{}>", new.user.id));
+        embed.thumbnail(new.face());
+        post(ctx, new.guild_id, MemberEvent::Avatars, &new.user, embed).await;
+    }
+}
+
+pub async fn on_ban(ctx: &Context, guild_id: GuildId, user: &User) {
+    let mut embed = CreateEmbed::default();
+    embed.title("Member Banned");
+    embed.description(format!("<@{}>", user.id));
+    post(ctx, guild_id, MemberEvent::Bans, user, embed).await;
+}
+
+pub async fn on_unban(ctx: &Context, guild_id: GuildId, user: &User) {
+    let mut embed = CreateEmbed::default();
+    embed.title("Member Unbanned");
+    embed.description(format!("<@{}>", user.id));
+    post(ctx, guild_id, MemberEvent::Bans, user, embed).await;
+}
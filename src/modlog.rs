@@ -0,0 +1,46 @@
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::http::Http;
+use serenity::model::prelude::{ChannelId, GuildId, RoleId};
+use serenity::model::Timestamp;
+use serenity::prelude::TypeMapKey;
+
+use crate::config::EMBED_COLOR;
+use crate::settings::{self, Settings};
+
+/// Posts an entry to the guild's moderation log channel, if one is configured.
+pub async fn log(ctx: &Context, guild_id: GuildId, embed: CreateEmbed) {
+    alert(ctx, guild_id, None, embed).await;
+}
+
+/// Like [`log`], for background tasks that run without a [`Context`].
+pub async fn log_with(http: &Http, settings: &<Settings as TypeMapKey>::Value, guild_id: GuildId, embed: CreateEmbed) {
+    let channel_id = settings.read().await.get(&guild_id).and_then(|settings| settings.mod_log_channel);
+    if let Some(channel_id) = channel_id {
+        post(http, channel_id, None, embed).await;
+    }
+}
+
+/// Posts an entry to the moderation log, pinging `role` so moderators notice it.
+pub async fn alert(ctx: &Context, guild_id: GuildId, role: Option<RoleId>, embed: CreateEmbed) {
+    if let Some(channel_id) = settings::get(ctx, guild_id).await.mod_log_channel {
+        post(&ctx.http, channel_id, role, embed).await;
+    }
+}
+
+async fn post(http: &Http, channel_id: ChannelId, role: Option<RoleId>, mut embed: CreateEmbed) {
+    embed.color(EMBED_COLOR);
+    embed.timestamp(Timestamp::now());
+
+    let result = channel_id
+        .send_message(http, |m| {
+            if let Some(role) = role {
+                m.content(format!("<@&{}>", role)).allowed_mentions(|a| a.roles(vec![role]));
+            }
+            m.set_embed(embed)
+        })
+        .await;
+    if let Err(err) = result {
+        error!("Failed to post to mod log channel {}: {}", channel_id, err);
+    }
+}
@@ -0,0 +1,327 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::channel::{AttachmentType, ChannelType};
+use serenity::model::prelude::{ChannelId, GuildId, Message, RoleId, User, UserId};
+use serenity::model::{Permissions, Timestamp};
+use serenity::prelude::TypeMapKey;
+
+use crate::config::EMBED_COLOR;
+use crate::store::{self, Store};
+use crate::utils::{download_attachments, truncate, KeyedLocks};
+use crate::{cases, settings, transcript};
+
+/// Serialises thread creation per user so two quick DMs don't open two channels.
+static OPENING: LazyLock<KeyedLocks<UserId>> = LazyLock::new(KeyedLocks::default);
+
+/// Users who are being asked which server to contact. No lock is held while they choose.
+static CHOOSING: LazyLock<Mutex<HashSet<UserId>>> = LazyLock::new(Default::default);
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModmailSettings {
+    /// Category new modmail channels are created in; modmail is off while unset.
+    pub category: Option<ChannelId>,
+    /// Roles that can reply to and close threads, besides members with Manage Messages.
+    pub staff_roles: Vec<RoleId>,
+}
+
+impl ModmailSettings {
+    /// Whether a member with these roles and permissions can handle modmail.
+    pub fn is_staff(&self, roles: &[RoleId], permissions: Permissions) -> bool {
+        permissions.manage_messages() || roles.iter().any(|role| self.staff_roles.contains(role))
+    }
+}
+
+/// An open conversation between a user and a guild's staff.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Thread {
+    pub guild_id: GuildId,
+    pub channel_id: ChannelId,
+    pub opened_at: Timestamp,
+}
+
+/// Open threads, keyed by the user on the other end.
+pub struct ModmailThreads;
+
+impl TypeMapKey for ModmailThreads {
+    type Value = Arc<Store<HashMap<UserId, Thread>>>;
+}
+
+/// Returns the user whose thread lives in `channel_id`, if any.
+pub async fn thread_for_channel(ctx: &Context, channel_id: ChannelId) -> Option<(UserId, Thread)> {
+    let threads = store::get::<ModmailThreads>(ctx).await;
+    let threads = threads.read().await;
+    threads
+        .iter()
+        .find(|(_, thread)| thread.channel_id == channel_id)
+        .map(|(user_id, thread)| (*user_id, thread.clone()))
+}
+
+/// Guilds the user shares with the bot that have modmail set up.
+async fn modmail_guilds(ctx: &Context, user_id: UserId) -> Vec<(GuildId, String)> {
+    let mut guilds = Vec::new();
+    for guild_id in ctx.cache.guilds() {
+        if settings::get(ctx, guild_id).await.modmail.category.is_none() {
+            continue;
+        }
+        let is_member = ctx.cache.member(guild_id, user_id).is_some() || guild_id.member(&ctx.http, user_id).await.is_ok();
+        if is_member {
+            let name = guild_id.name(&ctx.cache).unwrap_or_else(|| guild_id.to_string());
+            guilds.push((guild_id, name));
+        }
+    }
+    guilds
+}
+
+/// Asks a user who shares several modmail guilds with the bot which one they want to reach.
+async fn choose_guild(ctx: &Context, msg: &Message, guilds: &[(GuildId, String)]) -> anyhow::Result<Option<GuildId>> {
+    let prompt = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.content("Which server's staff do you want to contact?").components(|c| {
+                c.create_action_row(|row| {
+                    for (guild_id, name) in guilds.iter().take(5) {
+                        row.create_button(|b| {
+                            b.custom_id(guild_id.to_string()).label(truncate(name, 80)).style(ButtonStyle::Primary)
+                        });
+                    }
+                    row
+                })
+            })
+        })
+        .await?;
+
+    let Some(interaction) = prompt
+        .await_component_interaction(&ctx.shard)
+        .author_id(msg.author.id)
+        .timeout(Duration::from_secs(60))
+        .await
+    else {
+        prompt.delete(&ctx.http).await?;
+        return Ok(None);
+    };
+
+    interaction
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| d.content("Opening a conversation...").components(|c| c))
+        })
+        .await?;
+    Ok(interaction.data.custom_id.parse::<u64>().ok().map(GuildId))
+}
+
+/// Creates the staff channel for a new thread and posts an overview of the user.
+async fn open(ctx: &Context, guild_id: GuildId, user: &User) -> anyhow::Result<Thread> {
+    let category = settings::get(ctx, guild_id).await.modmail.category.context("modmail is not configured")?;
+
+    let channel = guild_id
+        .create_channel(&ctx.http, |c| {
+            c.name(format!("modmail-{}", user.name))
+                .kind(ChannelType::Text)
+                .category(category)
+                .topic(format!("Modmail with {} ({})", user.tag(), user.id))
+        })
+        .await
+        .context("failed to create modmail channel")?;
+
+    let records = cases::for_user(ctx, guild_id, user.id).await;
+    let mut embed = CreateEmbed::default();
+    embed.title("New Modmail Thread");
+    embed.author(|a| a.name(user.tag()).icon_url(user.face()));
+    embed.field("User", format!("<@{}> ({})", user.id, user.id), false);
+    embed.field("Account Created", format!("<t:{}:R>", user.created_at().unix_timestamp()), true);
+    if let Ok(member) = guild_id.member(&ctx.http, user.id).await {
+        if let Some(joined_at) = member.joined_at {
+            embed.field("Joined", format!("<t:{}:R>", joined_at.unix_timestamp()), true);
+        }
+    }
+    embed.field("Past Cases", records.cases.len(), true);
+    embed.field("Commands", "`rreply <message>`, `rareply <message>` (anonymous), `rclose [reason]`", false);
+    embed.color(EMBED_COLOR);
+    channel.id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+
+    let thread = Thread { guild_id, channel_id: channel.id, opened_at: Timestamp::now() };
+    let threads = store::get::<ModmailThreads>(ctx).await;
+    threads.write(|all| all.insert(user.id, thread.clone())).await?;
+    info!(user_id = u64::from(user.id), "Opened modmail thread in {}", channel.id);
+
+    Ok(thread)
+}
+
+/// Forgets a thread whose channel was deleted by hand instead of with `rclose`.
+pub async fn on_channel_delete(ctx: &Context, channel_id: ChannelId) {
+    let Some((user_id, _)) = thread_for_channel(ctx, channel_id).await else { return };
+    let threads = store::get::<ModmailThreads>(ctx).await;
+    if let Err(err) = threads.write(|all| all.remove(&user_id)).await {
+        error!("Failed to remove modmail thread for {}: {:?}", user_id, err);
+    }
+}
+
+/// Relays a direct message from a user into their modmail thread, opening one if needed.
+/// Only called for messages that weren't commands, so `rhelp` in DMs doesn't reach staff.
+pub async fn on_direct_message(ctx: &Context, msg: &Message) {
+    if msg.guild_id.is_some() || msg.author.bot {
+        return;
+    }
+    if let Err(err) = relay_from_user(ctx, msg).await {
+        error!("Failed to relay modmail from {}: {:?}", msg.author.id, err);
+        let _ = msg.channel_id.say(&ctx.http, "Sorry, your message couldn't be delivered to staff.").await;
+    }
+}
+
+async fn relay_from_user(ctx: &Context, msg: &Message) -> anyhow::Result<()> {
+    let threads = store::get::<ModmailThreads>(ctx).await;
+    let existing = threads.read().await.get(&msg.author.id).cloned();
+
+    let thread = match existing {
+        Some(thread) => thread,
+        None => {
+            let guilds = modmail_guilds(ctx, msg.author.id).await;
+            let guild_id = match guilds.as_slice() {
+                [] => return Ok(()),
+                [(guild_id, _)] => *guild_id,
+                _ => {
+                    if !CHOOSING.lock().expect("modmail prompt set shouldn't be poisoned").insert(msg.author.id) {
+                        msg.channel_id
+                            .say(&ctx.http, "Choose a server above first, then send your message again.")
+                            .await?;
+                        return Ok(());
+                    }
+                    let choice = choose_guild(ctx, msg, &guilds).await;
+                    CHOOSING.lock().expect("modmail prompt set shouldn't be poisoned").remove(&msg.author.id);
+                    match choice? {
+                        Some(guild_id) => guild_id,
+                        None => return Ok(()),
+                    }
+                }
+            };
+
+            let _opening = OPENING.lock(msg.author.id).await;
+            if let Some(thread) = threads.read().await.get(&msg.author.id).cloned() {
+                thread
+            } else {
+                let thread = open(ctx, guild_id, &msg.author).await?;
+                let guild_name = guild_id.name(&ctx.cache).unwrap_or_else(|| "the server".to_string());
+                msg.channel_id
+                    .say(&ctx.http, format!("Your message has been sent to the staff of **{}**. They'll reply here.", guild_name))
+                    .await?;
+                thread
+            }
+        }
+    };
+
+    let (files, links) = download_attachments(&msg.attachments).await;
+    let mut embed = CreateEmbed::default();
+    embed.author(|a| a.name(msg.author.tag()).icon_url(msg.author.face()));
+    embed.description(if msg.content.is_empty() { "*(no text)*" } else { &msg.content });
+    if !links.is_empty() {
+        embed.field("Attachments", truncate(&links.join("\n"), 1000), false);
+    }
+    embed.footer(|f| f.text(format!("User ID: {}", msg.author.id)));
+    embed.color(EMBED_COLOR);
+    embed.timestamp(msg.timestamp);
+
+    thread.channel_id.send_message(&ctx.http, |m| m.set_embed(embed).add_files(files)).await?;
+    msg.react(&ctx.http, '✅').await?;
+
+    Ok(())
+}
+
+/// Sends a staff reply to the user and mirrors it in the thread channel.
+pub async fn reply(ctx: &Context, msg: &Message, user_id: UserId, text: &str, anonymous: bool) -> anyhow::Result<()> {
+    let guild_name = msg.guild_id.and_then(|guild_id| guild_id.name(&ctx.cache)).unwrap_or_else(|| "Staff".to_string());
+    let (files, links) = download_attachments(&msg.attachments).await;
+
+    let mut embed = CreateEmbed::default();
+    if anonymous {
+        embed.author(|a| a.name(format!("{} Staff", guild_name)));
+    } else {
+        embed.author(|a| a.name(format!("{} ({})", msg.author.tag(), guild_name)).icon_url(msg.author.face()));
+    }
+    embed.description(text);
+    if !links.is_empty() {
+        embed.field("Attachments", truncate(&links.join("\n"), 1000), false);
+    }
+    embed.color(EMBED_COLOR);
+    embed.timestamp(Timestamp::now());
+
+    let dm = user_id.create_dm_channel(&ctx.http).await.context("failed to open DM")?;
+    let sent = dm
+        .send_message(&ctx.http, |m| m.set_embed(embed.clone()).add_files(files))
+        .await
+        .context("failed to DM user")?;
+
+    // Keep a copy in the thread so the transcript shows who actually replied
+    embed.footer(|f| {
+        f.text(if anonymous {
+            format!("Sent anonymously by {}", msg.author.tag())
+        } else {
+            format!("Sent by {}", msg.author.tag())
+        })
+    });
+    let uploaded = sent.attachments.iter().map(|a| a.url.clone()).collect::<Vec<_>>();
+    if !uploaded.is_empty() {
+        embed.field("Files", truncate(&uploaded.join("\n"), 1000), false);
+    }
+    msg.channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+    let _ = msg.delete(&ctx.http).await;
+
+    Ok(())
+}
+
+/// Closes a thread: notifies the user, saves and posts the transcript, and deletes the channel.
+pub async fn close(ctx: &Context, msg: &Message, user_id: UserId, thread: &Thread, reason: &str) -> anyhow::Result<()> {
+    let transcript = transcript::render_text(&transcript::history(ctx, thread.channel_id).await?);
+    let header = format!(
+        "Modmail transcript for user {}\nOpened: {}\nClosed: {} by {}\nReason: {}\n\n",
+        user_id,
+        thread.opened_at,
+        Timestamp::now(),
+        msg.author.tag(),
+        reason
+    );
+    let transcript = header + &transcript;
+
+    let dir = store::data_dir().join("modmail");
+    tokio::fs::create_dir_all(&dir).await.with_context(|| format!("failed to create {}", dir.display()))?;
+    let filename = format!("{}-{}-{}.txt", thread.guild_id, user_id, Timestamp::now().unix_timestamp());
+    tokio::fs::write(dir.join(&filename), &transcript).await.context("failed to save modmail transcript")?;
+
+    let threads = store::get::<ModmailThreads>(ctx).await;
+    threads.write(|all| all.remove(&user_id)).await?;
+
+    let guild_name = thread.guild_id.name(&ctx.cache).unwrap_or_else(|| "the server".to_string());
+    if let Ok(dm) = user_id.create_dm_channel(&ctx.http).await {
+        let _ = dm
+            .say(&ctx.http, format!("Your conversation with the staff of **{}** has been closed.\nReason: {}", guild_name, reason))
+            .await;
+    }
+
+    if let Some(log_channel) = settings::get(ctx, thread.guild_id).await.mod_log_channel {
+        let mut embed = CreateEmbed::default();
+        embed.title("Modmail Closed");
+        embed.field("User", format!("<@{}> ({})", user_id, user_id), true);
+        embed.field("Closed By", msg.author.tag(), true);
+        embed.field("Reason", truncate(reason, 1000), false);
+        embed.color(EMBED_COLOR);
+        embed.timestamp(Timestamp::now());
+        let file = AttachmentType::Bytes { data: Cow::Owned(transcript.into_bytes()), filename };
+        if let Err(err) = log_channel.send_message(&ctx.http, |m| m.set_embed(embed).add_file(file)).await {
+            error!("Failed to post modmail transcript: {}", err);
+        }
+    }
+
+    thread.channel_id.delete(&ctx.http).await?;
+    info!(user_id = u64::from(user_id), "Closed modmail thread: {}", reason);
+
+    Ok(())
+}
@@ -0,0 +1,347 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::model::channel::AttachmentType;
+use serenity::model::event::MessageUpdateEvent;
+use serenity::model::prelude::{ChannelId, GuildId, Message, MessageId, User, UserId};
+use serenity::model::Timestamp;
+use serenity::prelude::TypeMapKey;
+use tokio::sync::Mutex;
+
+use crate::config::{EMBED_COLOR, MESSAGE_CACHE_BUDGET};
+use crate::settings;
+use crate::store;
+use crate::utils::truncate;
+
+/// Rough per-message bookkeeping cost on top of the text itself.
+const ENTRY_OVERHEAD: usize = 128;
+/// Upper bound on remembered purge IDs whose delete events haven't arrived yet.
+const MAX_SUPPRESSED: usize = 10_000;
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MessageLogSettings {
+    /// Channel that receives edit and deletion logs; logging is off while unset.
+    pub channel: Option<ChannelId>,
+    pub ignored_channels: Vec<ChannelId>,
+}
+
+/// The parts of a message needed to log it after Discord stops telling us about it.
+#[derive(Clone)]
+pub struct CachedMessage {
+    pub id: MessageId,
+    pub author_id: UserId,
+    pub author_tag: String,
+    pub content: String,
+    pub attachments: Vec<String>,
+}
+
+impl CachedMessage {
+    fn from_message(msg: &Message) -> Self {
+        Self {
+            id: msg.id,
+            author_id: msg.author.id,
+            author_tag: msg.author.tag(),
+            content: msg.content.clone(),
+            attachments: msg.attachments.iter().map(|a| a.url.clone()).collect(),
+        }
+    }
+
+    fn size(&self) -> usize {
+        ENTRY_OVERHEAD
+            + self.author_tag.len()
+            + self.content.len()
+            + self.attachments.iter().map(String::len).sum::<usize>()
+    }
+}
+
+/// Recently seen messages, evicting the oldest once [`MESSAGE_CACHE_BUDGET`] is exceeded.
+#[derive(Default)]
+pub struct MessageCache {
+    messages: HashMap<MessageId, CachedMessage>,
+    order: VecDeque<MessageId>,
+    bytes: usize,
+    /// Messages removed by `rdelete`, which logs them itself.
+    suppressed: HashSet<MessageId>,
+}
+
+impl MessageCache {
+    fn insert(&mut self, message: CachedMessage) {
+        self.remove(message.id);
+        self.bytes += message.size();
+        self.order.push_back(message.id);
+        self.messages.insert(message.id, message);
+
+        if self.order.len() > 2 * self.messages.len() + 1024 {
+            let messages = &self.messages;
+            self.order.retain(|id| messages.contains_key(id));
+        }
+        self.evict();
+    }
+
+    /// Drops the oldest messages until the cache fits in its budget.
+    fn evict(&mut self) {
+        while self.bytes > MESSAGE_CACHE_BUDGET {
+            let Some(oldest) = self.order.pop_front() else { break };
+            if let Some(evicted) = self.messages.remove(&oldest) {
+                self.bytes -= evicted.size();
+            }
+        }
+    }
+
+    fn remove(&mut self, id: MessageId) -> Option<CachedMessage> {
+        let message = self.messages.remove(&id)?;
+        self.bytes -= message.size();
+        // The stale ID left in `order` is skipped on eviction or compacted away later
+        Some(message)
+    }
+
+    /// Replaces a message's content in place, returning the previous content.
+    fn update_content(&mut self, id: MessageId, content: &str) -> Option<String> {
+        let message = self.messages.get_mut(&id)?;
+        let before = std::mem::replace(&mut message.content, content.to_string());
+        self.bytes = self.bytes - before.len() + content.len();
+        self.evict();
+        Some(before)
+    }
+}
+
+pub struct MessageLogCache;
+
+impl TypeMapKey for MessageLogCache {
+    type Value = Arc<Mutex<MessageCache>>;
+}
+
+/// Returns the log channel if logging is enabled and `channel_id` isn't ignored.
+async fn log_channel(ctx: &Context, guild_id: GuildId, channel_id: ChannelId) -> Option<ChannelId> {
+    let settings = settings::get(ctx, guild_id).await.message_log;
+    let log_channel = settings.channel?;
+    (log_channel != channel_id && !settings.ignored_channels.contains(&channel_id)).then_some(log_channel)
+}
+
+async fn post(ctx: &Context, log_channel: ChannelId, mut embed: CreateEmbed, transcript: Option<(String, String)>) {
+    embed.color(EMBED_COLOR);
+    embed.timestamp(Timestamp::now());
+
+    let result = log_channel
+        .send_message(&ctx.http, |m| {
+            if let Some((filename, text)) = transcript {
+                m.add_file(AttachmentType::Bytes { data: Cow::Owned(text.into_bytes()), filename });
+            }
+            m.set_embed(embed)
+        })
+        .await;
+    if let Err(err) = result {
+        error!("Failed to post to message log channel {}: {}", log_channel, err);
+    }
+}
+
+fn attachment_list(attachments: &[String]) -> String {
+    truncate(&attachments.join("\n"), 1000)
+}
+
+fn content_field(content: &str) -> String {
+    if content.is_empty() {
+        "*(no text)*".to_string()
+    } else {
+        truncate(content, 1000)
+    }
+}
+
+/// Remembers a new message so later edits and deletions can be logged.
+pub async fn record(ctx: &Context, msg: &Message) {
+    let Some(guild_id) = msg.guild_id else { return };
+    if msg.author.id == ctx.cache.current_user_id() || log_channel(ctx, guild_id, msg.channel_id).await.is_none() {
+        return;
+    }
+
+    let cache = store::get::<MessageLogCache>(ctx).await;
+    cache.lock().await.insert(CachedMessage::from_message(msg));
+}
+
+pub async fn on_update(ctx: &Context, event: &MessageUpdateEvent) {
+    let (Some(guild_id), Some(content)) = (event.guild_id, event.content.as_deref()) else {
+        return;
+    };
+    let Some(log_channel) = log_channel(ctx, guild_id, event.channel_id).await else {
+        return;
+    };
+
+    let cache = store::get::<MessageLogCache>(ctx).await;
+    let (before, message) = {
+        let mut cache = cache.lock().await;
+        let before = cache.update_content(event.id, content);
+        (before, cache.messages.get(&event.id).cloned())
+    };
+    // Embed unfurls also arrive as updates; only real content changes are logged
+    if before.as_deref() == Some(content) {
+        return;
+    }
+
+    let author = match (&message, &event.author) {
+        (Some(message), _) => format!("{} ({})", message.author_tag, message.author_id),
+        (None, Some(author)) if author.bot => return,
+        (None, Some(author)) => format!("{} ({})", author.tag(), author.id),
+        (None, None) => "Unknown".to_string(),
+    };
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Message Edited");
+    embed.description(format!(
+        "[Jump to message](https://discord.com/channels/{}/{}/{})",
+        guild_id, event.channel_id, event.id
+    ));
+    embed.field("Author", author, true);
+    embed.field("Channel", format!("<#{}>", event.channel_id), true);
+    embed.field("Before", before.as_deref().map_or_else(|| "*(not cached)*".to_string(), content_field), false);
+    embed.field("After", content_field(content), false);
+    post(ctx, log_channel, embed, None).await;
+}
+
+pub async fn on_delete(ctx: &Context, guild_id: Option<GuildId>, channel_id: ChannelId, message_id: MessageId) {
+    let Some(guild_id) = guild_id else { return };
+    let cache = store::get::<MessageLogCache>(ctx).await;
+    let message = {
+        let mut cache = cache.lock().await;
+        let message = cache.remove(message_id);
+        if cache.suppressed.remove(&message_id) {
+            return;
+        }
+        message
+    };
+    let Some(log_channel) = log_channel(ctx, guild_id, channel_id).await else {
+        return;
+    };
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Message Deleted");
+    embed.field("Channel", format!("<#{}>", channel_id), true);
+    match message {
+        Some(message) => {
+            embed.field("Author", format!("{} ({})", message.author_tag, message.author_id), true);
+            embed.field("Content", content_field(&message.content), false);
+            if !message.attachments.is_empty() {
+                embed.field("Attachments", attachment_list(&message.attachments), false);
+            }
+        }
+        None => {
+            embed.field("Content", "*(not cached)*", false);
+        }
+    }
+    embed.footer(|f| f.text(format!("Message ID: {}", message_id)));
+    post(ctx, log_channel, embed, None).await;
+}
+
+pub async fn on_bulk_delete(ctx: &Context, guild_id: Option<GuildId>, channel_id: ChannelId, ids: &[MessageId]) {
+    let Some(guild_id) = guild_id else { return };
+    let cache = store::get::<MessageLogCache>(ctx).await;
+    let messages = {
+        let mut cache = cache.lock().await;
+        let mut messages = Vec::new();
+        for id in ids {
+            let message = cache.remove(*id);
+            if !cache.suppressed.remove(id) {
+                messages.push((*id, message));
+            }
+        }
+        messages
+    };
+    if messages.is_empty() {
+        return;
+    }
+    let Some(log_channel) = log_channel(ctx, guild_id, channel_id).await else {
+        return;
+    };
+
+    let uncached = messages.iter().filter(|(_, message)| message.is_none()).count();
+    let mut cached = messages.into_iter().filter_map(|(_, message)| message).collect::<Vec<_>>();
+    cached.sort_by_key(|message| message.id);
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Messages Bulk Deleted");
+    embed.field("Channel", format!("<#{}>", channel_id), true);
+    embed.field("Messages", ids.len(), true);
+    if uncached > 0 {
+        embed.field("Not Cached", format!("{} messages weren't cached and are missing from the transcript", uncached), false);
+    }
+    post(ctx, log_channel, embed, Some(transcript(channel_id, &cached))).await;
+}
+
+/// Logs messages removed by `rdelete` as a single transcript, and keeps their
+/// individual deletion events out of the log.
+///
+/// Call [`suppress`] before deleting so the gateway events are recognised.
+pub async fn log_purge(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, messages: &[Message], moderator: &User) {
+    let Some(log_channel) = log_channel(ctx, guild_id, channel_id).await else {
+        return;
+    };
+
+    let mut cached = messages.iter().map(CachedMessage::from_message).collect::<Vec<_>>();
+    cached.sort_by_key(|message| message.id);
+
+    let mut embed = CreateEmbed::default();
+    embed.title("Messages Purged");
+    embed.field("Channel", format!("<#{}>", channel_id), true);
+    embed.field("Messages", messages.len(), true);
+    embed.field("Moderator", format!("{} ({})", moderator.tag(), moderator.id), true);
+    post(ctx, log_channel, embed, Some(transcript(channel_id, &cached))).await;
+}
+
+/// Marks messages about to be deleted by the bot so their delete events aren't logged twice.
+pub async fn suppress(ctx: &Context, ids: &[MessageId]) {
+    let cache = store::get::<MessageLogCache>(ctx).await;
+    let mut cache = cache.lock().await;
+    if cache.suppressed.len() + ids.len() > MAX_SUPPRESSED {
+        cache.suppressed.clear();
+    }
+    cache.suppressed.extend(ids.iter().copied());
+}
+
+/// Renders messages as a plain-text transcript, returning its filename and contents.
+fn transcript(channel_id: ChannelId, messages: &[CachedMessage]) -> (String, String) {
+    let mut text = String::new();
+    for message in messages {
+        let _ = writeln!(text, "[{}] {} ({}): {}", message.id.created_at(), message.author_tag, message.author_id, message.content);
+        for attachment in &message.attachments {
+            let _ = writeln!(text, "    attachment: {}", attachment);
+        }
+    }
+    if text.is_empty() {
+        text.push_str("No deleted messages were cached.\n");
+    }
+    (format!("deleted-{}-{}.txt", channel_id, Timestamp::now().unix_timestamp()), text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: u64, content: &str) -> CachedMessage {
+        CachedMessage {
+            id: MessageId(id),
+            author_id: UserId(1),
+            author_tag: "user#0001".to_string(),
+            content: content.to_string(),
+            attachments: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn update_content_edits_in_place() {
+        let mut cache = MessageCache::default();
+        cache.insert(message(1, "hello"));
+        cache.insert(message(2, "world"));
+        let bytes = cache.bytes;
+
+        assert_eq!(cache.update_content(MessageId(1), "hello there").as_deref(), Some("hello"));
+        assert_eq!(cache.bytes, bytes + " there".len());
+        assert_eq!(cache.order, [MessageId(1), MessageId(2)]);
+        assert_eq!(cache.messages[&MessageId(1)].content, "hello there");
+        assert_eq!(cache.update_content(MessageId(3), "missing"), None);
+    }
+}
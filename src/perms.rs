@@ -0,0 +1,464 @@
+use std::cmp::Reverse;
+use std::fmt::Write as _;
+
+use serenity::model::channel::{Channel, PermissionOverwrite, PermissionOverwriteType};
+use serenity::model::guild::{Guild, Member, Role};
+use serenity::model::prelude::RoleId;
+use serenity::model::{Permissions, Timestamp};
+
+use crate::commands;
+
+/// Permissions that let a role or member damage the server if misassigned.
+pub const DANGEROUS_PERMISSIONS: Permissions = Permissions::ADMINISTRATOR
+    .union(Permissions::MANAGE_GUILD)
+    .union(Permissions::MANAGE_ROLES)
+    .union(Permissions::MANAGE_CHANNELS)
+    .union(Permissions::MANAGE_WEBHOOKS)
+    .union(Permissions::BAN_MEMBERS)
+    .union(Permissions::KICK_MEMBERS)
+    .union(Permissions::MODERATE_MEMBERS)
+    .union(Permissions::MANAGE_MESSAGES)
+    .union(Permissions::MANAGE_THREADS)
+    .union(Permissions::MANAGE_NICKNAMES)
+    .union(Permissions::MANAGE_EMOJIS_AND_STICKERS)
+    .union(Permissions::MANAGE_EVENTS)
+    .union(Permissions::MENTION_EVERYONE);
+
+/// What the bot needs for any command: reading the channel and replying with embeds and files.
+pub const BASE_PERMISSIONS: Permissions = Permissions::VIEW_CHANNEL
+    .union(Permissions::SEND_MESSAGES)
+    .union(Permissions::SEND_MESSAGES_IN_THREADS)
+    .union(Permissions::EMBED_LINKS)
+    .union(Permissions::ATTACH_FILES)
+    .union(Permissions::READ_MESSAGE_HISTORY)
+    .union(Permissions::ADD_REACTIONS);
+
+/// Permissions the bot uses itself when running a command, by top-level command name.
+/// Commands that aren't listed only need [`BASE_PERMISSIONS`].
+const COMMAND_PERMISSIONS: &[(&str, Permissions)] = &[
+    ("suggest", Permissions::CREATE_PUBLIC_THREADS),
+    ("kick", Permissions::KICK_MEMBERS),
+    ("ban", Permissions::BAN_MEMBERS),
+    ("delete", Permissions::MANAGE_MESSAGES),
+    ("raidmode", Permissions::MANAGE_GUILD.union(Permissions::KICK_MEMBERS)),
+    ("lock", Permissions::MANAGE_ROLES),
+    ("unlock", Permissions::MANAGE_ROLES),
+    ("slowmode", Permissions::MANAGE_CHANNELS),
+    ("role", Permissions::MANAGE_ROLES),
+    ("reply", Permissions::MANAGE_MESSAGES),
+    ("areply", Permissions::MANAGE_MESSAGES),
+    ("close", Permissions::MANAGE_CHANNELS),
+    (
+        "ticket",
+        Permissions::MANAGE_CHANNELS
+            .union(Permissions::MANAGE_ROLES)
+            .union(Permissions::CREATE_PRIVATE_THREADS)
+            .union(Permissions::MANAGE_THREADS),
+    ),
+];
+
+/// Permissions used by features that act on events rather than commands, or that are optional.
+pub const FEATURE_PERMISSIONS: &[(&str, Permissions)] = &[
+    ("automod, word filter and link scanning", Permissions::MANAGE_MESSAGES.union(Permissions::MODERATE_MEMBERS)),
+    ("anti-raid", Permissions::KICK_MEMBERS.union(Permissions::MANAGE_GUILD)),
+    ("join screening", Permissions::MANAGE_ROLES.union(Permissions::KICK_MEMBERS)),
+    ("sticky roles", Permissions::MANAGE_ROLES),
+    ("invite tracking", Permissions::MANAGE_GUILD),
+    ("modmail", Permissions::MANAGE_CHANNELS),
+    ("pings from `rsay --ping` and `rschedule create --ping`", Permissions::MENTION_EVERYONE),
+];
+
+/// What the bot needs for a registered top-level command, including its subcommands.
+pub struct CommandRequirement {
+    pub name: &'static str,
+    pub permissions: Permissions,
+}
+
+/// Every registered top-level command and what the bot needs for it.
+pub fn command_requirements() -> Vec<CommandRequirement> {
+    commands::GROUPS
+        .iter()
+        .flat_map(|group| group.options.commands)
+        .map(|command| {
+            let name = command.options.names.first().copied().unwrap_or_default();
+            let permissions = COMMAND_PERMISSIONS
+                .iter()
+                .find(|(command_name, _)| *command_name == name)
+                .map_or(BASE_PERMISSIONS, |(_, permissions)| BASE_PERMISSIONS | *permissions);
+            CommandRequirement { name, permissions }
+        })
+        .collect()
+}
+
+/// The permissions for an invite that makes every registered command work, and for one that
+/// also covers the event-driven and optional features.
+pub fn invite_permissions() -> (Permissions, Permissions) {
+    let minimal = command_requirements()
+        .iter()
+        .fold(BASE_PERMISSIONS, |acc, requirement| acc | requirement.permissions);
+    let full = FEATURE_PERMISSIONS.iter().fold(minimal, |acc, (_, permissions)| acc | *permissions);
+    (minimal, full)
+}
+
+/// Reason given for permissions no role grants and no overwrite touches.
+pub const NOT_GRANTED: &str = "not granted by any role";
+
+/// Whether a single permission ends up granted, and the role or overwrite that decided it.
+pub struct Explanation {
+    pub permission: Permissions,
+    pub allowed: bool,
+    pub reason: String,
+}
+
+impl Explanation {
+    pub fn name(&self) -> &'static str {
+        self.permission.get_permission_names().first().copied().unwrap_or("Unknown")
+    }
+}
+
+/// Comma-separated names of every permission in `permissions`.
+pub fn names(permissions: Permissions) -> String {
+    if permissions.is_empty() {
+        return "None".to_string();
+    }
+    permissions.get_permission_names().join(", ")
+}
+
+/// Each known permission as its own single-bit set.
+fn each_permission() -> impl Iterator<Item = Permissions> {
+    (0..64).filter_map(|bit| Permissions::from_bits(1 << bit))
+}
+
+/// The role's name as shown in Discord, with a single leading `@`.
+fn role_name(role: &Role) -> String {
+    format!("@{}", role.name.trim_start_matches('@'))
+}
+
+fn role_names<'a>(guild: &Guild, roles: impl IntoIterator<Item = &'a RoleId>) -> String {
+    roles
+        .into_iter()
+        .map(|role_id| guild.roles.get(role_id).map_or_else(|| role_id.to_string(), role_name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The member's roles, highest first, including @everyone.
+fn member_roles<'a>(guild: &'a Guild, member: &Member) -> Vec<&'a Role> {
+    let mut roles = guild
+        .roles
+        .values()
+        .filter(|role| role.id.0 == guild.id.0 || member.roles.contains(&role.id))
+        .collect::<Vec<_>>();
+    roles.sort_by_key(|role| Reverse(role.position));
+    roles
+}
+
+/// Computes a member's effective permissions, optionally in a channel with the given
+/// overwrites, and explains which role or overwrite decided each one.
+///
+/// Follows Discord's order: the owner and Administrator get everything, then role
+/// permissions are combined, then the @everyone, role and member overwrites are applied in
+/// turn, with an allow beating a deny at the same level.
+pub fn explain(guild: &Guild, member: &Member, overwrites: Option<&[PermissionOverwrite]>) -> Vec<Explanation> {
+    let roles = member_roles(guild, member);
+    let administrator = roles.iter().find(|role| role.permissions.administrator());
+
+    let mut explanations = each_permission()
+        .map(|permission| {
+            if member.user.id == guild.owner_id {
+                return Explanation { permission, allowed: true, reason: "server owner".to_string() };
+            }
+            if let Some(role) = administrator {
+                return Explanation { permission, allowed: true, reason: format!("Administrator from {}", role_name(role)) };
+            }
+
+            let granting = roles.iter().find(|role| role.permissions.contains(permission));
+            let (mut allowed, mut reason) = match granting {
+                Some(role) => (true, format!("granted by {}", role_name(role))),
+                None => (false, NOT_GRANTED.to_string()),
+            };
+
+            let Some(overwrites) = overwrites else {
+                return Explanation { permission, allowed, reason };
+            };
+
+            let everyone = RoleId(guild.id.0);
+            if let Some(overwrite) = overwrites.iter().find(|o| o.kind == PermissionOverwriteType::Role(everyone)) {
+                if overwrite.deny.contains(permission) {
+                    (allowed, reason) = (false, "denied by the @everyone overwrite".to_string());
+                }
+                if overwrite.allow.contains(permission) {
+                    (allowed, reason) = (true, "allowed by the @everyone overwrite".to_string());
+                }
+            }
+
+            let role_overwrites = overwrites
+                .iter()
+                .filter_map(|o| match o.kind {
+                    PermissionOverwriteType::Role(role_id) if role_id != everyone && member.roles.contains(&role_id) => {
+                        Some((role_id, o))
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let denying = role_overwrites.iter().filter(|(_, o)| o.deny.contains(permission)).map(|(id, _)| *id).collect::<Vec<_>>();
+            let allowing = role_overwrites.iter().filter(|(_, o)| o.allow.contains(permission)).map(|(id, _)| *id).collect::<Vec<_>>();
+            if !denying.is_empty() {
+                (allowed, reason) = (false, format!("denied by the overwrite for {}", role_names(guild, &denying)));
+            }
+            if !allowing.is_empty() {
+                (allowed, reason) = (true, format!("allowed by the overwrite for {}", role_names(guild, &allowing)));
+            }
+
+            let member_overwrite = overwrites.iter().find(|o| o.kind == PermissionOverwriteType::Member(member.user.id));
+            if let Some(overwrite) = member_overwrite {
+                if overwrite.deny.contains(permission) {
+                    (allowed, reason) = (false, "denied by the member's own overwrite".to_string());
+                }
+                if overwrite.allow.contains(permission) {
+                    (allowed, reason) = (true, "allowed by the member's own overwrite".to_string());
+                }
+            }
+
+            Explanation { permission, allowed, reason }
+        })
+        .collect::<Vec<_>>();
+
+    // Without View Channel nothing else in a channel is usable, whatever the overwrites say
+    let can_view = explanations.iter().any(|e| e.permission == Permissions::VIEW_CHANNEL && e.allowed);
+    if overwrites.is_some() && !can_view {
+        for explanation in explanations.iter_mut().filter(|e| e.allowed && e.permission != Permissions::VIEW_CHANNEL) {
+            explanation.allowed = false;
+            explanation.reason = "the member can't view this channel".to_string();
+        }
+    }
+    explanations
+}
+
+/// Counts from a permission audit, shown alongside the exported report.
+pub struct AuditSummary {
+    pub roles: usize,
+    pub members: usize,
+    pub overwrites: usize,
+}
+
+/// Builds a plain-text report of roles and members holding dangerous permissions and
+/// channels whose @everyone overwrite grants any.
+pub fn audit(guild: &Guild) -> (String, AuditSummary) {
+    let mut report = String::new();
+    let _ = writeln!(report, "Permission audit for {} ({})", guild.name, guild.id);
+    let _ = writeln!(report, "Generated: {}", Timestamp::now());
+    let _ = writeln!(report, "Dangerous permissions checked: {}", names(DANGEROUS_PERMISSIONS));
+    let _ = writeln!(report, "Members checked: {} of {} (cached members only)\n", guild.members.len(), guild.member_count);
+
+    let mut roles = guild
+        .roles
+        .values()
+        .filter(|role| role.permissions.intersects(DANGEROUS_PERMISSIONS))
+        .collect::<Vec<_>>();
+    roles.sort_by_key(|role| Reverse(role.position));
+
+    let _ = writeln!(report, "== Roles with dangerous permissions ({}) ==", roles.len());
+    for role in &roles {
+        let holders = guild.members.values().filter(|member| member.roles.contains(&role.id)).count();
+        let name = if role.id.0 == guild.id.0 { "@everyone".to_string() } else { format!("@{}", role.name) };
+        let managed = if role.managed { ", managed by an integration" } else { "" };
+        let _ = writeln!(report, "{} ({}), {} members{}", name, role.id, holders, managed);
+        let _ = writeln!(report, "    {}", names(role.permissions & DANGEROUS_PERMISSIONS));
+    }
+
+    let mut members = guild
+        .members
+        .values()
+        .filter_map(|member| {
+            let granted = member_roles(guild, member)
+                .iter()
+                .fold(Permissions::empty(), |acc, role| acc | role.permissions);
+            let dangerous = if member.user.id == guild.owner_id || granted.administrator() {
+                DANGEROUS_PERMISSIONS
+            } else {
+                granted & DANGEROUS_PERMISSIONS
+            };
+            (!dangerous.is_empty()).then_some((member, dangerous))
+        })
+        .collect::<Vec<_>>();
+    members.sort_by_key(|(member, _)| member.user.tag().to_lowercase());
+
+    let _ = writeln!(report, "\n== Members holding dangerous permissions ({}) ==", members.len());
+    for (member, dangerous) in &members {
+        let mut tag = format!("{} ({})", member.user.tag(), member.user.id);
+        if member.user.id == guild.owner_id {
+            tag.push_str(" [owner]");
+        }
+        if member.user.bot {
+            tag.push_str(" [bot]");
+        }
+        let _ = writeln!(report, "{}", tag);
+        let _ = writeln!(report, "    {}", names(*dangerous));
+    }
+
+    let everyone = PermissionOverwriteType::Role(RoleId(guild.id.0));
+    let mut exposed = guild
+        .channels
+        .values()
+        .filter_map(|channel| {
+            let (id, name, overwrites) = match channel {
+                Channel::Guild(channel) => (channel.id, &channel.name, &channel.permission_overwrites),
+                Channel::Category(category) => (category.id, &category.name, &category.permission_overwrites),
+                _ => return None,
+            };
+            let allowed = overwrites.iter().find(|o| o.kind == everyone)?.allow;
+            allowed.intersects(DANGEROUS_PERMISSIONS).then_some((id, name, allowed))
+        })
+        .collect::<Vec<_>>();
+    exposed.sort_by_key(|(_, name, _)| name.to_lowercase());
+    let _ = writeln!(report, "\n== @everyone overwrites granting dangerous permissions ({}) ==", exposed.len());
+    for (channel_id, name, allowed) in &exposed {
+        let _ = writeln!(report, "#{} ({})", name, channel_id);
+        let _ = writeln!(report, "    {}", names(*allowed & DANGEROUS_PERMISSIONS));
+    }
+
+    let summary = AuditSummary { roles: roles.len(), members: members.len(), overwrites: exposed.len() };
+    (report, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use serenity::model::prelude::UserId;
+
+    use super::*;
+
+    const GUILD: u64 = 1;
+    const OWNER: u64 = 2;
+    const MODS: u64 = 3;
+    const MUTED: u64 = 4;
+    const ADMINS: u64 = 5;
+
+    fn role(id: u64, name: &str, position: i64, permissions: Permissions) -> serde_json::Value {
+        json!({
+            "id": id.to_string(),
+            "name": name,
+            "color": 0,
+            "hoist": false,
+            "managed": false,
+            "mentionable": false,
+            "position": position,
+            "permissions": permissions.bits().to_string(),
+        })
+    }
+
+    fn guild() -> Guild {
+        serde_json::from_value(json!({
+            "id": GUILD.to_string(),
+            "name": "Test",
+            "icon": null,
+            "owner_id": OWNER.to_string(),
+            "afk_timeout": 300,
+            "verification_level": 0,
+            "default_message_notifications": 0,
+            "explicit_content_filter": 0,
+            "mfa_level": 0,
+            "nsfw_level": 0,
+            "premium_tier": 0,
+            "preferred_locale": "en-US",
+            "features": [],
+            "emojis": [],
+            "stickers": [],
+            "roles": [
+                role(GUILD, "@everyone", 0, Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES),
+                role(MODS, "Mods", 2, Permissions::MANAGE_MESSAGES | Permissions::KICK_MEMBERS),
+                role(MUTED, "Muted", 1, Permissions::empty()),
+                role(ADMINS, "Admins", 3, Permissions::ADMINISTRATOR),
+            ],
+            "joined_at": "2024-01-01T00:00:00+00:00",
+            "large": false,
+            "member_count": 0,
+            "members": [],
+            "channels": [],
+            "threads": [],
+            "presences": [],
+            "voice_states": [],
+            "system_channel_flags": 0,
+        }))
+        .expect("test guild should deserialize")
+    }
+
+    fn member(id: u64, roles: &[u64]) -> Member {
+        serde_json::from_value(json!({
+            "guild_id": GUILD.to_string(),
+            "user": { "id": id.to_string(), "username": "someone", "discriminator": "0001", "avatar": null },
+            "roles": roles.iter().map(u64::to_string).collect::<Vec<_>>(),
+            "joined_at": "2024-01-01T00:00:00+00:00",
+            "deaf": false,
+            "mute": false,
+        }))
+        .expect("test member should deserialize")
+    }
+
+    fn overwrite(allow: Permissions, deny: Permissions, kind: PermissionOverwriteType) -> PermissionOverwrite {
+        PermissionOverwrite { allow, deny, kind }
+    }
+
+    fn lookup(explanations: &[Explanation], permission: Permissions) -> (bool, &str) {
+        let explanation = explanations.iter().find(|e| e.permission == permission).expect("every permission is explained");
+        (explanation.allowed, explanation.reason.as_str())
+    }
+
+    #[test]
+    fn command_permissions_name_registered_commands() {
+        let registered = command_requirements().iter().map(|requirement| requirement.name).collect::<Vec<_>>();
+        for (name, _) in COMMAND_PERMISSIONS {
+            assert!(registered.contains(name), "`{}` isn't a registered top-level command", name);
+        }
+    }
+
+    #[test]
+    fn role_permissions_name_the_granting_role() {
+        let explanations = explain(&guild(), &member(10, &[MODS]), None);
+        assert_eq!(lookup(&explanations, Permissions::KICK_MEMBERS), (true, "granted by @Mods"));
+        assert_eq!(lookup(&explanations, Permissions::SEND_MESSAGES), (true, "granted by @everyone"));
+        assert_eq!(lookup(&explanations, Permissions::BAN_MEMBERS), (false, NOT_GRANTED));
+    }
+
+    #[test]
+    fn owner_and_administrator_get_everything() {
+        let guild = guild();
+        let overwrites = [overwrite(Permissions::empty(), Permissions::VIEW_CHANNEL, PermissionOverwriteType::Role(RoleId(GUILD)))];
+
+        let owner = explain(&guild, &member(OWNER, &[]), Some(&overwrites));
+        assert_eq!(lookup(&owner, Permissions::BAN_MEMBERS), (true, "server owner"));
+        let admin = explain(&guild, &member(10, &[ADMINS]), Some(&overwrites));
+        assert_eq!(lookup(&admin, Permissions::VIEW_CHANNEL), (true, "Administrator from @Admins"));
+    }
+
+    #[test]
+    fn overwrites_apply_everyone_then_roles_then_member() {
+        let guild = guild();
+        let overwrites = [
+            overwrite(Permissions::empty(), Permissions::SEND_MESSAGES, PermissionOverwriteType::Role(RoleId(GUILD))),
+            overwrite(Permissions::SEND_MESSAGES, Permissions::empty(), PermissionOverwriteType::Role(RoleId(MODS))),
+            overwrite(Permissions::empty(), Permissions::SEND_MESSAGES, PermissionOverwriteType::Role(RoleId(MUTED))),
+            overwrite(Permissions::empty(), Permissions::MANAGE_MESSAGES, PermissionOverwriteType::Member(UserId(11))),
+        ];
+
+        let regular = explain(&guild, &member(10, &[]), Some(&overwrites));
+        assert_eq!(lookup(&regular, Permissions::SEND_MESSAGES), (false, "denied by the @everyone overwrite"));
+
+        // An allow beats a deny from another role's overwrite
+        let muted_mod = explain(&guild, &member(11, &[MODS, MUTED]), Some(&overwrites));
+        assert_eq!(lookup(&muted_mod, Permissions::SEND_MESSAGES), (true, "allowed by the overwrite for @Mods"));
+        assert_eq!(lookup(&muted_mod, Permissions::MANAGE_MESSAGES), (false, "denied by the member's own overwrite"));
+
+        let muted = explain(&guild, &member(12, &[MUTED]), Some(&overwrites));
+        assert_eq!(lookup(&muted, Permissions::SEND_MESSAGES), (false, "denied by the overwrite for @Muted"));
+    }
+
+    #[test]
+    fn hidden_channels_deny_everything_else() {
+        let overwrites = [overwrite(Permissions::empty(), Permissions::VIEW_CHANNEL, PermissionOverwriteType::Role(RoleId(GUILD)))];
+        let explanations = explain(&guild(), &member(10, &[MODS]), Some(&overwrites));
+        assert_eq!(lookup(&explanations, Permissions::VIEW_CHANNEL), (false, "denied by the @everyone overwrite"));
+        assert_eq!(lookup(&explanations, Permissions::KICK_MEMBERS), (false, "the member can't view this channel"));
+    }
+}
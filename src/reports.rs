@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::model::prelude::{ChannelId, GuildId, Message, MessageId, UserId};
+use serenity::model::Timestamp;
+use serenity::prelude::TypeMapKey;
+
+use crate::config::EMBED_COLOR;
+use crate::settings;
+use crate::store::{self, Store};
+use crate::utils::{download_attachments, truncate};
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportSettings {
+    /// Channel reports are posted and tracked in; `rreport` is disabled while unset.
+    pub channel: Option<ChannelId>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "low" => Some(Severity::Low),
+            "medium" | "med" => Some(Severity::Medium),
+            "high" => Some(Severity::High),
+            "critical" | "crit" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Low => "Low",
+            Severity::Medium => "Medium",
+            Severity::High => "High",
+            Severity::Critical => "Critical",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReportStatus {
+    Open,
+    Confirmed,
+    InProgress,
+    Resolved,
+    WontFix,
+    Duplicate,
+}
+
+impl ReportStatus {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().replace(['-', '_'], "").as_str() {
+            "open" => Some(ReportStatus::Open),
+            "confirmed" => Some(ReportStatus::Confirmed),
+            "inprogress" => Some(ReportStatus::InProgress),
+            "resolved" | "fixed" => Some(ReportStatus::Resolved),
+            "wontfix" => Some(ReportStatus::WontFix),
+            "duplicate" => Some(ReportStatus::Duplicate),
+            _ => None,
+        }
+    }
+
+    /// Whether the report still needs work.
+    pub fn is_open(self) -> bool {
+        matches!(self, ReportStatus::Open | ReportStatus::Confirmed | ReportStatus::InProgress)
+    }
+}
+
+impl fmt::Display for ReportStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ReportStatus::Open => "Open",
+            ReportStatus::Confirmed => "Confirmed",
+            ReportStatus::InProgress => "In Progress",
+            ReportStatus::Resolved => "Resolved",
+            ReportStatus::WontFix => "Won't Fix",
+            ReportStatus::Duplicate => "Duplicate",
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub id: u32,
+    pub reporter: UserId,
+    pub reporter_tag: String,
+    pub description: String,
+    pub severity: Severity,
+    pub labels: Vec<String>,
+    pub attachments: Vec<String>,
+    pub status: ReportStatus,
+    pub duplicate_of: Option<u32>,
+    /// The latest note left by staff when changing the report.
+    pub note: Option<String>,
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+    pub created_at: Timestamp,
+}
+
+impl Report {
+    pub fn embed(&self) -> CreateEmbed {
+        let mut embed = CreateEmbed::default();
+        embed.title(format!("Report #{}", self.id));
+        embed.description(&self.description);
+        embed.color(EMBED_COLOR);
+        embed.field("Status", self.status_text(), true);
+        embed.field("Severity", self.severity, true);
+        embed.field("Labels", if self.labels.is_empty() { "None".to_string() } else { self.labels.join(", ") }, true);
+        if !self.attachments.is_empty() {
+            embed.field("Attachments", truncate(&self.attachments.join("\n"), 1000), false);
+            if let Some(image) = self.attachments.iter().find(|url| is_image(url)) {
+                embed.image(image);
+            }
+        }
+        if let Some(note) = &self.note {
+            embed.field("Staff Note", truncate(note, 1000), false);
+        }
+        embed.footer(|f| f.text(format!("Reported by: {}", self.reporter_tag)));
+        embed.timestamp(self.created_at);
+        embed
+    }
+
+    pub fn status_text(&self) -> String {
+        match self.duplicate_of {
+            Some(original) if self.status == ReportStatus::Duplicate => format!("Duplicate of #{}", original),
+            _ => self.status.to_string(),
+        }
+    }
+
+    /// Case-insensitive match against the description, labels and reporter.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.description.to_lowercase().contains(&query)
+            || self.labels.iter().any(|label| label.contains(&query))
+            || self.reporter_tag.to_lowercase().contains(&query)
+    }
+}
+
+fn is_image(url: &str) -> bool {
+    let path = url.split('?').next().unwrap_or(url).to_lowercase();
+    [".png", ".jpg", ".jpeg", ".gif", ".webp"].iter().any(|ext| path.ends_with(ext))
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildReports {
+    next_id: u32,
+    pub reports: Vec<Report>,
+}
+
+pub struct Reports;
+
+impl TypeMapKey for Reports {
+    type Value = Arc<Store<HashMap<GuildId, GuildReports>>>;
+}
+
+/// Details of a new report as given to `rreport`.
+pub struct NewReport<'a> {
+    pub description: &'a str,
+    pub severity: Severity,
+    pub labels: Vec<String>,
+}
+
+/// Files a report from `msg`, copying its attachments into the report channel.
+/// Returns `None` if the guild has no report channel.
+pub async fn create(ctx: &Context, msg: &Message, new: NewReport<'_>) -> anyhow::Result<Option<Report>> {
+    let guild_id = msg.guild_id.context("reports only work in servers")?;
+    let Some(channel_id) = settings::get(ctx, guild_id).await.reports.channel else {
+        return Ok(None);
+    };
+
+    let (files, links) = download_attachments(&msg.attachments).await;
+
+    let store = store::get::<Reports>(ctx).await;
+    let id = store
+        .write(|all| {
+            let guild = all.entry(guild_id).or_default();
+            guild.next_id += 1;
+            guild.next_id
+        })
+        .await?;
+
+    let mut report = Report {
+        id,
+        reporter: msg.author.id,
+        reporter_tag: msg.author.tag(),
+        description: new.description.to_string(),
+        severity: new.severity,
+        labels: new.labels,
+        attachments: links,
+        status: ReportStatus::Open,
+        duplicate_of: None,
+        note: None,
+        channel_id,
+        message_id: MessageId(0),
+        created_at: Timestamp::now(),
+    };
+
+    // Uploading first means the report keeps working copies of files even if the original message is deleted
+    let posted = channel_id
+        .send_message(&ctx.http, |m| m.set_embed(report.embed()).add_files(files))
+        .await
+        .context("failed to post report")?;
+    report.message_id = posted.id;
+    report.attachments.extend(posted.attachments.iter().map(|a| a.url.clone()));
+    if !posted.attachments.is_empty() {
+        let edited = channel_id.edit_message(&ctx.http, posted.id, |m| m.set_embed(report.embed())).await;
+        if let Err(err) = edited {
+            warn!("Failed to add attachments to report #{}: {}", id, err);
+        }
+    }
+
+    store.write(|all| all.entry(guild_id).or_default().reports.push(report.clone())).await?;
+    info!("Report #{} filed by {}", id, msg.author.id);
+
+    Ok(Some(report))
+}
+
+/// Returns a guild's reports, oldest first.
+pub async fn list(ctx: &Context, guild_id: GuildId) -> Vec<Report> {
+    let store = store::get::<Reports>(ctx).await;
+    let all = store.read().await;
+    all.get(&guild_id).map(|guild| guild.reports.clone()).unwrap_or_default()
+}
+
+pub async fn get(ctx: &Context, guild_id: GuildId, id: u32) -> Option<Report> {
+    let store = store::get::<Reports>(ctx).await;
+    let all = store.read().await;
+    all.get(&guild_id)?.reports.iter().find(|report| report.id == id).cloned()
+}
+
+/// Applies a staff change to a report, refreshes its message and tells the reporter what changed,
+/// along with the note given with this change, if any.
+pub async fn update(
+    ctx: &Context,
+    guild_id: GuildId,
+    id: u32,
+    change: &str,
+    note: Option<&str>,
+    f: impl FnOnce(&mut Report),
+) -> anyhow::Result<Option<Report>> {
+    let store = store::get::<Reports>(ctx).await;
+    let updated = store
+        .write(|all| {
+            let report = all.get_mut(&guild_id)?.reports.iter_mut().find(|report| report.id == id)?;
+            f(report);
+            Some(report.clone())
+        })
+        .await?;
+    let Some(report) = updated else {
+        return Ok(None);
+    };
+
+    let edited = report.channel_id.edit_message(&ctx.http, report.message_id, |m| m.set_embed(report.embed())).await;
+    if let Err(err) = edited {
+        warn!("Failed to update message for report #{}: {}", id, err);
+    }
+
+    let guild_name = guild_id.name(&ctx.cache).unwrap_or_else(|| "the server".to_string());
+    let mut dm = format!("Your report #{} in **{}** was updated: {}.", id, guild_name, change);
+    if let Some(note) = note {
+        dm.push_str(&format!("\nNote: {}", note));
+    }
+    if let Ok(channel) = report.reporter.create_dm_channel(&ctx.http).await {
+        let _ = channel.say(&ctx.http, dm).await;
+    }
+
+    Ok(Some(report))
+}
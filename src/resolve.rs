@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde_json::Value;
+use serenity::client::Context;
+use serenity::http::request::RequestBuilder;
+use serenity::http::routing::RouteInfo;
+use serenity::http::Http;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::guild::{Guild, Member, Role};
+use serenity::model::prelude::{GuildId, Message, RoleId, UserId};
+use serenity::utils::{parse_role, parse_username};
+
+use crate::utils::{levenshtein, truncate};
+
+/// Largest edit distance accepted when nothing matches a name more directly.
+const MAX_FUZZY_DISTANCE: usize = 2;
+
+/// Members offered in a disambiguation prompt; Discord allows at most 25 options.
+const MAX_CHOICES: usize = 25;
+
+/// How long a disambiguation prompt waits for the author to choose.
+const CHOICE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Picks the single candidate from a list of matches, or explains why there isn't one.
+fn pick<T: Copy>(matches: Vec<(T, &str)>, kind: &str, input: &str) -> Option<Result<T, String>> {
+    match matches.len() {
+        0 => None,
+        1 => Some(Ok(matches[0].0)),
+        _ => {
+            let mut names = matches.iter().map(|(_, name)| format!("`{}`", name)).collect::<Vec<_>>();
+            names.truncate(10);
+            Some(Err(format!("Multiple {}s match `{}`: {}", kind, input, names.join(", "))))
+        }
+    }
+}
+
+/// Resolves a role from a mention, an ID, or its name, trying exact, prefix, substring and
+/// finally fuzzy matches until exactly one role is found.
+pub fn role(guild: &Guild, input: &str) -> Result<RoleId, String> {
+    let input = input.trim();
+    let id = parse_role(input).or_else(|| input.parse::<u64>().ok()).map(RoleId);
+    if let Some(id) = id.filter(|id| guild.roles.contains_key(id)) {
+        return Ok(id);
+    }
+
+    let query = input.trim_start_matches('@').to_lowercase();
+    let roles = guild.roles.values().filter(|role| role.id.0 != guild.id.0).collect::<Vec<_>>();
+    let matching = |predicate: &dyn Fn(&Role) -> bool| {
+        roles
+            .iter()
+            .filter(|role| predicate(role))
+            .map(|role| (role.id, role.name.as_str()))
+            .collect::<Vec<_>>()
+    };
+
+    let strategies: [&dyn Fn(&Role) -> bool; 3] = [
+        &|role| role.name.to_lowercase() == query,
+        &|role| role.name.to_lowercase().starts_with(&query),
+        &|role| role.name.to_lowercase().contains(&query),
+    ];
+    for strategy in strategies {
+        if let Some(result) = pick(matching(strategy), "role", input) {
+            return result;
+        }
+    }
+
+    let fuzzy = roles
+        .iter()
+        .map(|role| (levenshtein(&role.name.to_lowercase(), &query), role))
+        .filter(|(distance, _)| *distance <= MAX_FUZZY_DISTANCE)
+        .collect::<Vec<_>>();
+    let best = fuzzy.iter().map(|(distance, _)| *distance).min();
+    let closest = fuzzy
+        .into_iter()
+        .filter(|(distance, _)| Some(*distance) == best)
+        .map(|(_, role)| (role.id, role.name.as_str()))
+        .collect();
+    pick(closest, "role", input).unwrap_or_else(|| Err(format!("No role matches `{}`.", input)))
+}
+
+/// How closely a resolved member matched the argument.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchQuality {
+    /// A mention, an ID, a full tag or a complete name, or a member the author chose.
+    Exact,
+    /// A name prefix, substring or fuzzy match.
+    Partial,
+}
+
+/// The outcome of matching a member argument.
+#[derive(Debug, PartialEq, Eq)]
+pub enum MemberMatch {
+    Found(UserId, MatchQuality),
+    /// Several members match equally well, best candidates first.
+    Ambiguous(Vec<UserId>),
+    NotFound,
+}
+
+/// Matches a member from a mention, an ID, `name#discriminator`, a username, a global display
+/// name or a nickname, trying exact, prefix, substring and finally fuzzy matches, all
+/// case-insensitive. Global display names are looked up in `global_names`.
+///
+/// Mentions and IDs are accepted even if the user isn't among `members`, so that users who
+/// already left can still be looked up or banned.
+pub fn match_member<'a>(
+    members: impl IntoIterator<Item = &'a Member>,
+    global_names: &HashMap<UserId, String>,
+    input: &str,
+) -> MemberMatch {
+    let input = input.trim();
+    if let Some(id) = parse_username(input).or_else(|| input.parse::<u64>().ok()) {
+        return MemberMatch::Found(UserId(id), MatchQuality::Exact);
+    }
+
+    let query = input.trim_start_matches('@').to_lowercase();
+    let members = members.into_iter().collect::<Vec<_>>();
+    let names = |member: &Member| {
+        let mut names = vec![member.user.name.to_lowercase()];
+        if let Some(global_name) = global_names.get(&member.user.id) {
+            names.push(global_name.to_lowercase());
+        }
+        if let Some(nick) = &member.nick {
+            names.push(nick.to_lowercase());
+        }
+        names
+    };
+    let matching = |predicate: &dyn Fn(&str) -> bool| {
+        members
+            .iter()
+            .filter(|member| names(member).iter().any(|name| predicate(name)))
+            .map(|member| member.user.id)
+            .collect::<Vec<_>>()
+    };
+
+    if query.contains('#') {
+        let tagged = members
+            .iter()
+            .filter(|member| member.user.tag().to_lowercase() == query)
+            .map(|member| member.user.id)
+            .collect::<Vec<_>>();
+        if let Some(result) = pick_member(tagged, MatchQuality::Exact) {
+            return result;
+        }
+    }
+
+    type Strategy<'a> = &'a dyn Fn(&str) -> bool;
+    let strategies: [(Strategy, MatchQuality); 3] = [
+        (&|name| name == query, MatchQuality::Exact),
+        (&|name| name.starts_with(&query), MatchQuality::Partial),
+        (&|name| name.contains(&query), MatchQuality::Partial),
+    ];
+    for (strategy, quality) in strategies {
+        if let Some(result) = pick_member(matching(strategy), quality) {
+            return result;
+        }
+    }
+
+    let mut fuzzy = members
+        .iter()
+        .filter_map(|member| {
+            let distance = names(member).iter().map(|name| levenshtein(name, &query)).min()?;
+            (distance <= MAX_FUZZY_DISTANCE).then_some((distance, member.user.id))
+        })
+        .collect::<Vec<_>>();
+    fuzzy.sort_by_key(|(distance, _)| *distance);
+    let best = fuzzy.first().map(|(distance, _)| *distance);
+    let closest = fuzzy.into_iter().filter(|(distance, _)| Some(*distance) == best).map(|(_, id)| id).collect();
+    pick_member(closest, MatchQuality::Partial).unwrap_or(MemberMatch::NotFound)
+}
+
+fn pick_member(matches: Vec<UserId>, quality: MatchQuality) -> Option<MemberMatch> {
+    match matches.len() {
+        0 => None,
+        1 => Some(MemberMatch::Found(matches[0], quality)),
+        _ => Some(MemberMatch::Ambiguous(matches)),
+    }
+}
+
+/// Resolves a member argument for a command, asking the author to choose when several members
+/// match. Falls back to a server-side search when the member cache has no match.
+///
+/// Replies and returns `None` if nothing matches or the author doesn't choose in time.
+pub async fn member(ctx: &Context, msg: &Message, input: &str) -> serenity::Result<Option<UserId>> {
+    Ok(member_match(ctx, msg, input).await?.map(|(user_id, _)| user_id))
+}
+
+/// Like [`member`], but also reports how closely the member matched, so that destructive
+/// commands can insist on confirmation when the argument only partially matched.
+pub async fn member_match(
+    ctx: &Context,
+    msg: &Message,
+    input: &str,
+) -> serenity::Result<Option<(UserId, MatchQuality)>> {
+    let Some(guild_id) = msg.guild_id else {
+        let found = parse_username(input).or_else(|| input.trim().parse::<u64>().ok()).map(UserId);
+        if found.is_none() {
+            msg.reply(&ctx.http, "Invalid user provided.").await?;
+        }
+        return Ok(found.map(|user_id| (user_id, MatchQuality::Exact)));
+    };
+
+    let cached = guild_id
+        .to_guild_cached(&ctx.cache)
+        .map_or(MemberMatch::NotFound, |guild| match_member(guild.members.values(), &HashMap::new(), input));
+    let (result, members) = match cached {
+        MemberMatch::Found(_, MatchQuality::Exact) => (cached, Vec::new()),
+        cached => {
+            // Only searched members come with global display names, which may match more closely
+            let query = input.trim().trim_start_matches('@');
+            let query = query.split('#').next().unwrap_or(query);
+            let (searched, global_names) = search_members(&ctx.http, guild_id, query).await.unwrap_or_default();
+            match match_member(&searched, &global_names, input) {
+                exact @ MemberMatch::Found(_, MatchQuality::Exact) => (exact, searched),
+                _ if cached != MemberMatch::NotFound => (cached, Vec::new()),
+                result => (result, searched),
+            }
+        }
+    };
+
+    match result {
+        MemberMatch::Found(user_id, quality) => Ok(Some((user_id, quality))),
+        MemberMatch::NotFound => {
+            msg.reply(&ctx.http, format!("No member matches `{}`.", truncate(input, 100))).await?;
+            Ok(None)
+        }
+        MemberMatch::Ambiguous(candidates) => {
+            let describe = |user_id: UserId| {
+                let member = members
+                    .iter()
+                    .find(|member| member.user.id == user_id)
+                    .cloned()
+                    .or_else(|| ctx.cache.member(guild_id, user_id));
+                match member {
+                    Some(member) => (member.user.tag(), member.nick.unwrap_or_default()),
+                    None => (user_id.to_string(), String::new()),
+                }
+            };
+            let choices = candidates.into_iter().take(MAX_CHOICES).map(|id| (id, describe(id))).collect::<Vec<_>>();
+            let chosen = choose_member(ctx, msg, input, &choices).await?;
+            Ok(chosen.map(|user_id| (user_id, MatchQuality::Exact)))
+        }
+    }
+}
+
+/// Searches the guild's members by name, also returning their global display names, which
+/// are read from the raw payload since serenity 0.11's `User` has no field for them.
+async fn search_members(
+    http: &Http,
+    guild_id: GuildId,
+    query: &str,
+) -> serenity::Result<(Vec<Member>, HashMap<UserId, String>)> {
+    let route = RouteInfo::SearchGuildMembers { guild_id: guild_id.0, query, limit: Some(100) };
+    let payload = http.fire::<Vec<Value>>(RequestBuilder::new(route).build()).await?;
+
+    let mut members = Vec::new();
+    let mut global_names = HashMap::new();
+    for mut value in payload {
+        let global_name = value.pointer("/user/global_name").and_then(Value::as_str).map(str::to_string);
+        if let Some(map) = value.as_object_mut() {
+            map.insert("guild_id".to_string(), Value::from(guild_id.0.to_string()));
+        }
+        let member = serde_json::from_value::<Member>(value)?;
+        if let Some(global_name) = global_name {
+            global_names.insert(member.user.id, global_name);
+        }
+        members.push(member);
+    }
+    Ok((members, global_names))
+}
+
+/// Shows a select menu of matching members and waits for the author to pick one.
+async fn choose_member(
+    ctx: &Context,
+    msg: &Message,
+    input: &str,
+    choices: &[(UserId, (String, String))],
+) -> serenity::Result<Option<UserId>> {
+    let prompt = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.reference_message(msg)
+                .content(format!("Several members match `{}`. Which one did you mean?", truncate(input, 100)))
+                .components(|c| {
+                    c.create_action_row(|row| {
+                        row.create_select_menu(|menu| {
+                            menu.custom_id("resolve_member").placeholder("Choose a member").options(|options| {
+                                for (user_id, (tag, nick)) in choices {
+                                    options.create_option(|option| {
+                                        option.label(truncate(tag, 90)).value(user_id);
+                                        if !nick.is_empty() {
+                                            option.description(format!("Nickname: {}", truncate(nick, 80)));
+                                        }
+                                        option
+                                    });
+                                }
+                                options
+                            })
+                        })
+                    })
+                })
+        })
+        .await?;
+
+    let Some(interaction) = prompt
+        .await_component_interaction(&ctx.shard)
+        .author_id(msg.author.id)
+        .timeout(CHOICE_TIMEOUT)
+        .await
+    else {
+        prompt.delete(&ctx.http).await?;
+        msg.reply(&ctx.http, "No member was chosen.").await?;
+        return Ok(None);
+    };
+
+    let chosen = interaction.data.values.first().and_then(|value| value.parse::<u64>().ok()).map(UserId);
+    interaction
+        .create_interaction_response(&ctx.http, |r| r.kind(InteractionResponseType::DeferredUpdateMessage))
+        .await?;
+    prompt.delete(&ctx.http).await?;
+    Ok(chosen)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn member(id: u64, name: &str, nick: Option<&str>) -> Member {
+        serde_json::from_value(json!({
+            "guild_id": "1",
+            "user": { "id": id.to_string(), "username": name, "discriminator": "0001", "avatar": null },
+            "nick": nick,
+            "roles": [],
+            "joined_at": "2024-01-01T00:00:00+00:00",
+            "deaf": false,
+            "mute": false,
+        }))
+        .expect("test member should deserialize")
+    }
+
+    fn matched(input: &str) -> MemberMatch {
+        let global_names = HashMap::from([(UserId(13), "Bobby Tables".to_string())]);
+        match_member(&members(), &global_names, input)
+    }
+
+    fn members() -> Vec<Member> {
+        vec![
+            member(10, "Alice", None),
+            member(11, "alicia", Some("Ali")),
+            member(12, "Bob", Some("Robert")),
+            member(13, "bobby", None),
+        ]
+    }
+
+    #[test]
+    fn mentions_and_ids_match_exactly_even_for_non_members() {
+        assert_eq!(matched("<@99>"), MemberMatch::Found(UserId(99), MatchQuality::Exact));
+        assert_eq!(matched("<@!12>"), MemberMatch::Found(UserId(12), MatchQuality::Exact));
+        assert_eq!(matched(" 13 "), MemberMatch::Found(UserId(13), MatchQuality::Exact));
+    }
+
+    #[test]
+    fn names_tags_and_nicknames_match_exactly() {
+        assert_eq!(matched("alice"), MemberMatch::Found(UserId(10), MatchQuality::Exact));
+        assert_eq!(matched("@BOB"), MemberMatch::Found(UserId(12), MatchQuality::Exact));
+        assert_eq!(matched("robert"), MemberMatch::Found(UserId(12), MatchQuality::Exact));
+        assert_eq!(matched("Alicia#0001"), MemberMatch::Found(UserId(11), MatchQuality::Exact));
+        assert_eq!(matched("bobby tables"), MemberMatch::Found(UserId(13), MatchQuality::Exact));
+    }
+
+    #[test]
+    fn partial_matches_are_reported_as_partial() {
+        assert_eq!(matched("bobb"), MemberMatch::Found(UserId(13), MatchQuality::Partial));
+        assert_eq!(matched("bert"), MemberMatch::Found(UserId(12), MatchQuality::Partial));
+        assert_eq!(matched("tables"), MemberMatch::Found(UserId(13), MatchQuality::Partial));
+        assert_eq!(matched("alicee"), MemberMatch::Found(UserId(10), MatchQuality::Partial));
+    }
+
+    #[test]
+    fn ties_are_ambiguous_and_misses_are_not_found() {
+        assert_eq!(matched("ali"), MemberMatch::Found(UserId(11), MatchQuality::Exact));
+        assert_eq!(matched("alic"), MemberMatch::Ambiguous(vec![UserId(10), UserId(11)]));
+        assert_eq!(matched("zed"), MemberMatch::NotFound);
+    }
+}
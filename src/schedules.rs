@@ -0,0 +1,393 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serenity::builder::CreateEmbed;
+use serenity::model::prelude::{ChannelId, GuildId, UserId};
+use serenity::model::Timestamp;
+use serenity::prelude::TypeMapKey;
+use serenity::CacheAndHttp;
+use tracing::{error, warn};
+
+use crate::announcements::EmbedTemplates;
+use crate::modlog;
+use crate::settings::Settings;
+use crate::store::Store;
+use crate::utils::parse_duration;
+
+/// How often due schedules are checked for.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How late a run can be before it counts as missed rather than just due.
+const MISSED_AFTER: i64 = 5 * 60;
+
+/// Shortest gap allowed between two runs of a cron schedule, in seconds.
+const MIN_CRON_INTERVAL: i64 = 60;
+
+/// Upcoming runs checked against [`MIN_CRON_INTERVAL`].
+const CRON_RUNS_CHECKED: usize = 100;
+
+/// When a schedule runs.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum When {
+    /// A single run at a unix timestamp.
+    Once { at: i64 },
+    /// A recurring run, with the expression as written and evaluated in the given time zone.
+    Cron { expression: String, timezone: String },
+}
+
+impl When {
+    /// Parses `in 2h`/`2h`, `2026-11-01 18:00 [time zone]` or `cron <expression> [time zone]`.
+    pub fn parse(input: &str, now: i64) -> Result<Self, String> {
+        let input = input.trim();
+
+        if let Some(rest) = input.strip_prefix("cron ") {
+            let (expression, timezone) = split_timezone(rest)?;
+            let schedule = cron::Schedule::from_str(&normalize_cron(expression)?).map_err(|err| err.to_string())?;
+            let start = DateTime::<Utc>::from_timestamp(now, 0).ok_or("That time is out of range.")?;
+            let runs = schedule.after(&start).take(CRON_RUNS_CHECKED).map(|run| run.timestamp()).collect::<Vec<_>>();
+            if runs.windows(2).any(|pair| pair[1] - pair[0] < MIN_CRON_INTERVAL) {
+                return Err("Cron schedules can run at most once a minute.".to_string());
+            }
+            return Ok(When::Cron { expression: expression.to_string(), timezone: timezone.name().to_string() });
+        }
+
+        let relative = input.strip_prefix("in ").unwrap_or(input);
+        if let Some(duration) = parse_duration(relative) {
+            let at = now.saturating_add(i64::try_from(duration.as_secs()).unwrap_or(i64::MAX));
+            return Ok(When::Once { at });
+        }
+
+        let (datetime, timezone) = split_timezone(input)?;
+        let datetime = datetime.replace('T', " ");
+        let naive = ["%Y-%m-%d %H:%M", "%Y-%m-%d %H:%M:%S"]
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(&datetime, format).ok())
+            .ok_or_else(|| {
+                "Times must be a duration like `2h30m`, a date like `2026-11-01 18:00 Europe/London` \
+                 or `cron <expression> [time zone]`."
+                    .to_string()
+            })?;
+        let at = timezone
+            .from_local_datetime(&naive)
+            .earliest()
+            .ok_or("That time doesn't exist in that time zone.")?
+            .timestamp();
+        if at <= now {
+            return Err("That time is in the past.".to_string());
+        }
+        Ok(When::Once { at })
+    }
+
+    /// The first run strictly after `now`, or `None` if there are no more.
+    pub fn next_after(&self, now: i64) -> Option<i64> {
+        match self {
+            When::Once { at } => (*at > now).then_some(*at),
+            When::Cron { expression, timezone } => {
+                let schedule = cron::Schedule::from_str(&normalize_cron(expression).ok()?).ok()?;
+                let timezone = Tz::from_str(timezone).ok()?;
+                let now = DateTime::<Utc>::from_timestamp(now, 0)?.with_timezone(&timezone);
+                schedule.after(&now).next().map(|next| next.timestamp())
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            When::Once { at } => format!("once at <t:{}:F>", at),
+            When::Cron { expression, timezone } => format!("`{}` ({})", expression, timezone),
+        }
+    }
+}
+
+/// Splits an optional trailing time zone off, defaulting to UTC.
+fn split_timezone(input: &str) -> Result<(&str, Tz), String> {
+    let input = input.trim();
+    match input.rsplit_once(' ') {
+        Some((rest, last)) if (last.starts_with(|c: char| c.is_ascii_alphabetic()) && last.contains('/')) || last.eq_ignore_ascii_case("UTC") => {
+            let timezone = Tz::from_str(last).map_err(|_| format!("`{}` isn't a known time zone.", last))?;
+            Ok((rest.trim(), timezone))
+        }
+        _ => Ok((input, Tz::UTC)),
+    }
+}
+
+/// Converts numeric days of the week from standard cron, where Sunday is 0 or 7, to the cron
+/// parser's numbering, where Sunday is 1. Named days are left alone.
+fn convert_days_of_week(field: &str) -> String {
+    field
+        .split(',')
+        .map(|part| {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, step.parse::<usize>().ok()),
+                None => (part, Some(1)),
+            };
+            // As in standard cron, a single day with a step runs from that day to the end of the week
+            let bounds = match range.split_once('-') {
+                Some((start, end)) => start.parse::<u32>().ok().zip(end.parse::<u32>().ok()),
+                None => range.parse::<u32>().ok().map(|day| (day, if part.contains('/') { 7 } else { day })),
+            };
+            match (bounds, step) {
+                (Some((start, end)), Some(step)) if end <= 7 && step > 0 => {
+                    (start..=end).step_by(step).map(|day| (day % 7 + 1).to_string()).collect::<Vec<_>>().join(",")
+                }
+                _ => part.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Accepts standard five-field expressions as well as the six and seven-field forms with
+/// seconds and years, returning the form the cron parser expects.
+fn normalize_cron(expression: &str) -> Result<String, String> {
+    let fields = expression.split_whitespace().collect::<Vec<_>>();
+    let expression = match fields.len() {
+        5 => format!("0 {} {}", fields[..4].join(" "), convert_days_of_week(fields[4])),
+        6 | 7 => fields.join(" "),
+        _ => return Err("Cron expressions need five fields: minute, hour, day of month, month and day of week.".to_string()),
+    };
+    cron::Schedule::from_str(&expression).map_err(|err| format!("Invalid cron expression: {}", err))?;
+    Ok(expression)
+}
+
+/// What a schedule posts.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum Content {
+    Text(String),
+    /// The name of a saved embed template, looked up when the schedule runs.
+    Template(String),
+}
+
+/// What happens to runs that were due while the bot was offline.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedRuns {
+    /// Post once as soon as the bot is back, however many runs were missed.
+    CatchUp,
+    /// Drop missed runs and wait for the next one.
+    Skip,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    pub id: u32,
+    pub channel_id: ChannelId,
+    pub author: UserId,
+    pub when: When,
+    pub content: Content,
+    pub missed: MissedRuns,
+    /// Whether mentions in the message ping.
+    pub ping: bool,
+    pub paused: bool,
+    pub next_run: i64,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildSchedules {
+    pub next_id: u32,
+    pub schedules: Vec<Schedule>,
+}
+
+pub struct Schedules;
+
+impl TypeMapKey for Schedules {
+    type Value = Arc<Store<HashMap<GuildId, GuildSchedules>>>;
+}
+
+/// Posts a schedule's message, filling template placeholders like `rembed send` does.
+async fn post(
+    cache_and_http: &CacheAndHttp,
+    templates: &<EmbedTemplates as TypeMapKey>::Value,
+    guild_id: GuildId,
+    schedule: &Schedule,
+) -> anyhow::Result<()> {
+    let (text, embed) = match &schedule.content {
+        Content::Text(text) => (Some(text.as_str()), None),
+        Content::Template(name) => {
+            let spec = templates.read().await.get(&guild_id).and_then(|all| all.get(name)).cloned();
+            let Some(spec) = spec else {
+                anyhow::bail!("template `{}` no longer exists", name);
+            };
+            let server = match guild_id.name(&cache_and_http.cache) {
+                Some(name) => name,
+                None => guild_id.to_partial_guild(&cache_and_http.http).await?.name,
+            };
+            (None, Some(spec.fill(&format!("<@{}>", schedule.author), &server).build()))
+        }
+    };
+
+    schedule
+        .channel_id
+        .send_message(&cache_and_http.http, |m| {
+            if let Some(text) = text {
+                m.content(text);
+            }
+            if let Some(embed) = embed {
+                m.set_embed(embed);
+            }
+            if !schedule.ping {
+                m.allowed_mentions(|am| am.empty_parse());
+            }
+            m
+        })
+        .await?;
+    Ok(())
+}
+
+/// Posts due schedules and moves them on to their next run, applying each schedule's
+/// missed-run policy to runs that came due while the bot was offline.
+async fn run_due(
+    cache_and_http: &CacheAndHttp,
+    schedules: &<Schedules as TypeMapKey>::Value,
+    templates: &<EmbedTemplates as TypeMapKey>::Value,
+    settings: &<Settings as TypeMapKey>::Value,
+) {
+    let now = Timestamp::now().unix_timestamp();
+    let due = schedules
+        .read()
+        .await
+        .iter()
+        .flat_map(|(guild_id, guild)| {
+            guild
+                .schedules
+                .iter()
+                .filter(|schedule| !schedule.paused && schedule.next_run <= now)
+                .map(|schedule| (*guild_id, schedule.clone()))
+        })
+        .collect::<Vec<_>>();
+    if due.is_empty() {
+        return;
+    }
+
+    for (guild_id, schedule) in &due {
+        let missed = now - schedule.next_run > MISSED_AFTER;
+        if missed && schedule.missed == MissedRuns::Skip {
+            continue;
+        }
+        if let Err(err) = post(cache_and_http, templates, *guild_id, schedule).await {
+            warn!(guild_id = u64::from(*guild_id), id = schedule.id, "Failed to post scheduled message: {:#}", err);
+            let mut embed = CreateEmbed::default();
+            embed.title("Scheduled Message Failed");
+            embed.description(format!("Schedule #{} couldn't post in <#{}>: {:#}", schedule.id, schedule.channel_id, err));
+            modlog::log_with(&cache_and_http.http, settings, *guild_id, embed).await;
+        }
+    }
+
+    let result = schedules
+        .write(|all| {
+            for (guild_id, done) in &due {
+                let Some(guild) = all.get_mut(guild_id) else { continue };
+                guild.schedules.retain_mut(|schedule| {
+                    if schedule.id != done.id {
+                        return true;
+                    }
+                    match schedule.when.next_after(now) {
+                        Some(next_run) => {
+                            schedule.next_run = next_run;
+                            true
+                        }
+                        None => false,
+                    }
+                });
+            }
+        })
+        .await;
+    if let Err(err) = result {
+        error!("Failed to save schedules: {:#}", err);
+    }
+}
+
+/// Checks for due schedules until the bot shuts down. The first check happens immediately,
+/// which is when runs missed during downtime are caught up or skipped.
+pub async fn run(
+    cache_and_http: Arc<CacheAndHttp>,
+    schedules: <Schedules as TypeMapKey>::Value,
+    templates: <EmbedTemplates as TypeMapKey>::Value,
+    settings: <Settings as TypeMapKey>::Value,
+) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        run_due(&cache_and_http, &schedules, &templates, &settings).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Saturday 2024-01-06 12:00:00 UTC.
+    const SATURDAY_NOON: i64 = 1_704_542_400;
+
+    #[test]
+    fn sunday_is_zero_or_seven() {
+        assert_eq!(convert_days_of_week("0"), "1");
+        assert_eq!(convert_days_of_week("7"), "1");
+        assert_eq!(convert_days_of_week("6"), "7");
+        assert_eq!(convert_days_of_week("MON"), "MON");
+        assert_eq!(convert_days_of_week("*"), "*");
+    }
+
+    #[test]
+    fn ranges_lists_and_steps_are_converted() {
+        assert_eq!(convert_days_of_week("1-5"), "2,3,4,5,6");
+        assert_eq!(convert_days_of_week("0,3"), "1,4");
+        assert_eq!(convert_days_of_week("1-5/2"), "2,4,6");
+        assert_eq!(convert_days_of_week("5/1"), "6,7,1");
+        assert_eq!(convert_days_of_week("*/2"), "*/2");
+    }
+
+    #[test]
+    fn five_field_expressions_gain_seconds() {
+        assert_eq!(normalize_cron("30 9 * * 1-5").unwrap(), "0 30 9 * * 2,3,4,5,6");
+        assert_eq!(normalize_cron("0 0 12 1 1 * 2030").unwrap(), "0 0 12 1 1 * 2030");
+        assert_eq!(normalize_cron("0 12 * * 5/1").unwrap(), "0 0 12 * * 6,7,1");
+        assert!(normalize_cron("* * *").is_err());
+        assert!(normalize_cron("61 * * * *").is_err());
+    }
+
+    #[test]
+    fn cron_runs_on_standard_days() {
+        let sunday = When::parse("cron 0 9 * * 0", SATURDAY_NOON).unwrap();
+        assert_eq!(sunday.next_after(SATURDAY_NOON), Some(SATURDAY_NOON + 21 * 3600));
+        let also_sunday = When::parse("cron 0 9 * * 7", SATURDAY_NOON).unwrap();
+        assert_eq!(also_sunday.next_after(SATURDAY_NOON), sunday.next_after(SATURDAY_NOON));
+        let weekdays = When::parse("cron 0 9 * * 1-5", SATURDAY_NOON).unwrap();
+        assert_eq!(weekdays.next_after(SATURDAY_NOON), Some(SATURDAY_NOON + 45 * 3600));
+    }
+
+    #[test]
+    fn parse_accepts_durations_dates_and_time_zones() {
+        assert!(matches!(When::parse("in 2h", SATURDAY_NOON), Ok(When::Once { at }) if at == SATURDAY_NOON + 7200));
+        assert!(matches!(When::parse("90m", SATURDAY_NOON), Ok(When::Once { at }) if at == SATURDAY_NOON + 5400));
+        assert!(matches!(When::parse("2024-01-06 13:00", SATURDAY_NOON), Ok(When::Once { at }) if at == SATURDAY_NOON + 3600));
+        assert!(matches!(
+            When::parse("2024-01-06T14:00 Europe/Berlin", SATURDAY_NOON),
+            Ok(When::Once { at }) if at == SATURDAY_NOON + 3600
+        ));
+        assert!(matches!(
+            When::parse("cron 0 9 * * * America/New_York", SATURDAY_NOON),
+            Ok(When::Cron { ref timezone, .. }) if timezone == "America/New_York"
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_bad_times() {
+        assert_eq!(When::parse("2024-01-06 11:00", SATURDAY_NOON).err().as_deref(), Some("That time is in the past."));
+        assert!(When::parse("2024-01-07 09:00 Mars/Olympus", SATURDAY_NOON).is_err());
+        assert!(When::parse("tomorrow-ish", SATURDAY_NOON).is_err());
+        assert!(When::parse("cron 0 9 * *", SATURDAY_NOON).is_err());
+        assert!(When::parse("cron * * * * * *", SATURDAY_NOON).is_err());
+        assert!(When::parse("cron */30 * * * * *", SATURDAY_NOON).is_err());
+        assert!(When::parse("cron 0 * * * * *", SATURDAY_NOON).is_ok());
+        assert!(When::parse("cron * * * * *", SATURDAY_NOON).is_ok());
+    }
+}
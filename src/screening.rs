@@ -0,0 +1,209 @@
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::prelude::{ChannelId, GuildId, Member, RoleId, User, UserId};
+use serenity::model::Timestamp;
+
+use crate::cases::{self, CaseKind};
+use crate::config::EMBED_COLOR;
+use crate::filter::normalize;
+use crate::settings;
+
+/// Custom ID prefix of the approve/deny buttons on review messages.
+pub const BUTTON_PREFIX: &str = "screening:";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScreeningSettings {
+    pub enabled: bool,
+    /// Accounts younger than this are flagged. 0 disables the check.
+    pub min_account_age_hours: u64,
+    pub flag_default_avatar: bool,
+    /// Case-insensitive regexes matched against usernames.
+    pub name_patterns: Vec<String>,
+    /// Role given to flagged members until a moderator approves them.
+    pub quarantine_role: Option<RoleId>,
+    /// Channel review requests are posted in. Falls back to the mod log channel.
+    pub review_channel: Option<ChannelId>,
+}
+
+impl Default for ScreeningSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_account_age_hours: 72,
+            flag_default_avatar: true,
+            name_patterns: Vec::new(),
+            quarantine_role: None,
+            review_channel: None,
+        }
+    }
+}
+
+/// Returns why a user looks like a throwaway or alt account, if they do.
+pub fn screen(user: &User, settings: &ScreeningSettings, now: Timestamp) -> Vec<String> {
+    let mut reasons = Vec::new();
+
+    let age_hours = (now.unix_timestamp() - user.created_at().unix_timestamp()) / 3600;
+    if settings.min_account_age_hours > 0 && age_hours < settings.min_account_age_hours as i64 {
+        reasons.push(format!("Account is only {} hours old", age_hours.max(0)));
+    }
+
+    if settings.flag_default_avatar && user.avatar.is_none() {
+        reasons.push("Uses the default avatar".to_string());
+    }
+
+    let normalized_name = normalize(&user.name);
+    for pattern in &settings.name_patterns {
+        let Ok(regex) = RegexBuilder::new(pattern).case_insensitive(true).build() else {
+            continue;
+        };
+        if regex.is_match(&user.name) || regex.is_match(&normalized_name) {
+            reasons.push(format!("Name matches `{}`", pattern));
+        }
+    }
+
+    reasons
+}
+
+/// Screens a newly joined member, quarantining them and asking moderators to review if flagged.
+pub async fn on_member_join(ctx: &Context, member: &Member) {
+    let guild_id = member.guild_id;
+    let settings = settings::get(ctx, guild_id).await;
+    let screening = &settings.screening;
+    if !screening.enabled || member.user.bot {
+        return;
+    }
+
+    let reasons = screen(&member.user, screening, Timestamp::now());
+    if reasons.is_empty() {
+        return;
+    }
+    info!(user_id = u64::from(member.user.id), "Join screening flagged member: {}", reasons.join("; "));
+
+    let mut quarantined = false;
+    if let Some(role) = screening.quarantine_role {
+        match ctx
+            .http
+            .add_member_role(guild_id.0, member.user.id.0, role.0, Some("Flagged by join screening"))
+            .await
+        {
+            Ok(()) => quarantined = true,
+            Err(err) => error!("Failed to quarantine {}: {}", member.user.id, err),
+        }
+    }
+
+    let Some(channel_id) = screening.review_channel.or(settings.mod_log_channel) else {
+        return;
+    };
+
+    let user = &member.user;
+    let mut embed = CreateEmbed::default();
+    embed.title("Join Screening");
+    embed.thumbnail(user.face());
+    embed.field("User", format!("{} ({})", user.tag(), user.id), false);
+    embed.field("Account Created", format!("<t:{}:R>", user.created_at().unix_timestamp()), true);
+    embed.field("Quarantined", if quarantined { "Yes" } else { "No" }, true);
+    embed.field("Flags", reasons.join("\n"), false);
+    embed.color(EMBED_COLOR);
+    embed.timestamp(Timestamp::now());
+
+    let result = channel_id
+        .send_message(&ctx.http, |m| {
+            m.set_embed(embed).components(|c| {
+                c.create_action_row(|row| {
+                    row.create_button(|b| {
+                        b.custom_id(format!("{}approve:{}", BUTTON_PREFIX, user.id))
+                            .label("Approve")
+                            .style(ButtonStyle::Success)
+                    });
+                    row.create_button(|b| {
+                        b.custom_id(format!("{}deny:{}", BUTTON_PREFIX, user.id))
+                            .label("Deny (Kick)")
+                            .style(ButtonStyle::Danger)
+                    })
+                })
+            })
+        })
+        .await;
+    if let Err(err) = result {
+        error!("Failed to post join screening review: {}", err);
+    }
+}
+
+/// Handles a moderator pressing Approve or Deny on a review message.
+pub async fn handle_button(ctx: &Context, interaction: &MessageComponentInteraction) {
+    let Some(guild_id) = interaction.guild_id else { return };
+    let Some((decision, user_id)) = interaction.data.custom_id[BUTTON_PREFIX.len()..].split_once(':') else {
+        return;
+    };
+    let Ok(user_id) = user_id.parse::<u64>().map(UserId) else { return };
+
+    let allowed = interaction
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.kick_members());
+    if !allowed {
+        respond_ephemeral(ctx, interaction, "You need the Kick Members permission to review joins.").await;
+        return;
+    }
+
+    let outcome = match decision {
+        "approve" => approve(ctx, guild_id, user_id).await,
+        "deny" => guild_id
+            .kick_with_reason(&ctx.http, user_id, "Denied by join screening")
+            .await
+            .map_err(|err| err.to_string()),
+        _ => return,
+    };
+
+    if let Err(err) = outcome {
+        respond_ephemeral(ctx, interaction, &format!("Failed to {} member: {}", decision, err)).await;
+        return;
+    }
+    if decision == "deny" {
+        cases::add_case(ctx, guild_id, CaseKind::Kick, user_id, Some(interaction.user.id), "Denied by join screening").await;
+    }
+
+    let verdict = if decision == "approve" { "Approved" } else { "Denied and kicked" };
+    let mut embed = interaction.message.embeds.first().cloned().map(CreateEmbed::from).unwrap_or_default();
+    embed.field("Decision", format!("{} by {}", verdict, interaction.user.tag()), false);
+
+    let result = interaction
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| d.set_embed(embed).components(|c| c))
+        })
+        .await;
+    if let Err(err) = result {
+        error!("Failed to update join screening review: {}", err);
+    }
+    info!(user_id = u64::from(user_id), "Join screening: {} by {}", verdict, interaction.user.id);
+}
+
+async fn approve(ctx: &Context, guild_id: GuildId, user_id: UserId) -> Result<(), String> {
+    let Some(role) = settings::get(ctx, guild_id).await.screening.quarantine_role else {
+        return Ok(());
+    };
+    ctx.http
+        .remove_member_role(guild_id.0, user_id.0, role.0, Some("Approved by join screening"))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+async fn respond_ephemeral(ctx: &Context, interaction: &MessageComponentInteraction, content: &str) {
+    let result = interaction
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.content(content).ephemeral(true))
+        })
+        .await;
+    if let Err(err) = result {
+        error!("Failed to respond to interaction: {}", err);
+    }
+}
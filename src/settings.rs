@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serenity::client::Context;
+use serenity::model::prelude::{ChannelId, GuildId, RoleId};
+use serenity::prelude::TypeMapKey;
+
+use crate::antiraid::AntiRaidSettings;
+use crate::automod::AutomodSettings;
+use crate::filter::FilterSettings;
+use crate::linkscan::LinkScanSettings;
+use crate::lockdown::LockdownSettings;
+use crate::memberlog::MemberLogSettings;
+use crate::modmail::ModmailSettings;
+use crate::msglog::MessageLogSettings;
+use crate::reports::ReportSettings;
+use crate::screening::ScreeningSettings;
+use crate::stickyroles::StickyRoleSettings;
+use crate::suggestions::SuggestionSettings;
+use crate::tickets::TicketSettings;
+use crate::store::{self, Store};
+
+/// Per-guild configuration, managed through the `config` command.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildSettings {
+    /// Members holding any of these roles skip confirmation prompts.
+    pub trusted_roles: Vec<RoleId>,
+    /// Channel that receives automated moderation reports.
+    pub mod_log_channel: Option<ChannelId>,
+    /// Channels where private moderation records (`rnotes`, `rdossier`) may be shown.
+    pub staff_channels: Vec<ChannelId>,
+    pub automod: AutomodSettings,
+    /// Word and pattern rules checked against every message.
+    pub filter: FilterSettings,
+    pub linkscan: LinkScanSettings,
+    pub antiraid: AntiRaidSettings,
+    pub lockdown: LockdownSettings,
+    pub screening: ScreeningSettings,
+    pub message_log: MessageLogSettings,
+    pub member_log: MemberLogSettings,
+    pub sticky_roles: StickyRoleSettings,
+    pub modmail: ModmailSettings,
+    pub suggestions: SuggestionSettings,
+    pub reports: ReportSettings,
+    pub tickets: TicketSettings,
+    /// Channel new bot versions are announced in, if the owner turned announcements on.
+    pub changelog_channel: Option<ChannelId>,
+}
+
+pub struct Settings;
+
+impl TypeMapKey for Settings {
+    type Value = Arc<Store<HashMap<GuildId, GuildSettings>>>;
+}
+
+/// Returns a snapshot of a guild's settings.
+pub async fn get(ctx: &Context, guild_id: GuildId) -> GuildSettings {
+    let settings = store::get::<Settings>(ctx).await;
+    let all = settings.read().await;
+    all.get(&guild_id).cloned().unwrap_or_default()
+}
+
+/// Modifies a guild's settings and persists them.
+pub async fn update<R>(ctx: &Context, guild_id: GuildId, f: impl FnOnce(&mut GuildSettings) -> R) -> Result<R> {
+    let settings = store::get::<Settings>(ctx).await;
+    settings.write(|all| f(all.entry(guild_id).or_default())).await
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::model::prelude::{Guild, GuildId, Member, RoleId, UserId};
+use serenity::model::Timestamp;
+use serenity::prelude::TypeMapKey;
+
+use crate::config::PUNISHMENT_ROLE_NAMES;
+use crate::store::{self, Store};
+use crate::{modlog, settings};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StickyRoleSettings {
+    pub enabled: bool,
+    /// Roles restored on rejoin. When empty, punishment roles are restored instead.
+    pub roles: Vec<RoleId>,
+    /// Snapshots older than this are ignored and pruned.
+    pub retention_days: u64,
+}
+
+impl Default for StickyRoleSettings {
+    fn default() -> Self {
+        Self { enabled: true, roles: Vec::new(), retention_days: 30 }
+    }
+}
+
+/// The sticky roles a member holds, kept up to date as their roles change so they're still
+/// known when the member leaves and isn't cached.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub roles: Vec<RoleId>,
+    /// When the member left, or `None` while they're still in the server.
+    #[serde(default)]
+    pub left_at: Option<Timestamp>,
+}
+
+pub struct StickyRoles;
+
+impl TypeMapKey for StickyRoles {
+    type Value = Arc<Store<HashMap<GuildId, HashMap<UserId, Snapshot>>>>;
+}
+
+/// Whether a role is restored on rejoin under the given settings.
+pub fn is_sticky(guild: &Guild, settings: &settings::GuildSettings, role_id: RoleId) -> bool {
+    if !settings.sticky_roles.roles.is_empty() {
+        return settings.sticky_roles.roles.contains(&role_id);
+    }
+    if settings.screening.quarantine_role == Some(role_id) {
+        return true;
+    }
+    guild.roles.get(&role_id).is_some_and(|role| {
+        let name = role.name.to_lowercase();
+        PUNISHMENT_ROLE_NAMES.iter().any(|pattern| name.contains(pattern))
+    })
+}
+
+fn expired(snapshot: &Snapshot, retention_days: u64, now: i64) -> bool {
+    snapshot
+        .left_at
+        .is_some_and(|left_at| now - left_at.unix_timestamp() > (retention_days * 24 * 60 * 60) as i64)
+}
+
+/// Persists a member's sticky roles whenever their roles change.
+pub async fn on_member_update(ctx: &Context, member: &Member) {
+    let guild_id = member.guild_id;
+    let settings = settings::get(ctx, guild_id).await;
+    if !settings.sticky_roles.enabled {
+        return;
+    }
+    let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else { return };
+
+    let mut roles = member.roles.iter().copied().filter(|role| is_sticky(&guild, &settings, *role)).collect::<Vec<_>>();
+    roles.sort();
+    let store = store::get::<StickyRoles>(ctx).await;
+    let saved = store.read().await.get(&guild_id).and_then(|snapshots| snapshots.get(&member.user.id)).map(|s| s.roles.clone());
+    if saved.as_ref() == Some(&roles) || (saved.is_none() && roles.is_empty()) {
+        return;
+    }
+
+    let result = store
+        .write(|all| {
+            let snapshots = all.entry(guild_id).or_default();
+            if roles.is_empty() {
+                snapshots.remove(&member.user.id);
+            } else {
+                snapshots.insert(member.user.id, Snapshot { roles, left_at: None });
+            }
+        })
+        .await;
+    if let Err(err) = result {
+        error!("Failed to save sticky roles for {}: {:?}", member.user.id, err);
+    }
+}
+
+/// Marks a departed member's sticky roles for restoring. `member` is the cached copy, if any,
+/// which is used in case a role change was missed.
+pub async fn on_member_leave(ctx: &Context, guild_id: GuildId, user_id: UserId, member: Option<&Member>) {
+    let settings = settings::get(ctx, guild_id).await;
+    if !settings.sticky_roles.enabled {
+        return;
+    }
+
+    let cached_roles = match (member, guild_id.to_guild_cached(&ctx.cache)) {
+        (Some(member), Some(guild)) => {
+            Some(member.roles.iter().copied().filter(|role| is_sticky(&guild, &settings, *role)).collect::<Vec<_>>())
+        }
+        _ => None,
+    };
+
+    let now = Timestamp::now();
+    let retention_days = settings.sticky_roles.retention_days;
+    let store = store::get::<StickyRoles>(ctx).await;
+    let result = store
+        .write(|all| {
+            let snapshots = all.entry(guild_id).or_default();
+            snapshots.retain(|_, snapshot| !expired(snapshot, retention_days, now.unix_timestamp()));
+            let roles = match cached_roles {
+                Some(roles) => roles,
+                None => snapshots.get(&user_id).map(|snapshot| snapshot.roles.clone()).unwrap_or_default(),
+            };
+            if roles.is_empty() {
+                snapshots.remove(&user_id);
+            } else {
+                snapshots.insert(user_id, Snapshot { roles, left_at: Some(now) });
+            }
+        })
+        .await;
+    if let Err(err) = result {
+        error!("Failed to save sticky roles for {}: {:?}", user_id, err);
+    }
+}
+
+/// Gives a rejoining member back their sticky roles that are still configured as sticky.
+pub async fn on_member_join(ctx: &Context, member: &Member) {
+    let guild_id = member.guild_id;
+    let store = store::get::<StickyRoles>(ctx).await;
+    if !store.read().await.get(&guild_id).is_some_and(|snapshots| snapshots.contains_key(&member.user.id)) {
+        return;
+    }
+    let snapshot = match store.write(|all| all.get_mut(&guild_id).and_then(|s| s.remove(&member.user.id))).await {
+        Ok(Some(snapshot)) => snapshot,
+        Ok(None) => return,
+        Err(err) => {
+            error!("Failed to load sticky roles for {}: {:?}", member.user.id, err);
+            return;
+        }
+    };
+
+    let settings = settings::get(ctx, guild_id).await;
+    let sticky = &settings.sticky_roles;
+    if !sticky.enabled || expired(&snapshot, sticky.retention_days, Timestamp::now().unix_timestamp()) {
+        return;
+    }
+    let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else { return };
+
+    let mut restored = Vec::new();
+    for role_id in snapshot.roles.into_iter().filter(|role| is_sticky(&guild, &settings, *role)) {
+        let result = ctx
+            .http
+            .add_member_role(guild_id.0, member.user.id.0, role_id.0, Some("Sticky role restored on rejoin"))
+            .await;
+        match result {
+            Ok(()) => restored.push(role_id),
+            Err(err) => error!("Failed to restore sticky role {} for {}: {}", role_id, member.user.id, err),
+        }
+    }
+    if restored.is_empty() {
+        return;
+    }
+
+    info!(user_id = u64::from(member.user.id), "Restored {} sticky roles", restored.len());
+    let mut embed = CreateEmbed::default();
+    embed.title("Sticky Roles Restored");
+    embed.field("User", format!("{} ({})", member.user.tag(), member.user.id), false);
+    embed.field(
+        "Roles",
+        restored.iter().map(|role| format!("<@&{}>", role)).collect::<Vec<_>>().join(", "),
+        false,
+    );
+    modlog::log(ctx, guild_id, embed).await;
+}
@@ -0,0 +1,82 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context as _, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serenity::client::Context;
+use serenity::prelude::TypeMapKey;
+use tokio::sync::{RwLock, RwLockReadGuard};
+
+const DEFAULT_DATA_DIR: &str = "data";
+
+/// Directory holding persisted bot data, configurable through `DATA_DIR`.
+pub fn data_dir() -> PathBuf {
+    PathBuf::from(env::var("DATA_DIR").unwrap_or_else(|_| DEFAULT_DATA_DIR.to_string()))
+}
+
+/// A value persisted as a JSON file inside the data directory.
+///
+/// Every call to [`Store::write`] rewrites the whole file, so stores should stay small.
+pub struct Store<T> {
+    path: PathBuf,
+    data: RwLock<T>,
+}
+
+impl<T> Store<T>
+where
+    T: Serialize + DeserializeOwned + Default + Clone,
+{
+    /// Loads `<DATA_DIR>/<name>.json`, starting from the default value if it doesn't exist yet.
+    pub fn open(name: &str) -> Result<Self> {
+        let dir = data_dir();
+        fs::create_dir_all(&dir).with_context(|| format!("failed to create data directory {}", dir.display()))?;
+
+        let path = dir.join(format!("{name}.json"));
+        let data = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .with_context(|| format!("failed to parse {}", path.display()))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => T::default(),
+            Err(err) => return Err(err).with_context(|| format!("failed to read {}", path.display())),
+        };
+
+        Ok(Self { path, data: RwLock::new(data) })
+    }
+
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.data.read().await
+    }
+
+    /// Applies `f` to a copy of the stored value and swaps it in once it has been persisted, so
+    /// a failed write leaves both the file and the in-memory value untouched.
+    pub async fn write<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R> {
+        let mut data = self.data.write().await;
+        let mut updated = data.clone();
+        let result = f(&mut updated);
+
+        let contents = serde_json::to_string_pretty(&updated).context("failed to serialize store")?;
+        let tmp_path = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, contents)
+            .await
+            .with_context(|| format!("failed to write {}", tmp_path.display()))?;
+        tokio::fs::rename(&tmp_path, &self.path)
+            .await
+            .with_context(|| format!("failed to replace {}", self.path.display()))?;
+
+        *data = updated;
+        Ok(result)
+    }
+}
+
+/// Fetches a shared value registered in the client's type map.
+pub async fn get<K>(ctx: &Context) -> K::Value
+where
+    K: TypeMapKey,
+    K::Value: Clone,
+{
+    let data = ctx.data.read().await;
+    data.get::<K>()
+        .cloned()
+        .expect("store should be registered when the client is built")
+}
@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use serenity::builder::{CreateComponents, CreateEmbed};
+use serenity::client::Context;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::{InteractionResponseType, MessageFlags};
+use serenity::model::prelude::{ChannelId, GuildId, Message, MessageId, UserId};
+use serenity::model::Timestamp;
+use serenity::prelude::TypeMapKey;
+
+use crate::config::EMBED_COLOR;
+use crate::settings;
+use crate::store::{self, Store};
+use crate::utils::truncate;
+
+/// Custom ID prefix of the vote buttons on suggestion messages.
+pub const BUTTON_PREFIX: &str = "suggestion:";
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SuggestionSettings {
+    /// Channel suggestions are posted in; `rsuggest` is disabled while unset.
+    pub channel: Option<ChannelId>,
+    /// Whether each suggestion gets its own discussion thread.
+    pub threads: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Status {
+    Pending,
+    Approved,
+    Denied,
+    Considered,
+    Implemented,
+}
+
+impl Status {
+    /// Whether members can still vote on the suggestion.
+    fn open(self) -> bool {
+        matches!(self, Status::Pending | Status::Considered)
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Status::Pending => "Pending",
+            Status::Approved => "Approved",
+            Status::Denied => "Denied",
+            Status::Considered => "Under Consideration",
+            Status::Implemented => "Implemented",
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub id: u32,
+    pub author: UserId,
+    pub author_tag: String,
+    pub content: String,
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+    pub status: Status,
+    pub upvotes: Vec<UserId>,
+    pub downvotes: Vec<UserId>,
+    /// The staff member who last changed the status, and why.
+    pub reviewer: Option<UserId>,
+    pub reason: Option<String>,
+    pub created_at: Timestamp,
+}
+
+impl Suggestion {
+    fn embed(&self) -> CreateEmbed {
+        let mut embed = CreateEmbed::default();
+        embed.title(format!("Suggestion #{}", self.id));
+        embed.description(&self.content);
+        embed.color(EMBED_COLOR);
+        embed.field("Status", self.status, true);
+        embed.field("Votes", format!("👍 {}  👎 {}", self.upvotes.len(), self.downvotes.len()), true);
+        if let Some(reviewer) = self.reviewer {
+            let reason = self.reason.as_deref().unwrap_or("No reason given");
+            embed.field("Staff Response", format!("<@{}>: {}", reviewer, truncate(reason, 1000)), false);
+        }
+        embed.footer(|f| f.text(format!("Suggested by: {}", self.author_tag)));
+        embed.timestamp(self.created_at);
+        embed
+    }
+
+    fn buttons(&self) -> CreateComponents {
+        let mut components = CreateComponents::default();
+        if self.status.open() {
+            components.create_action_row(|row| {
+                row.create_button(|b| {
+                    b.custom_id(format!("{}up:{}", BUTTON_PREFIX, self.id))
+                        .emoji('👍')
+                        .label(self.upvotes.len())
+                        .style(ButtonStyle::Success)
+                })
+                .create_button(|b| {
+                    b.custom_id(format!("{}down:{}", BUTTON_PREFIX, self.id))
+                        .emoji('👎')
+                        .label(self.downvotes.len())
+                        .style(ButtonStyle::Danger)
+                })
+            });
+        }
+        components
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildSuggestions {
+    next_id: u32,
+    suggestions: Vec<Suggestion>,
+}
+
+pub struct Suggestions;
+
+impl TypeMapKey for Suggestions {
+    type Value = Arc<Store<HashMap<GuildId, GuildSuggestions>>>;
+}
+
+/// Posts a new suggestion to the guild's suggestion channel and returns it,
+/// or `None` if the guild hasn't configured one.
+pub async fn create(ctx: &Context, msg: &Message, content: &str) -> anyhow::Result<Option<Suggestion>> {
+    let guild_id = msg.guild_id.context("suggestions only work in servers")?;
+    let config = settings::get(ctx, guild_id).await.suggestions;
+    let Some(channel_id) = config.channel else {
+        return Ok(None);
+    };
+
+    let store = store::get::<Suggestions>(ctx).await;
+    let id = store
+        .write(|all| {
+            let guild = all.entry(guild_id).or_default();
+            guild.next_id += 1;
+            guild.next_id
+        })
+        .await?;
+
+    let mut suggestion = Suggestion {
+        id,
+        author: msg.author.id,
+        author_tag: msg.author.tag(),
+        content: content.to_string(),
+        channel_id,
+        message_id: MessageId(0),
+        status: Status::Pending,
+        upvotes: Vec::new(),
+        downvotes: Vec::new(),
+        reviewer: None,
+        reason: None,
+        created_at: Timestamp::now(),
+    };
+    let posted = channel_id
+        .send_message(&ctx.http, |m| m.set_embed(suggestion.embed()).set_components(suggestion.buttons()))
+        .await
+        .context("failed to post suggestion")?;
+    suggestion.message_id = posted.id;
+
+    if config.threads {
+        let name = format!("Suggestion #{}: {}", id, truncate(content, 60));
+        if let Err(err) = channel_id.create_public_thread(&ctx.http, posted.id, |t| t.name(name)).await {
+            warn!("Failed to create thread for suggestion #{}: {}", id, err);
+        }
+    }
+
+    store
+        .write(|all| all.entry(guild_id).or_default().suggestions.push(suggestion.clone()))
+        .await?;
+    info!("Suggestion #{} created by {}", id, msg.author.id);
+
+    Ok(Some(suggestion))
+}
+
+/// Applies `f` to a stored suggestion and returns the updated copy.
+async fn modify(
+    ctx: &Context,
+    guild_id: GuildId,
+    id: u32,
+    f: impl FnOnce(&mut Suggestion),
+) -> anyhow::Result<Option<Suggestion>> {
+    let store = store::get::<Suggestions>(ctx).await;
+    store
+        .write(|all| {
+            let suggestion = all.get_mut(&guild_id)?.suggestions.iter_mut().find(|s| s.id == id)?;
+            f(suggestion);
+            Some(suggestion.clone())
+        })
+        .await
+}
+
+/// Changes a suggestion's status, refreshes its message and DMs the author.
+pub async fn review(
+    ctx: &Context,
+    guild_id: GuildId,
+    id: u32,
+    status: Status,
+    reviewer: UserId,
+    reason: Option<&str>,
+) -> anyhow::Result<Option<Suggestion>> {
+    let updated = modify(ctx, guild_id, id, |suggestion| {
+        suggestion.status = status;
+        suggestion.reviewer = Some(reviewer);
+        suggestion.reason = reason.map(str::to_string);
+    })
+    .await?;
+    let Some(suggestion) = updated else {
+        return Ok(None);
+    };
+
+    let edited = suggestion
+        .channel_id
+        .edit_message(&ctx.http, suggestion.message_id, |m| {
+            m.set_embed(suggestion.embed()).set_components(suggestion.buttons())
+        })
+        .await;
+    if let Err(err) = edited {
+        warn!("Failed to update message for suggestion #{}: {}", id, err);
+    }
+
+    let guild_name = guild_id.name(&ctx.cache).unwrap_or_else(|| "the server".to_string());
+    let mut dm = format!("Your suggestion #{} in **{}** is now **{}**.", id, guild_name, status);
+    if let Some(reason) = reason {
+        dm.push_str(&format!("\nReason: {}", reason));
+    }
+    if let Ok(channel) = suggestion.author.create_dm_channel(&ctx.http).await {
+        let _ = channel.say(&ctx.http, dm).await;
+    }
+
+    Ok(Some(suggestion))
+}
+
+/// Handles a vote button, toggling the member's vote and refreshing the tally.
+pub async fn handle_button(ctx: &Context, interaction: &MessageComponentInteraction) {
+    let Some(guild_id) = interaction.guild_id else { return };
+    let Some((direction, id)) = interaction.data.custom_id[BUTTON_PREFIX.len()..].split_once(':') else {
+        return;
+    };
+    let Ok(id) = id.parse::<u32>() else { return };
+    let upvote = direction == "up";
+    let voter = interaction.user.id;
+
+    let result = modify(ctx, guild_id, id, |suggestion| {
+        if !suggestion.status.open() {
+            return;
+        }
+        let (chosen, other) = if upvote {
+            (&mut suggestion.upvotes, &mut suggestion.downvotes)
+        } else {
+            (&mut suggestion.downvotes, &mut suggestion.upvotes)
+        };
+        other.retain(|user| *user != voter);
+        if chosen.contains(&voter) {
+            chosen.retain(|user| *user != voter);
+        } else {
+            chosen.push(voter);
+        }
+    })
+    .await;
+
+    let response = match result {
+        Ok(Some(suggestion)) => {
+            interaction
+                .create_interaction_response(&ctx.http, |r| {
+                    r.kind(InteractionResponseType::UpdateMessage)
+                        .interaction_response_data(|d| d.set_embed(suggestion.embed()).set_components(suggestion.buttons()))
+                })
+                .await
+        }
+        Ok(None) => {
+            interaction
+                .create_interaction_response(&ctx.http, |r| {
+                    r.kind(InteractionResponseType::ChannelMessageWithSource)
+                        .interaction_response_data(|d| d.content("This suggestion no longer exists.").flags(MessageFlags::EPHEMERAL))
+                })
+                .await
+        }
+        Err(err) => {
+            error!("Failed to record vote on suggestion #{}: {:?}", id, err);
+            return;
+        }
+    };
+    if let Err(err) = response {
+        error!("Failed to respond to suggestion vote: {}", err);
+    }
+}
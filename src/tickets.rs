@@ -0,0 +1,510 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+
+use anyhow::{bail, Context as _};
+use serde::{Deserialize, Serialize};
+use serenity::builder::{CreateComponents, CreateEmbed};
+use serenity::client::Context;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::channel::{AttachmentType, ChannelType, PermissionOverwrite, PermissionOverwriteType};
+use serenity::model::prelude::{ChannelId, GuildId, RoleId, User, UserId};
+use serenity::model::{Permissions, Timestamp};
+use serenity::prelude::TypeMapKey;
+
+use crate::config::EMBED_COLOR;
+use crate::store::{self, Store};
+use crate::utils::{truncate, KeyedLocks};
+use crate::{settings, transcript};
+
+/// Custom ID prefix of the panel and ticket buttons.
+pub const BUTTON_PREFIX: &str = "ticket:";
+
+/// Most categories a panel can offer, since a message holds at most 25 buttons.
+pub const MAX_CATEGORIES: usize = 25;
+
+/// Longest category description, so that a panel with every category still fits in one embed.
+pub const MAX_DESCRIPTION: usize = 150;
+
+/// What participants of a private ticket channel are allowed to do in it.
+const PARTICIPANT_PERMISSIONS: Permissions = Permissions::VIEW_CHANNEL
+    .union(Permissions::SEND_MESSAGES)
+    .union(Permissions::READ_MESSAGE_HISTORY)
+    .union(Permissions::ATTACH_FILES)
+    .union(Permissions::EMBED_LINKS);
+
+/// Serialises ticket creation per member so double-clicking a panel button can't dodge the open
+/// ticket limit.
+static OPENING: LazyLock<KeyedLocks<(GuildId, UserId)>> = LazyLock::new(KeyedLocks::default);
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TicketCategory {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TicketSettings {
+    /// One "Open ticket" button is shown per category on the panel.
+    pub categories: Vec<TicketCategory>,
+    /// Roles that can see, claim and close every ticket.
+    pub support_roles: Vec<RoleId>,
+    /// Channel transcripts are uploaded to; falls back to the mod log.
+    pub log_channel: Option<ChannelId>,
+    /// Open tickets as private threads of the panel channel instead of private channels.
+    pub threads: bool,
+    /// Category new ticket channels are created in.
+    pub parent: Option<ChannelId>,
+    /// How many tickets one member may have open at once.
+    pub max_open: u32,
+}
+
+impl Default for TicketSettings {
+    fn default() -> Self {
+        TicketSettings {
+            categories: Vec::new(),
+            support_roles: Vec::new(),
+            log_channel: None,
+            threads: false,
+            parent: None,
+            max_open: 1,
+        }
+    }
+}
+
+impl TicketSettings {
+    pub fn category(&self, name: &str) -> Option<&TicketCategory> {
+        self.categories.iter().find(|category| category.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether a member with these roles and permissions counts as support staff.
+    pub fn is_staff(&self, roles: &[RoleId], permissions: Permissions) -> bool {
+        permissions.manage_channels() || roles.iter().any(|role| self.support_roles.contains(role))
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Ticket {
+    pub id: u32,
+    pub opener: UserId,
+    pub category: String,
+    pub channel_id: ChannelId,
+    pub thread: bool,
+    pub claimed_by: Option<UserId>,
+    /// Members added to the ticket besides the opener and support staff.
+    pub participants: Vec<UserId>,
+    pub opened_at: Timestamp,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildTickets {
+    next_id: u32,
+    open: Vec<Ticket>,
+}
+
+pub struct Tickets;
+
+impl TypeMapKey for Tickets {
+    type Value = Arc<Store<HashMap<GuildId, GuildTickets>>>;
+}
+
+/// The panel message's buttons, one per category. Callers check there are at most
+/// [`MAX_CATEGORIES`].
+pub fn panel_buttons(settings: &TicketSettings) -> CreateComponents {
+    let mut components = CreateComponents::default();
+    for chunk in settings.categories.chunks(5) {
+        components.create_action_row(|row| {
+            for category in chunk {
+                row.create_button(|b| {
+                    b.custom_id(format!("{}open:{}", BUTTON_PREFIX, category.name))
+                        .label(format!("Open ticket: {}", truncate(&category.name, 60)))
+                        .style(ButtonStyle::Primary)
+                });
+            }
+            row
+        });
+    }
+    components
+}
+
+fn ticket_buttons(ticket: &Ticket) -> CreateComponents {
+    let mut components = CreateComponents::default();
+    components.create_action_row(|row| {
+        row.create_button(|b| {
+            b.custom_id(format!("{}claim:{}", BUTTON_PREFIX, ticket.id)).label("Claim / Unclaim").style(ButtonStyle::Secondary)
+        })
+        .create_button(|b| {
+            b.custom_id(format!("{}close:{}", BUTTON_PREFIX, ticket.id)).label("Close").style(ButtonStyle::Danger)
+        })
+    });
+    components
+}
+
+pub async fn ticket_for_channel(ctx: &Context, guild_id: GuildId, channel_id: ChannelId) -> Option<Ticket> {
+    let store = store::get::<Tickets>(ctx).await;
+    let all = store.read().await;
+    all.get(&guild_id)?.open.iter().find(|ticket| ticket.channel_id == channel_id).cloned()
+}
+
+async fn get(ctx: &Context, guild_id: GuildId, id: u32) -> Option<Ticket> {
+    let store = store::get::<Tickets>(ctx).await;
+    let all = store.read().await;
+    all.get(&guild_id)?.open.iter().find(|ticket| ticket.id == id).cloned()
+}
+
+async fn modify(ctx: &Context, guild_id: GuildId, id: u32, f: impl FnOnce(&mut Ticket)) -> anyhow::Result<Option<Ticket>> {
+    let store = store::get::<Tickets>(ctx).await;
+    store
+        .write(|all| {
+            let ticket = all.get_mut(&guild_id)?.open.iter_mut().find(|ticket| ticket.id == id)?;
+            f(ticket);
+            Some(ticket.clone())
+        })
+        .await
+}
+
+/// Opens a ticket for `user`, returning an explanation instead if they can't open one.
+async fn open(
+    ctx: &Context,
+    guild_id: GuildId,
+    user: &User,
+    category: &TicketCategory,
+    panel_channel: ChannelId,
+) -> anyhow::Result<Result<Ticket, String>> {
+    let config = settings::get(ctx, guild_id).await.tickets;
+    let _opening = OPENING.lock((guild_id, user.id)).await;
+
+    let store = store::get::<Tickets>(ctx).await;
+    let open_count = store
+        .read()
+        .await
+        .get(&guild_id)
+        .map_or(0, |guild| guild.open.iter().filter(|ticket| ticket.opener == user.id).count());
+    if open_count >= config.max_open as usize {
+        return Ok(Err(format!(
+            "You already have {} open ticket{}. Close one before opening another.",
+            open_count,
+            if open_count == 1 { "" } else { "s" }
+        )));
+    }
+
+    let id = store
+        .write(|all| {
+            let guild = all.entry(guild_id).or_default();
+            guild.next_id += 1;
+            guild.next_id
+        })
+        .await?;
+    let name = format!("ticket-{}-{}", id, user.name);
+
+    let channel = if config.threads {
+        let thread = panel_channel
+            .create_private_thread(&ctx.http, |t| t.name(&name).auto_archive_duration(10080))
+            .await
+            .context("failed to create ticket thread")?;
+        thread.id.add_thread_member(&ctx.http, user.id).await.context("failed to add user to ticket thread")?;
+        thread
+    } else {
+        let bot_id = ctx.cache.current_user_id();
+        let mut overwrites = vec![
+            PermissionOverwrite {
+                allow: Permissions::empty(),
+                deny: Permissions::VIEW_CHANNEL,
+                kind: PermissionOverwriteType::Role(RoleId(guild_id.0)),
+            },
+            PermissionOverwrite {
+                allow: PARTICIPANT_PERMISSIONS | Permissions::MANAGE_CHANNELS,
+                deny: Permissions::empty(),
+                kind: PermissionOverwriteType::Member(bot_id),
+            },
+            PermissionOverwrite {
+                allow: PARTICIPANT_PERMISSIONS,
+                deny: Permissions::empty(),
+                kind: PermissionOverwriteType::Member(user.id),
+            },
+        ];
+        overwrites.extend(config.support_roles.iter().map(|role| PermissionOverwrite {
+            allow: PARTICIPANT_PERMISSIONS,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Role(*role),
+        }));
+
+        guild_id
+            .create_channel(&ctx.http, |c| {
+                c.name(&name)
+                    .kind(ChannelType::Text)
+                    .topic(format!("{} ticket for {} ({})", category.name, user.tag(), user.id))
+                    .permissions(overwrites);
+                if let Some(parent) = config.parent {
+                    c.category(parent);
+                }
+                c
+            })
+            .await
+            .context("failed to create ticket channel")?
+    };
+
+    let ticket = Ticket {
+        id,
+        opener: user.id,
+        category: category.name.clone(),
+        channel_id: channel.id,
+        thread: config.threads,
+        claimed_by: None,
+        participants: Vec::new(),
+        opened_at: Timestamp::now(),
+    };
+    store.write(|all| all.entry(guild_id).or_default().open.push(ticket.clone())).await?;
+
+    let mut embed = CreateEmbed::default();
+    embed.title(format!("Ticket #{}: {}", id, category.name));
+    embed.description(category.description.as_deref().unwrap_or("Describe your issue and support will be with you shortly."));
+    embed.field("Opened By", format!("<@{}>", user.id), true);
+    embed.field("Commands", "`rticket claim`, `rticket add <user>`, `rticket remove <user>`, `rticket close [reason]`", false);
+    embed.color(EMBED_COLOR);
+    embed.timestamp(ticket.opened_at);
+
+    // Mentioning the support roles also pulls their members into a private thread
+    let mentions = std::iter::once(format!("<@{}>", user.id))
+        .chain(config.support_roles.iter().map(|role| format!("<@&{}>", role)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    channel
+        .id
+        .send_message(&ctx.http, |m| m.content(mentions).set_embed(embed).set_components(ticket_buttons(&ticket)))
+        .await?;
+    info!(user_id = u64::from(user.id), "Opened ticket #{} in {}", id, channel.id);
+
+    Ok(Ok(ticket))
+}
+
+/// Claims a ticket for `staff`, or releases it when `staff` is `None`.
+pub async fn set_claim(ctx: &Context, guild_id: GuildId, ticket: &Ticket, staff: Option<UserId>) -> anyhow::Result<()> {
+    modify(ctx, guild_id, ticket.id, |ticket| ticket.claimed_by = staff).await?;
+    let content = match staff {
+        Some(staff) => format!("🙋 <@{}> has claimed this ticket.", staff),
+        None => "This ticket is no longer claimed.".to_string(),
+    };
+    ticket.channel_id.say(&ctx.http, content).await?;
+    Ok(())
+}
+
+/// Gives another member access to a ticket.
+pub async fn add_participant(ctx: &Context, guild_id: GuildId, ticket: &Ticket, user_id: UserId) -> anyhow::Result<()> {
+    if ticket.thread {
+        ticket.channel_id.add_thread_member(&ctx.http, user_id).await?;
+    } else {
+        let overwrite = PermissionOverwrite {
+            allow: PARTICIPANT_PERMISSIONS,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Member(user_id),
+        };
+        ticket.channel_id.create_permission(&ctx.http, &overwrite).await?;
+    }
+    modify(ctx, guild_id, ticket.id, |ticket| {
+        if !ticket.participants.contains(&user_id) {
+            ticket.participants.push(user_id);
+        }
+    })
+    .await?;
+    ticket.channel_id.say(&ctx.http, format!("<@{}> was added to the ticket.", user_id)).await?;
+    Ok(())
+}
+
+/// Takes away a member's access to a ticket. The opener can't be removed.
+pub async fn remove_participant(ctx: &Context, guild_id: GuildId, ticket: &Ticket, user_id: UserId) -> anyhow::Result<()> {
+    if user_id == ticket.opener {
+        bail!("the ticket opener can't be removed");
+    }
+    if ticket.thread {
+        ticket.channel_id.remove_thread_member(&ctx.http, user_id).await?;
+    } else {
+        ticket.channel_id.delete_permission(&ctx.http, PermissionOverwriteType::Member(user_id)).await?;
+    }
+    modify(ctx, guild_id, ticket.id, |ticket| ticket.participants.retain(|user| *user != user_id)).await?;
+    ticket.channel_id.say(&ctx.http, format!("<@{}> was removed from the ticket.", user_id)).await?;
+    Ok(())
+}
+
+/// Closes a ticket: uploads text and HTML transcripts to the log channel, DMs them to the
+/// opener, and deletes the channel or thread.
+pub async fn close(ctx: &Context, guild_id: GuildId, ticket: &Ticket, closer: &User, reason: &str) -> anyhow::Result<()> {
+    let messages = transcript::history(ctx, ticket.channel_id).await?;
+    let title = format!("Ticket #{} ({}) opened by {}", ticket.id, ticket.category, ticket.opener);
+    let header = format!(
+        "{}\nOpened: {}\nClosed: {} by {}\nReason: {}\n\n",
+        title,
+        ticket.opened_at,
+        Timestamp::now(),
+        closer.tag(),
+        reason
+    );
+    let text = header + &transcript::render_text(&messages);
+    let html = transcript::render_html(&title, &messages);
+
+    let dir = store::data_dir().join("tickets");
+    tokio::fs::create_dir_all(&dir).await.with_context(|| format!("failed to create {}", dir.display()))?;
+    let basename = format!("ticket-{}-{}", guild_id, ticket.id);
+    tokio::fs::write(dir.join(format!("{}.txt", basename)), &text).await.context("failed to save ticket transcript")?;
+    tokio::fs::write(dir.join(format!("{}.html", basename)), &html).await.context("failed to save ticket transcript")?;
+
+    let store = store::get::<Tickets>(ctx).await;
+    store
+        .write(|all| {
+            if let Some(guild) = all.get_mut(&guild_id) {
+                guild.open.retain(|open| open.id != ticket.id);
+            }
+        })
+        .await?;
+
+    let files = || {
+        [
+            AttachmentType::Bytes { data: Cow::Owned(text.clone().into_bytes()), filename: format!("{}.txt", basename) },
+            AttachmentType::Bytes { data: Cow::Owned(html.clone().into_bytes()), filename: format!("{}.html", basename) },
+        ]
+    };
+
+    let settings = settings::get(ctx, guild_id).await;
+    if let Some(log_channel) = settings.tickets.log_channel.or(settings.mod_log_channel) {
+        let mut embed = CreateEmbed::default();
+        embed.title(format!("Ticket #{} Closed", ticket.id));
+        embed.field("Category", &ticket.category, true);
+        embed.field("Opened By", format!("<@{}>", ticket.opener), true);
+        embed.field("Closed By", closer.tag(), true);
+        if let Some(staff) = ticket.claimed_by {
+            embed.field("Claimed By", format!("<@{}>", staff), true);
+        }
+        embed.field("Reason", truncate(reason, 1000), false);
+        embed.color(EMBED_COLOR);
+        embed.timestamp(Timestamp::now());
+        if let Err(err) = log_channel.send_message(&ctx.http, |m| m.set_embed(embed).add_files(files())).await {
+            error!("Failed to post ticket transcript: {}", err);
+        }
+    }
+
+    let guild_name = guild_id.name(&ctx.cache).unwrap_or_else(|| "the server".to_string());
+    if let Ok(dm) = ticket.opener.create_dm_channel(&ctx.http).await {
+        let content = format!(
+            "Your ticket #{} in **{}** has been closed.\nReason: {}\nA transcript is attached.",
+            ticket.id, guild_name, reason
+        );
+        let _ = dm.send_message(&ctx.http, |m| m.content(content).add_files(files())).await;
+    }
+
+    ticket.channel_id.delete(&ctx.http).await?;
+    info!(user_id = u64::from(ticket.opener), "Closed ticket #{}: {}", ticket.id, reason);
+
+    Ok(())
+}
+
+/// Forgets a ticket whose channel was deleted by hand instead of with `rticket close`.
+pub async fn on_channel_delete(ctx: &Context, guild_id: GuildId, channel_id: ChannelId) {
+    let store = store::get::<Tickets>(ctx).await;
+    let result = store
+        .write(|all| {
+            if let Some(guild) = all.get_mut(&guild_id) {
+                guild.open.retain(|ticket| ticket.channel_id != channel_id);
+            }
+        })
+        .await;
+    if let Err(err) = result {
+        error!("Failed to forget ticket in {}: {:?}", channel_id, err);
+    }
+}
+
+/// Handles the panel's open buttons and the claim and close buttons inside tickets.
+pub async fn handle_button(ctx: &Context, interaction: &MessageComponentInteraction) {
+    let Some(guild_id) = interaction.guild_id else { return };
+    let Some((action, target)) = interaction.data.custom_id[BUTTON_PREFIX.len()..].split_once(':') else {
+        return;
+    };
+    let config = settings::get(ctx, guild_id).await.tickets;
+
+    if action == "open" {
+        let Some(category) = config.category(target).cloned() else {
+            respond_ephemeral(ctx, interaction, "This ticket category no longer exists.").await;
+            return;
+        };
+        // Creating the channel can take a moment, so acknowledge the click first
+        let deferred = interaction
+            .create_interaction_response(&ctx.http, |r| {
+                r.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                    .interaction_response_data(|d| d.ephemeral(true))
+            })
+            .await;
+        if let Err(err) = deferred {
+            error!("Failed to acknowledge ticket button: {}", err);
+            return;
+        }
+        let content = match open(ctx, guild_id, &interaction.user, &category, interaction.channel_id).await {
+            Ok(Ok(ticket)) => format!("Your ticket has been opened: <#{}>", ticket.channel_id),
+            Ok(Err(refusal)) => refusal,
+            Err(err) => {
+                error!("Failed to open ticket for {}: {:?}", interaction.user.id, err);
+                "Failed to open a ticket. Please contact a moderator.".to_string()
+            }
+        };
+        if let Err(err) = interaction.edit_original_interaction_response(&ctx.http, |r| r.content(content)).await {
+            error!("Failed to respond to ticket button: {}", err);
+        }
+        return;
+    }
+
+    let Ok(id) = target.parse::<u32>() else { return };
+    let Some(ticket) = get(ctx, guild_id, id).await else {
+        respond_ephemeral(ctx, interaction, "This ticket is already closed.").await;
+        return;
+    };
+    let is_staff = interaction
+        .member
+        .as_ref()
+        .is_some_and(|member| config.is_staff(&member.roles, member.permissions.unwrap_or_default()));
+
+    match action {
+        "claim" => {
+            if !is_staff {
+                respond_ephemeral(ctx, interaction, "Only support staff can claim tickets.").await;
+                return;
+            }
+            let staff = match ticket.claimed_by {
+                Some(claimer) if claimer == interaction.user.id => None,
+                Some(claimer) => {
+                    respond_ephemeral(ctx, interaction, &format!("This ticket is already claimed by <@{}>.", claimer)).await;
+                    return;
+                }
+                None => Some(interaction.user.id),
+            };
+            respond_ephemeral(ctx, interaction, if staff.is_some() { "Ticket claimed." } else { "Ticket unclaimed." }).await;
+            if let Err(err) = set_claim(ctx, guild_id, &ticket, staff).await {
+                error!("Failed to update claim on ticket #{}: {:?}", id, err);
+            }
+        }
+        "close" => {
+            if !is_staff && interaction.user.id != ticket.opener {
+                respond_ephemeral(ctx, interaction, "Only support staff or the ticket opener can close this ticket.").await;
+                return;
+            }
+            respond_ephemeral(ctx, interaction, "Closing ticket...").await;
+            if let Err(err) = close(ctx, guild_id, &ticket, &interaction.user, "Closed with the button").await {
+                error!("Failed to close ticket #{}: {:?}", id, err);
+            }
+        }
+        _ => {}
+    }
+}
+
+async fn respond_ephemeral(ctx: &Context, interaction: &MessageComponentInteraction, content: &str) {
+    let result = interaction
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.content(content).ephemeral(true))
+        })
+        .await;
+    if let Err(err) = result {
+        error!("Failed to respond to interaction: {}", err);
+    }
+}
@@ -0,0 +1,195 @@
+use std::fmt::Write as _;
+
+use serenity::client::Context;
+use serenity::model::prelude::{ChannelId, Message};
+
+/// Fetches every message in a channel, oldest first.
+pub async fn history(ctx: &Context, channel_id: ChannelId) -> serenity::Result<Vec<Message>> {
+    let mut messages = Vec::new();
+    let mut before = None;
+    loop {
+        let page = channel_id
+            .messages(&ctx.http, |retriever| {
+                if let Some(before) = before {
+                    retriever.before(before);
+                }
+                retriever.limit(100)
+            })
+            .await?;
+        let Some(last) = page.last() else { break };
+        before = Some(last.id);
+        let done = page.len() < 100;
+        messages.extend(page);
+        if done {
+            break;
+        }
+    }
+    messages.reverse();
+    Ok(messages)
+}
+
+/// Renders messages as plain text, including embeds and attachment links.
+pub fn render_text(messages: &[Message]) -> String {
+    let mut text = String::new();
+    for message in messages {
+        let _ = write!(text, "[{}] {}", message.timestamp, message.author.tag());
+        if !message.content.is_empty() {
+            let _ = write!(text, ": {}", message.content);
+        }
+        text.push('\n');
+        for embed in &message.embeds {
+            let author = embed.author.as_ref().map_or("", |a| a.name.as_str());
+            let _ = writeln!(text, "    [{}] {}", author, embed.description.as_deref().unwrap_or(""));
+            for field in &embed.fields {
+                let _ = writeln!(text, "    {}: {}", field.name, field.value);
+            }
+            if let Some(footer) = &embed.footer {
+                let _ = writeln!(text, "    ({})", footer.text);
+            }
+        }
+        for attachment in &message.attachments {
+            let _ = writeln!(text, "    attachment: {}", attachment.url);
+        }
+    }
+    text
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders messages as a standalone HTML page that can be opened in a browser.
+pub fn render_html(title: &str, messages: &[Message]) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n\
+         body {{ background: #313338; color: #dbdee1; font-family: sans-serif; margin: 2em; }}\n\
+         .message {{ display: flex; gap: 12px; margin-bottom: 16px; }}\n\
+         .avatar {{ width: 40px; height: 40px; border-radius: 50%; }}\n\
+         .author {{ font-weight: bold; color: #f2f3f5; }}\n\
+         .time {{ color: #949ba4; font-size: 0.8em; margin-left: 6px; }}\n\
+         .content {{ white-space: pre-wrap; }}\n\
+         .embed {{ border-left: 4px solid #ffa500; background: #2b2d31; padding: 6px 10px; margin-top: 4px; white-space: pre-wrap; }}\n\
+         a {{ color: #00a8fc; }}\n\
+         </style>\n</head>\n<body>\n<h1>{title}</h1>\n",
+        title = escape_html(title)
+    );
+
+    for message in messages {
+        let _ = write!(
+            html,
+            "<div class=\"message\">\n<img class=\"avatar\" src=\"{}\" alt=\"\">\n<div>\n\
+             <span class=\"author\">{}</span><span class=\"time\">{}</span>\n",
+            escape_html(&message.author.face()),
+            escape_html(&message.author.tag()),
+            message.timestamp
+        );
+        if !message.content.is_empty() {
+            let _ = writeln!(html, "<div class=\"content\">{}</div>", escape_html(&message.content));
+        }
+        for embed in &message.embeds {
+            let mut body = String::new();
+            if let Some(author) = &embed.author {
+                let _ = writeln!(body, "<b>{}</b>", escape_html(&author.name));
+            }
+            if let Some(title) = &embed.title {
+                let _ = writeln!(body, "<b>{}</b>", escape_html(title));
+            }
+            if let Some(description) = &embed.description {
+                let _ = writeln!(body, "{}", escape_html(description));
+            }
+            for field in &embed.fields {
+                let _ = writeln!(body, "<b>{}</b>: {}", escape_html(&field.name), escape_html(&field.value));
+            }
+            if let Some(footer) = &embed.footer {
+                let _ = writeln!(body, "<i>{}</i>", escape_html(&footer.text));
+            }
+            let _ = writeln!(html, "<div class=\"embed\">{}</div>", body.trim_end());
+        }
+        for attachment in &message.attachments {
+            let _ = writeln!(
+                html,
+                "<div><a href=\"{}\">{}</a></div>",
+                escape_html(&attachment.url),
+                escape_html(&attachment.filename)
+            );
+        }
+        html.push_str("</div>\n</div>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn message(id: u64, author: &str, content: &str, embeds: serde_json::Value) -> Message {
+        serde_json::from_value(json!({
+            "id": id.to_string(),
+            "channel_id": "10",
+            "author": { "id": "20", "username": author, "discriminator": "0001", "avatar": null },
+            "content": content,
+            "timestamp": format!("2024-01-01T00:00:0{}+00:00", id),
+            "edited_timestamp": null,
+            "tts": false,
+            "mention_everyone": false,
+            "mentions": [],
+            "mention_roles": [],
+            "attachments": [],
+            "embeds": embeds,
+            "pinned": false,
+            "type": 0,
+        }))
+        .expect("test message should deserialize")
+    }
+
+    #[test]
+    fn render_text_keeps_order_and_embeds() {
+        let messages = [
+            message(1, "alice", "first", json!([])),
+            message(2, "bob", "", json!([{ "description": "embedded", "fields": [{ "name": "Key", "value": "Value", "inline": false }] }])),
+            message(3, "alice", "third", json!([])),
+        ];
+        let text = render_text(&messages);
+
+        let first = text.find("alice#0001: first").expect("first message should be rendered");
+        let second = text.find("bob#0001").expect("second message should be rendered");
+        let third = text.find("alice#0001: third").expect("third message should be rendered");
+        assert!(first < second && second < third);
+        assert!(text.contains("    [] embedded\n"));
+        assert!(text.contains("    Key: Value\n"));
+    }
+
+    #[test]
+    fn render_html_escapes_user_content() {
+        let messages = [
+            message(1, "<b>alice</b>", "<script>alert('hi')</script> & \"more\"", json!([])),
+            message(2, "bob", "second", json!([{ "title": "<i>title</i>" }])),
+        ];
+        let html = render_html("Ticket <#1>", &messages);
+
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("<b>alice</b>"));
+        assert!(html.contains("&lt;script&gt;alert(&#39;hi&#39;)&lt;/script&gt; &amp; &quot;more&quot;"));
+        assert!(html.contains("&lt;b&gt;alice&lt;/b&gt;#0001"));
+        assert!(html.contains("<b>&lt;i&gt;title&lt;/i&gt;</b>"));
+        assert!(html.contains("<title>Ticket &lt;#1&gt;</title>"));
+        assert!(html.find("&lt;script&gt;").unwrap() < html.find("second").unwrap());
+    }
+}
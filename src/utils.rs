@@ -0,0 +1,172 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serenity::model::channel::{Attachment, AttachmentType};
+use tokio::sync::OwnedMutexGuard;
+
+/// Attachments larger than this are linked instead of re-uploaded.
+const MAX_REUPLOAD_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Shortens `text` to at most `max_chars` characters, marking the cut with an ellipsis.
+pub fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((idx, _)) => format!("{}…", &text[..idx]),
+        None => text.to_string(),
+    }
+}
+
+/// Number of single-character insertions, deletions or substitutions needed to turn `a` into `b`.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Parses durations like `30s`, `5m`, `1h30m` or `2d`. A bare number is taken as seconds.
+pub fn parse_duration(input: &str) -> Option<Duration> {
+    let input = input.trim().to_lowercase();
+    if let Ok(secs) = input.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return None,
+        };
+        let value = number.parse::<u64>().ok()?;
+        total = total.checked_add(value.checked_mul(unit)?)?;
+        number.clear();
+    }
+
+    if !number.is_empty() || input.is_empty() {
+        return None;
+    }
+    Some(Duration::from_secs(total))
+}
+
+/// Formats a duration as e.g. `1h 30m` or `45s`.
+pub fn format_duration(duration: Duration) -> String {
+    let mut secs = duration.as_secs();
+    if secs == 0 {
+        return "0s".to_string();
+    }
+
+    let mut parts = Vec::new();
+    for (unit, name) in [(24 * 60 * 60, "d"), (60 * 60, "h"), (60, "m"), (1, "s")] {
+        if secs >= unit {
+            parts.push(format!("{}{}", secs / unit, name));
+            secs %= unit;
+        }
+    }
+    parts.join(" ")
+}
+
+/// Downloads attachments so they can be re-uploaded somewhere else, such as a DM or a report
+/// channel. Returns the files and links for any that were too large or failed to download.
+pub async fn download_attachments(attachments: &[Attachment]) -> (Vec<AttachmentType<'static>>, Vec<String>) {
+    let mut files = Vec::new();
+    let mut links = Vec::new();
+    for attachment in attachments {
+        if attachment.size > MAX_REUPLOAD_BYTES {
+            links.push(attachment.url.clone());
+            continue;
+        }
+        match attachment.download().await {
+            Ok(data) => files.push(AttachmentType::Bytes { data: Cow::Owned(data), filename: attachment.filename.clone() }),
+            Err(err) => {
+                warn!("Failed to download attachment {}: {}", attachment.url, err);
+                links.push(attachment.url.clone());
+            }
+        }
+    }
+    (files, links)
+}
+
+/// Async locks keyed by value, so that work for one key never waits on another.
+pub struct KeyedLocks<K> {
+    locks: Mutex<HashMap<K, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl<K> Default for KeyedLocks<K> {
+    fn default() -> Self {
+        Self { locks: Mutex::new(HashMap::new()) }
+    }
+}
+
+impl<K: Eq + Hash> KeyedLocks<K> {
+    /// Waits for the lock for `key`. Locks nobody holds or waits on are dropped along the way.
+    pub async fn lock(&self, key: K) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().expect("keyed lock map shouldn't be poisoned");
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks.entry(key).or_default().clone()
+        };
+        lock.lock_owned().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levenshtein_counts_edits() {
+        assert_eq!(levenshtein("discord", "discord"), 0);
+        assert_eq!(levenshtein("dlscord", "discord"), 1);
+        assert_eq!(levenshtein("dicord", "discord"), 1);
+        assert_eq!(levenshtein("steam", "stream"), 1);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn parse_duration_accepts_units_and_bare_seconds() {
+        assert_eq!(parse_duration("90"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("5m"), Some(Duration::from_secs(300)));
+        assert_eq!(parse_duration(" 1H30m "), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("2d"), Some(Duration::from_secs(172_800)));
+        assert_eq!(parse_duration("1w"), Some(Duration::from_secs(604_800)));
+    }
+
+    #[test]
+    fn parse_duration_rejects_malformed_input() {
+        for input in ["", "m", "5x", "1h30", "1.5h", "-5m", "99999999999999999999w"] {
+            assert_eq!(parse_duration(input), None, "{:?} should be rejected", input);
+        }
+    }
+
+    #[test]
+    fn format_duration_splits_into_units() {
+        assert_eq!(format_duration(Duration::ZERO), "0s");
+        assert_eq!(format_duration(Duration::from_secs(45)), "45s");
+        assert_eq!(format_duration(Duration::from_secs(5400)), "1h 30m");
+        assert_eq!(format_duration(Duration::from_secs(90_061)), "1d 1h 1m 1s");
+        assert_eq!(format_duration(parse_duration("6h").unwrap()), "6h");
+    }
+}